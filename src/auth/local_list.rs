@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::auth::{AuthorizationStatus, IdToken};
+
+pub const DEFAULT_LOCAL_AUTH_PATH: &str = "data/local_auth.json";
+
+// How long an accepted token is trusted offline when the backend gave no expiry
+const CACHE_LIFETIME: chrono::Duration = chrono::Duration::days(7);
+const MAX_CACHE_ENTRIES: usize = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LocalAuthError {
+    #[error("differential update to version {received} does not follow local list version {current}")]
+    VersionMismatch { current: i32, received: i32 },
+    #[error("cannot save local authorization: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot encode local authorization: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// What the backend said about a token, and until when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdTagInfo {
+    pub status: AuthorizationStatus,
+    pub expiry: Option<DateTime<Utc>>,
+}
+
+impl IdTagInfo {
+    pub fn status_at(&self, now: DateTime<Utc>) -> AuthorizationStatus {
+        match self.expiry {
+            Some(expiry) if self.status == AuthorizationStatus::Accepted && expiry <= now => AuthorizationStatus::Expired,
+            _ => self.status,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateType {
+    Full,
+    Differential,
+}
+
+/// Tokens the kiosk can decide on without the backend: the local authorization
+/// list maintained by the central system (SendLocalList) and a cache of tokens
+/// it recently accepted. The list always wins over the cache.
+#[derive(Debug, Default, Deserialize)]
+pub struct LocalAuthorization {
+    list_version: i32,
+    list: HashMap<IdToken, IdTagInfo>,
+    cache: HashMap<IdToken, IdTagInfo>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+pub type SharedLocalAuth = Arc<Mutex<LocalAuthorization>>;
+
+impl LocalAuthorization {
+    /// Saved list and cache from `path`; starts empty if there are none yet
+    pub fn load(path: &Path) -> LocalAuthorization {
+        let mut local: LocalAuthorization = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                println!("Ignoring invalid local authorization file {}: {}", path.display(), err);
+                LocalAuthorization::default()
+            }),
+            Err(_) => LocalAuthorization::default(),
+        };
+        local.path = Some(path.to_path_buf());
+        local
+    }
+
+    pub fn list_version(&self) -> i32 {
+        self.list_version
+    }
+
+    /// Apply a list sent by the central system. A full update replaces the list;
+    /// a differential one adds or updates entries and removes those without info.
+    pub fn update_list(
+        &mut self,
+        version: i32,
+        update: UpdateType,
+        entries: Vec<(IdToken, Option<IdTagInfo>)>,
+    ) -> Result<(), LocalAuthError> {
+        // Built in a copy and saved first, so a failed save leaves the old list and version in place
+        let list = match update {
+            UpdateType::Full => entries.into_iter().filter_map(|(token, info)| Some((token, info?))).collect(),
+            UpdateType::Differential => {
+                if version <= self.list_version {
+                    return Err(LocalAuthError::VersionMismatch { current: self.list_version, received: version });
+                }
+                let mut list = self.list.clone();
+                for (token, info) in entries {
+                    match info {
+                        Some(info) => list.insert(token, info),
+                        None => list.remove(&token),
+                    };
+                }
+                list
+            }
+        };
+        self.write(&Saved { list_version: version, list: &list, cache: &self.cache })?;
+        self.list = list;
+        self.list_version = version;
+        Ok(())
+    }
+
+    /// Remember the backend's answer for a token so it can be decided offline next time
+    pub fn remember(&mut self, token: IdToken, info: IdTagInfo, now: DateTime<Utc>) {
+        if info.status_at(now) != AuthorizationStatus::Accepted {
+            self.cache.remove(&token);
+        } else {
+            let expiry = info.expiry.unwrap_or(now + CACHE_LIFETIME);
+            self.cache.insert(token, IdTagInfo { status: info.status, expiry: Some(expiry) });
+            self.evict(now);
+        }
+        if let Err(err) = self.save() {
+            println!("{}", err);
+        }
+    }
+
+    /// Offline decision for a token: the local list first, then the cache
+    pub fn authorize_offline(&self, token: &IdToken, now: DateTime<Utc>) -> AuthorizationStatus {
+        self.list
+            .get(token)
+            .or_else(|| self.cache.get(token))
+            .map_or(AuthorizationStatus::Invalid, |info| info.status_at(now))
+    }
+
+    // Drop expired entries, then the ones closest to expiring, until the cache fits
+    fn evict(&mut self, now: DateTime<Utc>) {
+        self.cache.retain(|_, info| info.status_at(now) == AuthorizationStatus::Accepted);
+        while self.cache.len() > MAX_CACHE_ENTRIES {
+            let Some(oldest) = self.cache.iter().min_by_key(|(_, info)| info.expiry).map(|(token, _)| token.clone()) else {
+                break;
+            };
+            self.cache.remove(&oldest);
+        }
+    }
+
+    fn save(&self) -> Result<(), LocalAuthError> {
+        self.write(&Saved { list_version: self.list_version, list: &self.list, cache: &self.cache })
+    }
+
+    fn write(&self, saved: &Saved) -> Result<(), LocalAuthError> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write a sibling file and rename so a power loss never leaves half a list
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec_pretty(saved)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+/// On-disk form of `LocalAuthorization`, borrowing the parts to save
+#[derive(Serialize)]
+struct Saved<'a> {
+    list_version: i32,
+    list: &'a HashMap<IdToken, IdTagInfo>,
+    cache: &'a HashMap<IdToken, IdTagInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted() -> Option<IdTagInfo> {
+        Some(IdTagInfo { status: AuthorizationStatus::Accepted, expiry: None })
+    }
+
+    #[test]
+    fn differential_updates_follow_the_full_list() {
+        let mut local = LocalAuthorization::default();
+        let now = Utc::now();
+        let (card, other) = (IdToken::new("04A1B2C3"), IdToken::new("DEADBEEF"));
+
+        local.update_list(3, UpdateType::Full, vec![(card.clone(), accepted()), (other.clone(), accepted())]).unwrap();
+        assert_eq!(local.authorize_offline(&other, now), AuthorizationStatus::Accepted);
+
+        let blocked = IdTagInfo { status: AuthorizationStatus::Blocked, expiry: None };
+        local.update_list(4, UpdateType::Differential, vec![(card.clone(), Some(blocked)), (other.clone(), None)]).unwrap();
+        assert_eq!(local.authorize_offline(&card, now), AuthorizationStatus::Blocked);
+        assert_eq!(local.authorize_offline(&other, now), AuthorizationStatus::Invalid);
+
+        assert!(matches!(
+            local.update_list(4, UpdateType::Differential, Vec::new()),
+            Err(LocalAuthError::VersionMismatch { current: 4, received: 4 })
+        ));
+        assert_eq!(local.list_version(), 4);
+    }
+
+    #[test]
+    fn list_is_unchanged_when_it_cannot_be_saved() {
+        // A file where the data directory should be makes every save fail
+        let blocker = std::env::temp_dir().join(format!("ev-charger-auth-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&blocker, "").unwrap();
+        let mut local = LocalAuthorization::default();
+        let card = IdToken::new("04A1B2C3");
+        local.update_list(3, UpdateType::Full, vec![(card.clone(), accepted())]).unwrap();
+        local.path = Some(blocker.join("local_auth.json"));
+
+        let blocked = IdTagInfo { status: AuthorizationStatus::Blocked, expiry: None };
+        assert!(matches!(
+            local.update_list(4, UpdateType::Differential, vec![(card.clone(), Some(blocked))]),
+            Err(LocalAuthError::Io(_))
+        ));
+        assert!(local.update_list(5, UpdateType::Full, Vec::new()).is_err());
+        assert_eq!(local.list_version(), 3);
+        assert_eq!(local.authorize_offline(&card, Utc::now()), AuthorizationStatus::Accepted);
+
+        std::fs::remove_file(&blocker).unwrap();
+    }
+
+    #[test]
+    fn cached_tokens_expire_and_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("ev-charger-auth-{}.json", uuid::Uuid::new_v4()));
+        let now = Utc::now();
+        let (card, rejected) = (IdToken::new("04a1b2c3"), IdToken::new("CAFEBABE"));
+
+        let mut local = LocalAuthorization::load(&path);
+        local.remember(card.clone(), IdTagInfo { status: AuthorizationStatus::Accepted, expiry: Some(now + chrono::Duration::hours(1)) }, now);
+        local.remember(rejected.clone(), IdTagInfo { status: AuthorizationStatus::Invalid, expiry: None }, now);
+
+        let reloaded = LocalAuthorization::load(&path);
+        assert_eq!(reloaded.authorize_offline(&IdToken::new("04A1B2C3"), now), AuthorizationStatus::Accepted);
+        assert_eq!(reloaded.authorize_offline(&card, now + chrono::Duration::hours(2)), AuthorizationStatus::Expired);
+        assert_eq!(reloaded.authorize_offline(&rejected, now), AuthorizationStatus::Invalid);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
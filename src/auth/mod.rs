@@ -0,0 +1,257 @@
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+pub mod local_list;
+pub mod wedge;
+
+use local_list::SharedLocalAuth;
+use crate::membership::SharedMembers;
+use crate::ocpp::{ChargerEvent, OcppHandle, SharedBackendLink};
+
+// How long a card read stays on screen
+const CARD_NOTICE_DURATION: Duration = Duration::from_secs(3);
+// How long a card waits for the central system before it is decided offline
+const ONLINE_AUTHORIZE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Identifier read from an RFID card, normalised to upper case
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct IdToken(String);
+
+impl IdToken {
+    pub fn new(raw: &str) -> Self {
+        Self(raw.trim().to_ascii_uppercase())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for IdToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Answer to an authorization request, named after the OCPP id tag statuses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthorizationStatus {
+    Accepted,
+    Blocked,
+    Expired,
+    Invalid,
+}
+
+/// Decides whether an id token may start a session. Asking the central
+/// system takes a round trip, so the answer comes back as a request the UI
+/// polls each frame.
+pub trait Authorizer: Send {
+    fn authorize(&mut self, token: &IdToken) -> AuthorizeRequest;
+}
+
+/// A card waiting for its authorization status
+pub struct AuthorizeRequest {
+    pub token: IdToken,
+    pending: Pending,
+}
+
+enum Pending {
+    Decided(AuthorizationStatus),
+    // Waiting for the central system; decided from the local list if it drops or times out
+    Online { link: SharedBackendLink, local: SharedLocalAuth, deadline: Instant },
+}
+
+impl AuthorizeRequest {
+    pub fn decided(token: IdToken, status: AuthorizationStatus) -> Self {
+        Self { token, pending: Pending::Decided(status) }
+    }
+
+    /// The card's status, once it is known
+    pub fn poll(&mut self) -> Option<AuthorizationStatus> {
+        let status = match self.pending {
+            Pending::Decided(status) => status,
+            Pending::Online { ref link, ref local, deadline } => {
+                let mut link = link.lock().unwrap();
+                if let Some(status) = link.take_answer(&self.token) {
+                    status
+                } else if link.is_connected() && Instant::now() < deadline {
+                    return None;
+                } else {
+                    println!("No answer from the central system for card {}, deciding offline", self.token);
+                    local.lock().unwrap().authorize_offline(&self.token, chrono::Utc::now())
+                }
+            }
+        };
+        self.pending = Pending::Decided(status);
+        Some(status)
+    }
+}
+
+/// Accepts the kiosk's own member cards, asks the central system about any
+/// other card while it is connected, and otherwise decides from the local
+/// authorization list and the cache of accepted tokens, so cards keep working
+/// while the backend is unreachable
+pub struct LocalAuthorizer {
+    local: SharedLocalAuth,
+    members: SharedMembers,
+    backend: Option<OcppHandle>,
+}
+
+impl LocalAuthorizer {
+    pub fn new(local: SharedLocalAuth, members: SharedMembers) -> Self {
+        Self { local, members, backend: None }
+    }
+
+    pub fn with_backend(mut self, backend: OcppHandle) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Ask the central system about `token`, or `None` if it is offline.
+    /// The protocol session also caches the answer for offline use.
+    fn authorize_online(&self, token: &IdToken) -> Option<AuthorizeRequest> {
+        let backend = self.backend.as_ref()?;
+        {
+            let mut link = backend.link().lock().unwrap();
+            if !link.is_connected() {
+                return None;
+            }
+            link.take_answer(token);
+        }
+        backend.send(ChargerEvent::Authorize { id_tag: token.to_string() });
+        let pending = Pending::Online {
+            link: backend.link().clone(),
+            local: self.local.clone(),
+            deadline: Instant::now() + ONLINE_AUTHORIZE_TIMEOUT,
+        };
+        Some(AuthorizeRequest { token: token.clone(), pending })
+    }
+}
+
+impl Authorizer for LocalAuthorizer {
+    fn authorize(&mut self, token: &IdToken) -> AuthorizeRequest {
+        if self.members.lock().unwrap().find_by_token(token).is_some() {
+            return AuthorizeRequest::decided(token.clone(), AuthorizationStatus::Accepted);
+        }
+        if let Some(request) = self.authorize_online(token) {
+            return request;
+        }
+        let status = self.local.lock().unwrap().authorize_offline(token, chrono::Utc::now());
+        AuthorizeRequest::decided(token.clone(), status)
+    }
+}
+
+pub fn create_authorizer(local: SharedLocalAuth, members: SharedMembers, backend: Option<OcppHandle>) -> Box<dyn Authorizer> {
+    let authorizer = LocalAuthorizer::new(local, members);
+    Box::new(match backend {
+        Some(backend) => authorizer.with_backend(backend),
+        None => authorizer,
+    })
+}
+
+/// A card presented at the reader, kept briefly for on-screen feedback
+#[derive(Debug, Clone)]
+pub struct CardRead {
+    pub token: IdToken,
+    pub status: AuthorizationStatus,
+    read_at: Instant,
+}
+
+impl CardRead {
+    pub fn new(token: IdToken, status: AuthorizationStatus) -> Self {
+        Self { token, status, read_at: Instant::now() }
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.status == AuthorizationStatus::Accepted
+    }
+
+    pub fn is_recent(&self) -> bool {
+        self.read_at.elapsed() < CARD_NOTICE_DURATION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use local_list::LocalAuthorization;
+    use crate::membership::{MemberStore, DEFAULT_MEMBERS_SEED_PATH};
+    use crate::ocpp::{OcppConfig, OcppVersion};
+
+    fn demo_members() -> SharedMembers {
+        let path = std::env::temp_dir().join(format!("ev-charger-members-{}.json", uuid::Uuid::new_v4()));
+        Arc::new(Mutex::new(MemberStore::load(&path, Path::new(DEFAULT_MEMBERS_SEED_PATH))))
+    }
+
+    #[test]
+    fn member_card_is_accepted_without_a_central_system() {
+        let local = Arc::new(Mutex::new(LocalAuthorization::default()));
+        let mut authorizer = LocalAuthorizer::new(local, demo_members());
+        assert_eq!(authorizer.authorize(&IdToken::new("04a1b2c3")).poll(), Some(AuthorizationStatus::Accepted));
+        assert_eq!(authorizer.authorize(&IdToken::new("CAFEBABE")).poll(), Some(AuthorizationStatus::Invalid));
+    }
+
+    #[test]
+    fn card_waits_for_the_central_system_until_it_answers_or_drops() {
+        let local = Arc::new(Mutex::new(LocalAuthorization::default()));
+        let link: SharedBackendLink = Arc::default();
+        link.lock().unwrap().set_connected(true);
+        let waiting = |token: &IdToken| AuthorizeRequest {
+            token: token.clone(),
+            pending: Pending::Online { link: link.clone(), local: local.clone(), deadline: Instant::now() + Duration::from_secs(60) },
+        };
+
+        let answered = IdToken::new("CAFEBABE");
+        let mut request = waiting(&answered);
+        assert_eq!(request.poll(), None);
+        link.lock().unwrap().answer(answered, AuthorizationStatus::Blocked);
+        assert_eq!(request.poll(), Some(AuthorizationStatus::Blocked));
+
+        // Unknown to the empty local list once the connection is gone
+        let mut request = waiting(&IdToken::new("DEADBEEF"));
+        assert_eq!(request.poll(), None);
+        link.lock().unwrap().set_connected(false);
+        assert_eq!(request.poll(), Some(AuthorizationStatus::Invalid));
+    }
+
+    #[test]
+    fn unknown_card_is_asked_online_and_then_works_offline() {
+        let local = Arc::new(Mutex::new(LocalAuthorization::default()));
+        let config = OcppConfig {
+            csms_url: "mock".to_string(),
+            version: OcppVersion::V16,
+            charge_point_id: "TEST-01".to_string(),
+            connector_id: 1,
+            vendor: "test".to_string(),
+            model: "test".to_string(),
+            meter_interval: Duration::from_secs(60),
+            call_timeout: Duration::from_secs(30),
+        };
+        let backend = crate::ocpp::spawn(config, local.clone());
+        for _ in 0..250 {
+            if backend.link().lock().unwrap().is_connected() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(backend.link().lock().unwrap().is_connected());
+
+        // The mock central system accepts every card
+        let card = IdToken::new("CAFEBABE");
+        let mut authorizer = LocalAuthorizer::new(local.clone(), demo_members()).with_backend(backend);
+        let mut request = authorizer.authorize(&card);
+        let mut status = request.poll();
+        for _ in 0..250 {
+            if status.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+            status = request.poll();
+        }
+        assert_eq!(status, Some(AuthorizationStatus::Accepted));
+        assert_eq!(local.lock().unwrap().authorize_offline(&card, chrono::Utc::now()), AuthorizationStatus::Accepted);
+    }
+}
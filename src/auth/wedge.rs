@@ -0,0 +1,146 @@
+use std::time::{Duration, Instant};
+use eframe::egui;
+use crate::auth::IdToken;
+
+// Readers type a whole card in a few milliseconds per key; people are far slower
+const MAX_KEY_GAP: Duration = Duration::from_millis(50);
+const MIN_TOKEN_LEN: usize = 4;
+const MAX_TOKEN_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+enum WedgeOutput {
+    Token(IdToken),
+    Typed(String),
+}
+
+/// RFID reader that acts as a USB keyboard. Text arriving in a fast burst and
+/// ending in Enter is taken out of the egui input and returned as a token;
+/// anything slower is handed back to the widgets a moment later, so typing in
+/// text fields keeps working.
+#[derive(Default)]
+pub struct KeyboardWedge {
+    buffer: String,
+    last_key_at: Option<Instant>,
+}
+
+impl KeyboardWedge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Card reads from this frame's input. Call before any screen reads the input.
+    pub fn capture(&mut self, ctx: &egui::Context) -> Vec<IdToken> {
+        let now = Instant::now();
+        let mut tokens = Vec::new();
+
+        ctx.input_mut(|input| {
+            let mut events = Vec::with_capacity(input.events.len());
+            let mut outputs = Vec::new();
+            for event in input.events.drain(..) {
+                match event {
+                    egui::Event::Text(ref text) => self.key(text, now, &mut outputs),
+                    egui::Event::Key { key: egui::Key::Enter, pressed: true, .. } if !self.buffer.is_empty() => {
+                        if !self.enter(now, &mut outputs) {
+                            Self::release(&mut outputs, &mut events, &mut tokens);
+                            events.push(event);
+                            continue;
+                        }
+                    }
+                    _ => {
+                        Self::release(&mut outputs, &mut events, &mut tokens);
+                        events.push(event);
+                        continue;
+                    }
+                }
+                Self::release(&mut outputs, &mut events, &mut tokens);
+            }
+            self.expire(now, &mut outputs);
+            Self::release(&mut outputs, &mut events, &mut tokens);
+            input.events = events;
+        });
+
+        // Come back to hand held keystrokes on if no more arrive
+        if !self.buffer.is_empty() {
+            ctx.request_repaint_after(MAX_KEY_GAP);
+        }
+        tokens
+    }
+
+    fn release(outputs: &mut Vec<WedgeOutput>, events: &mut Vec<egui::Event>, tokens: &mut Vec<IdToken>) {
+        for output in outputs.drain(..) {
+            match output {
+                WedgeOutput::Token(token) => tokens.push(token),
+                WedgeOutput::Typed(text) => events.push(egui::Event::Text(text)),
+            }
+        }
+    }
+
+    fn key(&mut self, text: &str, at: Instant, outputs: &mut Vec<WedgeOutput>) {
+        self.expire(at, outputs);
+        self.buffer.push_str(text);
+        self.last_key_at = Some(at);
+        if self.buffer.chars().count() > MAX_TOKEN_LEN {
+            outputs.push(WedgeOutput::Typed(std::mem::take(&mut self.buffer)));
+        }
+    }
+
+    /// Returns whether the Enter key finished a card read and should be swallowed
+    fn enter(&mut self, at: Instant, outputs: &mut Vec<WedgeOutput>) -> bool {
+        self.expire(at, outputs);
+        if self.buffer.chars().count() >= MIN_TOKEN_LEN {
+            outputs.push(WedgeOutput::Token(IdToken::new(&std::mem::take(&mut self.buffer))));
+            true
+        } else {
+            if !self.buffer.is_empty() {
+                outputs.push(WedgeOutput::Typed(std::mem::take(&mut self.buffer)));
+            }
+            false
+        }
+    }
+
+    /// Hand back held keystrokes once the burst has stopped without an Enter
+    fn expire(&mut self, now: Instant, outputs: &mut Vec<WedgeOutput>) {
+        let stale = self.last_key_at.is_some_and(|last| now.duration_since(last) > MAX_KEY_GAP);
+        if stale && !self.buffer.is_empty() {
+            outputs.push(WedgeOutput::Typed(std::mem::take(&mut self.buffer)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_keys(wedge: &mut KeyboardWedge, keys: &str, start: Instant, gap: Duration) -> (Vec<WedgeOutput>, Instant) {
+        let mut outputs = Vec::new();
+        let mut at = start;
+        for key in keys.chars() {
+            wedge.key(&key.to_string(), at, &mut outputs);
+            at += gap;
+        }
+        (outputs, at)
+    }
+
+    #[test]
+    fn fast_burst_ending_in_enter_is_a_card() {
+        let mut wedge = KeyboardWedge::new();
+        let (mut outputs, at) = type_keys(&mut wedge, "04a1b2c3", Instant::now(), Duration::from_millis(5));
+        assert!(wedge.enter(at, &mut outputs));
+        assert_eq!(outputs, vec![WedgeOutput::Token(IdToken::new("04A1B2C3"))]);
+    }
+
+    #[test]
+    fn human_typing_is_handed_back() {
+        let mut wedge = KeyboardWedge::new();
+        let start = Instant::now();
+        let (mut outputs, at) = type_keys(&mut wedge, "42", start, Duration::from_millis(200));
+        wedge.expire(at + Duration::from_millis(200), &mut outputs);
+        assert_eq!(outputs, vec![WedgeOutput::Typed("4".to_string()), WedgeOutput::Typed("2".to_string())]);
+
+        // A short burst finished with Enter is still keyboard input
+        let mut wedge = KeyboardWedge::new();
+        let (mut outputs, at) = type_keys(&mut wedge, "12", start, Duration::from_millis(5));
+        assert!(!wedge.enter(at, &mut outputs));
+        assert_eq!(outputs, vec![WedgeOutput::Typed("12".to_string())]);
+    }
+}
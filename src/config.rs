@@ -0,0 +1,736 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Deserialize;
+use crate::hardware::battery::{BatteryPack, Supply};
+use crate::i18n::Locale;
+use crate::ocpp::OcppVersion;
+use crate::payment::mock::MockOutcome;
+use crate::tariff::Tariff;
+use crate::theme::Theme;
+
+pub const DEFAULT_CONFIG_PATH: &str = "config/kiosk.toml";
+
+/// Environment variables `KIOSK_<KEY>` override the file, `__` separates
+/// sections: `KIOSK_LOCALE=ko`, `KIOSK_CHARGER__MAX_POWER_KW=100`
+const ENV_PREFIX: &str = "KIOSK";
+const ENV_SEPARATOR: &str = "__";
+
+/// Backend variables from before the config file, still honoured
+const LEGACY_ENV: [(&str, &str); 4] = [
+    ("OCPP_CSMS_URL", "backend.url"),
+    ("OCPP_VERSION", "backend.version"),
+    ("OCPP_CHARGE_POINT_ID", "charger.id"),
+    ("PAYMENT_MOCK_OUTCOME", "payment.mock_outcome"),
+];
+
+// IEC 61851-1 PWM 범위 (6 A ~ 80 A)
+const MIN_CURRENT_AMPS: f32 = 6.0;
+const MAX_CURRENT_AMPS: f32 = 80.0;
+// OCPP chargeBoxIdentity / 2.0.1 identity 최대 길이
+const MAX_CHARGER_ID_LEN: usize = 48;
+
+pub const USAGE: &str = "\
+Usage: ev-charger [OPTIONS]
+
+Options:
+  --config <PATH>        Configuration file (default config/kiosk.toml)
+  --set <KEY>=<VALUE>    Override any setting, e.g. --set charger.max_power_kw=100
+  --charger-id <ID>      Same as --set charger.id=<ID>
+  --locale <CODE>        Same as --set locale=<CODE> (en, ko)
+  --backend-url <URL>    Same as --set backend.url=<URL> (ws://, wss:// or mock)
+  --assets <DIR>         Same as --set assets_dir=<DIR>
+  --fullscreen           Same as --set window.fullscreen=true
+  --help                 Print this message
+
+Settings are read from the file, then KIOSK_* environment variables
+(KIOSK_LOCALE=ko, KIOSK_CHARGER__MAX_POWER_KW=100), then the options above.";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("{0}\n\n{}", USAGE)]
+    Args(String),
+    #[error("cannot load configuration: {0}")]
+    Load(#[from] ::config::ConfigError),
+    #[error("invalid configuration:\n{}", .0.iter().map(|problem| format!("  - {}", problem)).collect::<Vec<_>>().join("\n"))]
+    Invalid(Vec<String>),
+}
+
+/// Command line of the kiosk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args {
+    /// Set with `--config`; the file must exist then
+    pub config: Option<PathBuf>,
+    /// `(key, value)` pairs, applied in order
+    pub overrides: Vec<(String, String)>,
+    pub help: bool,
+}
+
+impl Args {
+    /// Parses the arguments after the program name; `--flag=value` works too
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, ConfigError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| ConfigError::Args(format!("{} needs a value", flag)))
+            };
+            match flag.as_str() {
+                "--help" | "-h" => parsed.help = true,
+                "--config" => parsed.config = Some(PathBuf::from(value()?)),
+                "--set" => {
+                    let setting = value()?;
+                    let (key, value) = setting
+                        .split_once('=')
+                        .filter(|(key, _)| !key.trim().is_empty())
+                        .ok_or_else(|| ConfigError::Args(format!("--set expects KEY=VALUE, got `{}`", setting)))?;
+                    parsed.overrides.push((key.trim().to_string(), value.to_string()));
+                }
+                "--charger-id" => parsed.overrides.push(("charger.id".to_string(), value()?)),
+                "--locale" => parsed.overrides.push(("locale".to_string(), value()?)),
+                "--backend-url" => parsed.overrides.push(("backend.url".to_string(), value()?)),
+                "--assets" => parsed.overrides.push(("assets_dir".to_string(), value()?)),
+                "--fullscreen" => parsed.overrides.push(("window.fullscreen".to_string(), "true".to_string())),
+                _ => return Err(ConfigError::Args(format!("unknown option `{}`", arg))),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub width: f32,
+    pub height: f32,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self { width: 800.0, height: 600.0, fullscreen: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ChargerConfig {
+    /// Identity towards the central system, also the OCPP URL path
+    pub id: String,
+    /// Power stage limit, in kW
+    pub max_power_kw: f32,
+    /// `dc` or `ac` (through the vehicle's on-board charger)
+    pub output: String,
+    /// Simulated seconds per wall-clock second (simulator only)
+    pub time_scale: f32,
+}
+
+impl Default for ChargerConfig {
+    fn default() -> Self {
+        Self { id: "EVC-0001".to_string(), max_power_kw: 50.0, output: "dc".to_string(), time_scale: 20.0 }
+    }
+}
+
+impl ChargerConfig {
+    #[cfg(not(feature = "hardware"))]
+    pub fn supply(&self) -> Supply {
+        Supply::parse(&self.output).unwrap_or(Supply::Dc)
+    }
+}
+
+/// The vehicle the simulator plugs in (simulator only)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct VehicleConfig {
+    pub capacity_kwh: f32,
+    /// Battery level on arrival, percent
+    pub start_soc: f32,
+    /// Pack and outside temperature on arrival, °C
+    pub temperature_c: f32,
+    pub max_dc_kw: f32,
+    pub max_ac_kw: f32,
+}
+
+impl Default for VehicleConfig {
+    fn default() -> Self {
+        let pack = BatteryPack::default();
+        Self {
+            capacity_kwh: pack.capacity_kwh,
+            start_soc: pack.soc,
+            temperature_c: pack.temperature_c,
+            max_dc_kw: pack.max_dc_kw,
+            max_ac_kw: pack.max_ac_kw,
+        }
+    }
+}
+
+impl VehicleConfig {
+    #[cfg(not(feature = "hardware"))]
+    pub fn battery(&self) -> BatteryPack {
+        BatteryPack {
+            capacity_kwh: self.capacity_kwh,
+            soc: self.start_soc,
+            temperature_c: self.temperature_c,
+            ambient_c: self.temperature_c,
+            max_dc_kw: self.max_dc_kw,
+            max_ac_kw: self.max_ac_kw,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConnectorConfig {
+    pub id: u32,
+    /// Current offered to the vehicle through the pilot PWM
+    #[serde(default = "default_max_current")]
+    pub max_current_a: f32,
+}
+
+fn default_max_current() -> f32 {
+    32.0
+}
+
+/// Tariff file plus single prices overriding it
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PricingConfig {
+    pub tariff: PathBuf,
+    pub energy_price_per_kwh: Option<f64>,
+    pub session_fee: Option<f64>,
+    pub idle_fee_per_minute: Option<f64>,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            tariff: PathBuf::from(crate::tariff::DEFAULT_TARIFF_PATH),
+            energy_price_per_kwh: None,
+            session_fee: None,
+            idle_fee_per_minute: None,
+        }
+    }
+}
+
+/// Timeouts in seconds
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    /// Splash screen before the connect screen
+    pub splash_secs: f64,
+    /// Payment terminal answer
+    pub payment_secs: f64,
+    /// Central system answer to a request
+    pub backend_call_secs: f64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self { splash_secs: 4.0, payment_secs: 5.0, backend_call_secs: 30.0 }
+    }
+}
+
+/// Payment holds, in the tariff currency
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PaymentConfig {
+    /// Held before a full charge, whose cost is unknown until the battery is full.
+    /// Charging stops if the session reaches it; whatever is not used is released.
+    pub full_charge_hold: f64,
+    /// What the development terminal answers: `approve`, `decline`, `timeout` or `error`
+    pub mock_outcome: String,
+}
+
+impl Default for PaymentConfig {
+    fn default() -> Self {
+        Self { full_charge_hold: 50000.0, mock_outcome: "approve".to_string() }
+    }
+}
+
+impl PaymentConfig {
+    pub fn mock_outcome(&self) -> MockOutcome {
+        MockOutcome::parse(&self.mock_outcome).unwrap_or(MockOutcome::Approve)
+    }
+}
+
+/// Files the kiosk keeps its records in
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DataConfig {
+    /// Append-only ledger of finished sessions
+    pub sessions: PathBuf,
+    pub members: PathBuf,
+    /// Accounts the member file starts from on the first run
+    pub members_seed: PathBuf,
+    /// Local authorization list and cache from the central system
+    pub local_auth: PathBuf,
+    /// Connectors an operator took out of service
+    pub availability: PathBuf,
+}
+
+impl Default for DataConfig {
+    fn default() -> Self {
+        Self {
+            sessions: PathBuf::from(crate::ledger::DEFAULT_LEDGER_PATH),
+            members: PathBuf::from(crate::membership::DEFAULT_MEMBERS_PATH),
+            members_seed: PathBuf::from(crate::membership::DEFAULT_MEMBERS_SEED_PATH),
+            local_auth: PathBuf::from(crate::auth::local_list::DEFAULT_LOCAL_AUTH_PATH),
+            availability: PathBuf::from(crate::operator::DEFAULT_AVAILABILITY_PATH),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BackendConfig {
+    /// `ws://` / `wss://` central system, `mock` for the built-in one; unset runs offline
+    pub url: Option<String>,
+    pub version: String,
+    pub meter_interval_secs: f64,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self { url: None, version: "1.6".to_string(), meter_interval_secs: 10.0 }
+    }
+}
+
+/// Technician access through the top bar Settings button
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OperatorConfig {
+    pub pin: String,
+    /// Back to Standby after this long without a touch
+    pub idle_timeout_secs: f64,
+}
+
+impl Default for OperatorConfig {
+    fn default() -> Self {
+        Self { pin: "2580".to_string(), idle_timeout_secs: 60.0 }
+    }
+}
+
+impl OperatorConfig {
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs_f64(self.idle_timeout_secs)
+    }
+}
+
+/// Everything that used to be compiled in: see `config/kiosk.toml`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct KioskConfig {
+    /// File the settings were read from, rewritten by the operator screen
+    #[serde(skip)]
+    pub file: PathBuf,
+    pub locale: String,
+    pub assets_dir: PathBuf,
+    /// Built-in theme name or theme file
+    pub theme: String,
+    /// Font file tried before the ones in `config/fonts.toml`
+    pub font: Option<PathBuf>,
+    pub window: WindowConfig,
+    pub charger: ChargerConfig,
+    pub connectors: Vec<ConnectorConfig>,
+    pub pricing: PricingConfig,
+    pub timeouts: TimeoutConfig,
+    pub payment: PaymentConfig,
+    pub vehicle: VehicleConfig,
+    pub data: DataConfig,
+    pub backend: BackendConfig,
+    pub operator: OperatorConfig,
+    /// Device files of the real charger
+    #[cfg(feature = "hardware")]
+    pub sysfs: crate::hardware::driver::SysfsPaths,
+}
+
+impl Default for KioskConfig {
+    fn default() -> Self {
+        Self {
+            file: PathBuf::from(DEFAULT_CONFIG_PATH),
+            locale: Locale::En.code().to_string(),
+            assets_dir: PathBuf::from("assets"),
+            theme: crate::theme::DEFAULT_THEME_PATH.to_string(),
+            font: None,
+            window: WindowConfig::default(),
+            charger: ChargerConfig::default(),
+            connectors: vec![ConnectorConfig { id: crate::hardware::CONNECTOR_ID, max_current_a: default_max_current() }],
+            pricing: PricingConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            payment: PaymentConfig::default(),
+            vehicle: VehicleConfig::default(),
+            data: DataConfig::default(),
+            backend: BackendConfig::default(),
+            operator: OperatorConfig::default(),
+            #[cfg(feature = "hardware")]
+            sysfs: Default::default(),
+        }
+    }
+}
+
+impl KioskConfig {
+    /// File, then environment, then command line; validated
+    pub fn load(args: &Args) -> Result<KioskConfig, ConfigError> {
+        KioskConfig::layered(args, std::env::vars().collect())
+    }
+
+    fn layered(args: &Args, mut env: HashMap<String, String>) -> Result<KioskConfig, ConfigError> {
+        // Legacy variables stand in for their `KIOSK_*` names, which win when both are set
+        for (var, key) in LEGACY_ENV {
+            if let Some(value) = env.get(var).cloned() {
+                let name = format!("{}_{}", ENV_PREFIX, key.to_ascii_uppercase().replace('.', ENV_SEPARATOR));
+                env.entry(name).or_insert(value);
+            }
+        }
+        let path = args.config.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH));
+        let mut builder = ::config::Config::builder()
+            .add_source(::config::File::from(path.clone()).format(::config::FileFormat::Toml).required(args.config.is_some()))
+            .add_source(
+                ::config::Environment::with_prefix(ENV_PREFIX)
+                    .prefix_separator("_")
+                    .separator(ENV_SEPARATOR)
+                    .source(Some(env)),
+            );
+        for (key, value) in &args.overrides {
+            builder = builder.set_override(key.as_str(), value.as_str())?;
+        }
+
+        let mut config: KioskConfig = builder.build()?.try_deserialize()?;
+        config.file = path;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Contents of a configuration file on their own, checked like at startup
+    pub fn parse(text: &str) -> Result<KioskConfig, ConfigError> {
+        let config: KioskConfig = ::config::Config::builder()
+            .add_source(::config::File::from_str(text, ::config::FileFormat::Toml))
+            .build()?
+            .try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Every problem at once, so a broken install is fixed in one go
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, problem: String| {
+            if !ok {
+                problems.push(problem);
+            }
+        };
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+
+        check(
+            Locale::parse(&self.locale).is_some(),
+            format!("locale: `{}` is not supported (expected en or ko)", self.locale),
+        );
+        check(
+            !self.assets_dir.exists() || self.assets_dir.is_dir(),
+            format!("assets_dir: {} is not a directory", self.assets_dir.display()),
+        );
+        if Theme::builtin(&self.theme).is_none() {
+            if let Err(err) = Theme::load(Path::new(&self.theme)) {
+                check(false, format!("theme: {}: {}", self.theme, err));
+            }
+        }
+        if let Some(font) = &self.font {
+            check(font.is_file(), format!("font: {} does not exist", font.display()));
+        }
+
+        check(
+            self.window.width >= 320.0 && self.window.height >= 240.0,
+            format!("window: {}x{} is smaller than 320x240", self.window.width, self.window.height),
+        );
+
+        let id = &self.charger.id;
+        check(
+            !id.is_empty() && id.len() <= MAX_CHARGER_ID_LEN && id.chars().all(|c| c.is_ascii_graphic() && c != '/'),
+            format!("charger.id: `{}` must be 1-{} printable ASCII characters without `/`", id, MAX_CHARGER_ID_LEN),
+        );
+        check(
+            positive(self.charger.max_power_kw as f64),
+            format!("charger.max_power_kw: {} must be above 0", self.charger.max_power_kw),
+        );
+        check(
+            Supply::parse(&self.charger.output).is_some(),
+            format!("charger.output: `{}` is not supported (expected dc or ac)", self.charger.output),
+        );
+        check(
+            positive(self.charger.time_scale as f64),
+            format!("charger.time_scale: {} must be above 0", self.charger.time_scale),
+        );
+
+        check(!self.connectors.is_empty(), "connectors: at least one connector is required".to_string());
+        // The screens, session and OCPP status all drive a single connector
+        check(
+            self.connectors.len() <= 1,
+            format!("connectors: the kiosk drives one connector, {} are listed", self.connectors.len()),
+        );
+        for (index, connector) in self.connectors.iter().enumerate() {
+            check(connector.id >= 1, format!("connectors[{}].id: connector ids start at 1", index));
+            check(
+                (MIN_CURRENT_AMPS..=MAX_CURRENT_AMPS).contains(&connector.max_current_a),
+                format!(
+                    "connectors[{}].max_current_a: {} A is outside {}-{} A",
+                    index, connector.max_current_a, MIN_CURRENT_AMPS, MAX_CURRENT_AMPS
+                ),
+            );
+        }
+
+        if let Err(err) = Tariff::load(&self.pricing.tariff) {
+            check(false, format!("pricing.tariff: {}: {}", self.pricing.tariff.display(), err));
+        }
+        for (key, price) in [
+            ("energy_price_per_kwh", self.pricing.energy_price_per_kwh),
+            ("session_fee", self.pricing.session_fee),
+            ("idle_fee_per_minute", self.pricing.idle_fee_per_minute),
+        ] {
+            if let Some(price) = price {
+                check(price.is_finite() && price >= 0.0, format!("pricing.{}: {} must not be negative", key, price));
+            }
+        }
+
+        for (key, secs) in [
+            ("splash_secs", self.timeouts.splash_secs),
+            ("payment_secs", self.timeouts.payment_secs),
+            ("backend_call_secs", self.timeouts.backend_call_secs),
+        ] {
+            check(positive(secs), format!("timeouts.{}: {} must be above 0", key, secs));
+        }
+
+        check(
+            positive(self.payment.full_charge_hold),
+            format!("payment.full_charge_hold: {} must be above 0", self.payment.full_charge_hold),
+        );
+        check(
+            MockOutcome::parse(&self.payment.mock_outcome).is_some(),
+            format!(
+                "payment.mock_outcome: `{}` is not supported (expected approve, decline, timeout or error)",
+                self.payment.mock_outcome
+            ),
+        );
+
+        for (key, value) in [
+            ("capacity_kwh", self.vehicle.capacity_kwh),
+            ("max_dc_kw", self.vehicle.max_dc_kw),
+            ("max_ac_kw", self.vehicle.max_ac_kw),
+        ] {
+            check(positive(value as f64), format!("vehicle.{}: {} must be above 0", key, value));
+        }
+        check(
+            (0.0..100.0).contains(&self.vehicle.start_soc),
+            format!("vehicle.start_soc: {} must be from 0 up to 100", self.vehicle.start_soc),
+        );
+        check(
+            (-40.0..=60.0).contains(&self.vehicle.temperature_c),
+            format!("vehicle.temperature_c: {} is outside -40-60 °C", self.vehicle.temperature_c),
+        );
+
+        if let Some(url) = &self.backend.url {
+            check(
+                url == "mock" || url.starts_with("ws://") || url.starts_with("wss://"),
+                format!("backend.url: `{}` must start with ws:// or wss:// (or be `mock`)", url),
+            );
+        }
+        check(
+            OcppVersion::parse(&self.backend.version).is_some(),
+            format!("backend.version: `{}` is not supported (expected 1.6 or 2.0.1)", self.backend.version),
+        );
+        check(
+            positive(self.backend.meter_interval_secs),
+            format!("backend.meter_interval_secs: {} must be above 0", self.backend.meter_interval_secs),
+        );
+
+        let pin = &self.operator.pin;
+        check(
+            (4..=8).contains(&pin.len()) && pin.chars().all(|c| c.is_ascii_digit()),
+            "operator.pin: must be 4-8 digits".to_string(),
+        );
+        check(
+            positive(self.operator.idle_timeout_secs),
+            format!("operator.idle_timeout_secs: {} must be above 0", self.operator.idle_timeout_secs),
+        );
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    pub fn locale(&self) -> Locale {
+        Locale::parse(&self.locale).unwrap_or(Locale::En)
+    }
+
+    /// The kiosk's only connector
+    pub fn connector(&self) -> &ConnectorConfig {
+        &self.connectors[0]
+    }
+
+    /// Tariff file with the configured prices applied
+    pub fn tariff(&self) -> Tariff {
+        let mut tariff = Tariff::load_or_default(&self.pricing.tariff);
+        if let Some(price) = self.pricing.energy_price_per_kwh {
+            tariff.energy_price_per_kwh = price;
+            // A flat price replaces the seasonal time-of-use prices
+            tariff.seasons.clear();
+        }
+        if let Some(fee) = self.pricing.session_fee {
+            tariff.session_fee = fee;
+        }
+        if let Some(fee) = self.pricing.idle_fee_per_minute {
+            tariff.idle_fee_per_minute = fee;
+        }
+        tariff
+    }
+
+    pub fn backend_version(&self) -> OcppVersion {
+        OcppVersion::parse(&self.backend.version).unwrap_or(OcppVersion::V16)
+    }
+}
+
+impl TimeoutConfig {
+    pub fn splash(&self) -> Duration {
+        Duration::from_secs_f64(self.splash_secs)
+    }
+
+    pub fn payment(&self) -> Duration {
+        Duration::from_secs_f64(self.payment_secs)
+    }
+
+    pub fn backend_call(&self) -> Duration {
+        Duration::from_secs_f64(self.backend_call_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Args {
+        Args::parse(list.iter().map(|arg| arg.to_string())).unwrap()
+    }
+
+    fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn shipped_file_is_the_default() {
+        let config = KioskConfig::layered(&Args::default(), HashMap::new()).unwrap();
+        assert_eq!(config, KioskConfig::default());
+    }
+
+    #[test]
+    fn command_line_beats_environment_beats_file() {
+        let config = KioskConfig::layered(
+            &args(&["--locale", "ko", "--set", "charger.max_power_kw=150", "--backend-url=mock"]),
+            env(&[
+                ("KIOSK_LOCALE", "en"),
+                ("KIOSK_CHARGER__MAX_POWER_KW", "100"),
+                ("KIOSK_TIMEOUTS__SPLASH_SECS", "1.5"),
+                ("OCPP_CHARGE_POINT_ID", "EVC-0042"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.locale(), Locale::Ko);
+        assert_eq!(config.charger.max_power_kw, 150.0);
+        assert_eq!(config.charger.id, "EVC-0042");
+        assert_eq!(config.timeouts.splash(), Duration::from_millis(1500));
+        assert_eq!(config.backend.url.as_deref(), Some("mock"));
+        assert_eq!(config.connector().max_current_a, 32.0);
+    }
+
+    #[test]
+    fn kiosk_variables_beat_the_legacy_ones() {
+        let legacy = [("OCPP_CSMS_URL", "ws://legacy"), ("OCPP_CHARGE_POINT_ID", "EVC-OLD")];
+        let config = KioskConfig::layered(&Args::default(), env(&legacy)).unwrap();
+        assert_eq!((config.backend.url.as_deref(), config.charger.id.as_str()), (Some("ws://legacy"), "EVC-OLD"));
+
+        let mut vars = env(&legacy);
+        vars.extend(env(&[("KIOSK_BACKEND__URL", "ws://current"), ("KIOSK_CHARGER__ID", "EVC-NEW")]));
+        let config = KioskConfig::layered(&Args::default(), vars.clone()).unwrap();
+        assert_eq!((config.backend.url.as_deref(), config.charger.id.as_str()), (Some("ws://current"), "EVC-NEW"));
+
+        let config = KioskConfig::layered(&args(&["--charger-id", "EVC-CLI"]), vars).unwrap();
+        assert_eq!(config.charger.id, "EVC-CLI");
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let result = KioskConfig::layered(
+            &args(&["--set", "locale=fr", "--set", "charger.id=EVC/1", "--backend-url", "http://csms"]),
+            env(&[
+                ("KIOSK_TIMEOUTS__PAYMENT_SECS", "0"),
+                ("KIOSK_PAYMENT__FULL_CHARGE_HOLD", "-1"),
+                ("KIOSK_CHARGER__OUTPUT", "hvdc"),
+                ("KIOSK_THEME", "config/them.toml"),
+                ("KIOSK_PRICING__TARIFF", "config/tarif.toml"),
+                ("PAYMENT_MOCK_OUTCOME", "maybe"),
+                ("KIOSK_VEHICLE__START_SOC", "100"),
+            ]),
+        );
+        let Err(ConfigError::Invalid(problems)) = result else {
+            panic!("expected validation errors, got {:?}", result);
+        };
+        let keys: Vec<&str> = problems.iter().filter_map(|problem| problem.split(':').next()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "locale",
+                "theme",
+                "charger.id",
+                "charger.output",
+                "pricing.tariff",
+                "timeouts.payment_secs",
+                "payment.full_charge_hold",
+                "payment.mock_outcome",
+                "vehicle.start_soc",
+                "backend.url"
+            ]
+        );
+
+        let mut config = KioskConfig::default();
+        config.connectors.push(ConnectorConfig { id: 2, max_current_a: 100.0 });
+        let Err(ConfigError::Invalid(problems)) = config.validate() else { panic!("second connector accepted") };
+        let keys: Vec<&str> = problems.iter().filter_map(|problem| problem.split(':').next()).collect();
+        assert_eq!(keys, vec!["connectors", "connectors[1].max_current_a"]);
+
+        assert!(matches!(
+            KioskConfig::layered(&args(&["--config", "config/missing.toml"]), HashMap::new()),
+            Err(ConfigError::Load(_))
+        ));
+        assert!(matches!(Args::parse(["--verbose".to_string()]), Err(ConfigError::Args(_))));
+        assert!(matches!(Args::parse(["--set".to_string(), "locale".to_string()]), Err(ConfigError::Args(_))));
+    }
+
+    #[test]
+    fn edited_file_is_checked_before_saving() {
+        let text = std::fs::read_to_string(DEFAULT_CONFIG_PATH).unwrap();
+        assert_eq!(KioskConfig::parse(&text).unwrap(), KioskConfig::default());
+
+        let edited = text.replace("pin = \"2580\"", "pin = \"12\"");
+        let Err(ConfigError::Invalid(problems)) = KioskConfig::parse(&edited) else { panic!("short PIN accepted") };
+        assert_eq!(problems, vec!["operator.pin: must be 4-8 digits".to_string()]);
+        assert!(matches!(KioskConfig::parse("[charger"), Err(ConfigError::Load(_))));
+    }
+
+    #[test]
+    fn prices_override_the_tariff_file() {
+        let mut config = KioskConfig::default();
+        assert!(!config.tariff().seasons.is_empty());
+        config.pricing.energy_price_per_kwh = Some(300.0);
+        config.pricing.session_fee = Some(500.0);
+        let tariff = config.tariff();
+        assert_eq!(tariff.energy_price_per_kwh, 300.0);
+        assert_eq!(tariff.session_fee, 500.0);
+        assert!(tariff.seasons.is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use eframe::egui;
+use serde::Deserialize;
+use crate::i18n::{self, Locale};
+
+pub const DEFAULT_FONTS_PATH: &str = "config/fonts.toml";
+
+/// Fallback chain entry standing for egui's built-in fonts (Latin, emoji)
+const BUILTIN_FONTS: &str = "default";
+const CONFIGURED_FONT: &str = "configured";
+
+#[derive(Debug, thiserror::Error)]
+pub enum FontError {
+    #[error("cannot read font config: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid font config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("cannot read font {}: {source}", path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+    #[error("{} is not a usable TTF/OTF font (face {index})", path.display())]
+    Invalid { path: PathBuf, index: u32 },
+    #[error("no configured font can render {0}; set `font` in {cfg} or add a font to {fonts}", cfg = crate::config::DEFAULT_CONFIG_PATH, fonts = DEFAULT_FONTS_PATH)]
+    MissingGlyphs(String),
+}
+
+/// A font file; `index` picks the face inside a `.ttc` collection
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FontFile {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub index: u32,
+}
+
+impl FontFile {
+    fn read(&self) -> Result<egui::FontData, FontError> {
+        let bytes = std::fs::read(&self.path).map_err(|source| FontError::Read { path: self.path.clone(), source })?;
+        // egui은 잘못된 글꼴 파일에서 패닉하므로 미리 확인
+        if ab_glyph::FontRef::try_from_slice_and_index(&bytes, self.index).is_err() {
+            return Err(FontError::Invalid { path: self.path.clone(), index: self.index });
+        }
+        let mut data = egui::FontData::from_owned(bytes);
+        data.index = self.index;
+        Ok(data)
+    }
+}
+
+/// Fonts to load and the order each language uses them in
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FontConfig {
+    #[serde(default)]
+    pub fonts: BTreeMap<String, FontFile>,
+    /// Font names per locale code, highest priority first. `default` marks
+    /// where egui's built-in fonts go; they are appended if not listed.
+    #[serde(default)]
+    pub fallback: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for FontConfig {
+    /// The usual places for a Hangul font: bundled in `assets/fonts`, then
+    /// common Linux packages (fonts-noto-cjk, fonts-nanum)
+    fn default() -> Self {
+        let font = |path: &str, index| FontFile { path: PathBuf::from(path), index };
+        let fonts = BTreeMap::from([
+            ("noto_sans_kr".to_string(), font("assets/fonts/NotoSansKR-Regular.otf", 0)),
+            ("noto_sans_cjk".to_string(), font("/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc", 1)),
+            ("nanum_gothic".to_string(), font("/usr/share/fonts/truetype/nanum/NanumGothic.ttf", 0)),
+        ]);
+        let hangul = ["noto_sans_kr", "noto_sans_cjk", "nanum_gothic"];
+        let chain = |builtin_first: bool| {
+            let mut chain: Vec<String> = hangul.iter().map(|name| name.to_string()).collect();
+            chain.insert(if builtin_first { 0 } else { chain.len() }, BUILTIN_FONTS.to_string());
+            chain
+        };
+        Self {
+            fonts,
+            fallback: BTreeMap::from([
+                (Locale::En.code().to_string(), chain(true)),
+                (Locale::Ko.code().to_string(), chain(false)),
+            ]),
+        }
+    }
+}
+
+impl FontConfig {
+    pub fn load(path: &Path) -> Result<FontConfig, FontError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Config from `path` (or the built-in one), plus `font` tried before every other
+    pub fn load_or_default(path: &Path, font: Option<&Path>) -> FontConfig {
+        let mut config = FontConfig::load(path).unwrap_or_else(|err| {
+            println!("Using default fonts ({}): {}", path.display(), err);
+            FontConfig::default()
+        });
+        if let Some(font) = font {
+            config.fonts.insert(CONFIGURED_FONT.to_string(), FontFile { path: font.to_path_buf(), index: 0 });
+        }
+        config
+    }
+}
+
+/// Loaded font files and the resolved fallback chain for each locale
+pub struct FontSet {
+    data: BTreeMap<String, Arc<egui::FontData>>,
+    chains: HashMap<Locale, Vec<String>>,
+}
+
+impl FontSet {
+    /// Reads every configured font; missing or broken files are skipped
+    pub fn load(config: &FontConfig) -> Self {
+        let mut data = BTreeMap::new();
+        for (name, file) in &config.fonts {
+            match file.read() {
+                Ok(font) => {
+                    println!("Loaded font {} ({})", name, file.path.display());
+                    data.insert(name.clone(), Arc::new(font));
+                }
+                Err(err) => println!("Skipping font {}: {}", name, err),
+            }
+        }
+
+        let chains = Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                let configured = data.contains_key(CONFIGURED_FONT).then(|| CONFIGURED_FONT.to_string());
+                let mut chain: Vec<String> = configured
+                    .into_iter()
+                    .chain(config.fallback.get(locale.code()).cloned().unwrap_or_default())
+                    .filter(|name| name == BUILTIN_FONTS || data.contains_key(name))
+                    .collect();
+                chain.dedup();
+                if !chain.iter().any(|name| name == BUILTIN_FONTS) {
+                    chain.push(BUILTIN_FONTS.to_string());
+                }
+                (locale, chain)
+            })
+            .collect();
+
+        Self { data, chains }
+    }
+
+    /// egui font setup for `locale`
+    pub fn definitions(&self, locale: Locale) -> egui::FontDefinitions {
+        let mut definitions = egui::FontDefinitions::default();
+        definitions.font_data.extend(self.data.iter().map(|(name, font)| (name.clone(), font.clone())));
+
+        let chain = &self.chains[&locale];
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            let builtin = definitions.families.get(&family).cloned().unwrap_or_default();
+            let fonts = chain
+                .iter()
+                .flat_map(|name| if name == BUILTIN_FONTS { builtin.clone() } else { vec![name.clone()] })
+                .collect();
+            definitions.families.insert(family, fonts);
+        }
+        definitions
+    }
+
+    /// Messages of `locale` (and the language toggle labels) containing
+    /// characters its fonts cannot draw, with those characters
+    pub fn missing_glyphs(&self, locale: Locale) -> Vec<(String, Vec<char>)> {
+        let fonts = egui::epaint::text::Fonts::new(
+            1.0,
+            1024,
+            egui::epaint::AlphaFromCoverage::default(),
+            self.definitions(locale),
+        );
+        let font_id = egui::FontId::proportional(14.0);
+
+        let language_names = Locale::ALL
+            .into_iter()
+            .map(|language| (format!("language.{}", language.code()), language.native_name().to_string()));
+        let messages = i18n::catalog(locale)
+            .iter()
+            .map(|(key, text)| (key.to_string(), text.to_string()))
+            .chain(language_names);
+
+        let mut missing: Vec<(String, Vec<char>)> = messages
+            .filter_map(|(key, text)| {
+                let mut chars: Vec<char> = text.chars().filter(|&c| !fonts.has_glyph(&font_id, c)).collect();
+                chars.sort_unstable();
+                chars.dedup();
+                (!chars.is_empty()).then_some((key, chars))
+            })
+            .collect();
+        missing.sort();
+        missing
+    }
+
+    /// Startup check that every localized string can be rendered; fails (after
+    /// logging the offending messages) if any would show boxes
+    pub fn check(&self) -> Result<(), FontError> {
+        let mut unrenderable = Vec::new();
+        for locale in Locale::ALL {
+            let missing = self.missing_glyphs(locale);
+            if missing.is_empty() {
+                continue;
+            }
+            println!(
+                "Fonts for {} ({}) cannot render {} message(s)",
+                locale.code(),
+                self.chains[&locale].join(" → "),
+                missing.len(),
+            );
+            for (key, chars) in missing {
+                println!("  {}: {}", key, chars.iter().map(|c| format!("U+{:04X}", *c as u32)).collect::<Vec<_>>().join(" "));
+            }
+            unrenderable.push(locale.code());
+        }
+        if unrenderable.is_empty() {
+            Ok(())
+        } else {
+            Err(FontError::MissingGlyphs(unrenderable.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_fonts_cover_english_but_not_hangul() {
+        let config = FontConfig {
+            fonts: BTreeMap::from([(
+                "broken".to_string(),
+                FontFile { path: PathBuf::from("Cargo.toml"), index: 0 },
+            )]),
+            fallback: BTreeMap::from([("ko".to_string(), vec!["broken".to_string()])]),
+        };
+        let fonts = FontSet::load(&config);
+        assert_eq!(fonts.chains[&Locale::Ko], vec![BUILTIN_FONTS.to_string()]);
+
+        // 한국어 글꼴 없이는 언어 전환 버튼의 "한국어"만 그릴 수 없음
+        let english: Vec<String> = fonts.missing_glyphs(Locale::En).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(english, vec!["language.ko".to_string()]);
+
+        let korean = fonts.missing_glyphs(Locale::Ko);
+        assert!(korean.iter().any(|(key, chars)| key == "standby.full_charge" && chars.contains(&'충')));
+        assert!(matches!(fonts.check(), Err(FontError::MissingGlyphs(ref locales)) if locales == "en, ko"));
+    }
+
+    #[test]
+    fn shipped_config_matches_the_default() {
+        assert_eq!(FontConfig::load(Path::new(DEFAULT_FONTS_PATH)).unwrap(), FontConfig::default());
+    }
+}
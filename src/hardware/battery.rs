@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+// SoC where the cells reach their voltage limit: constant current below, constant voltage above
+pub const TAPER_START_SOC: f32 = 80.0;
+// Power the pack still takes just before full, as a share of its constant-current rate
+const TAPER_END_RATIO: f32 = 0.08;
+// Energy that ends up in the cells; the rest is heat in the charger and the pack
+#[cfg(not(feature = "hardware"))]
+const CHARGE_EFFICIENCY: f32 = 0.93;
+// Pack temperature rise per minute for each kW going in, and the share of the
+// difference to ambient the thermal management removes each minute
+#[cfg(not(feature = "hardware"))]
+const HEATING_PER_KW_MINUTE: f32 = 0.01;
+#[cfg(not(feature = "hardware"))]
+const COOLING_PER_MINUTE: f32 = 0.05;
+// Share of the charge rate the BMS allows by pack temperature (°C), interpolated
+#[cfg(not(feature = "hardware"))]
+const TEMPERATURE_LIMITS: [(f32, f32); 6] = [(-10.0, 0.1), (0.0, 0.3), (15.0, 1.0), (40.0, 1.0), (50.0, 0.5), (60.0, 0.0)];
+
+/// What the charger puts out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Supply {
+    /// Through the vehicle's on-board charger
+    Ac,
+    /// Straight into the pack
+    Dc,
+}
+
+impl Supply {
+    pub fn parse(value: &str) -> Option<Supply> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ac" => Some(Supply::Ac),
+            "dc" => Some(Supply::Dc),
+            _ => None,
+        }
+    }
+}
+
+/// Share of the constant-current rate the pack takes at `soc`: all of it up to
+/// about 80%, then falling as the cells hold their voltage limit
+pub fn taper(soc: f32) -> f32 {
+    if soc >= 100.0 {
+        return 0.0;
+    }
+    if soc <= TAPER_START_SOC {
+        return 1.0;
+    }
+    let through = (soc - TAPER_START_SOC) / (100.0 - TAPER_START_SOC);
+    1.0 - through * (1.0 - TAPER_END_RATIO)
+}
+
+/// Share of the charge rate allowed at a pack temperature: cold packs charge
+/// slowly, hot ones are derated and stop at 60 °C
+#[cfg(not(feature = "hardware"))]
+pub fn temperature_factor(temperature_c: f32) -> f32 {
+    let (first, last) = (TEMPERATURE_LIMITS[0], TEMPERATURE_LIMITS[TEMPERATURE_LIMITS.len() - 1]);
+    if temperature_c <= first.0 {
+        return first.1;
+    }
+    if temperature_c >= last.0 {
+        return last.1;
+    }
+    TEMPERATURE_LIMITS
+        .windows(2)
+        .find(|pair| temperature_c <= pair[1].0)
+        .map(|pair| {
+            let ((t0, f0), (t1, f1)) = (pair[0], pair[1]);
+            f0 + (f1 - f0) * (temperature_c - t0) / (t1 - t0)
+        })
+        .unwrap_or(last.1)
+}
+
+/// Time to put `energy_kwh` into a pack of `capacity_kwh` now at `soc`, taking
+/// `power_kw` at the moment, if the rest of the session follows the CC/CV curve
+pub fn time_to_deliver(capacity_kwh: f32, soc: f32, power_kw: f32, energy_kwh: f32) -> Duration {
+    if power_kw <= 0.0 || capacity_kwh <= 0.0 || energy_kwh <= 0.0 {
+        return Duration::ZERO;
+    }
+    let now = taper(soc).max(TAPER_END_RATIO);
+    let step_kwh = capacity_kwh / 100.0;
+    let (mut soc, mut left, mut hours) = (soc, energy_kwh, 0.0);
+    while left > 0.0 && soc < 100.0 {
+        let energy = left.min(step_kwh);
+        let power = power_kw * (taper(soc) / now).min(1.0);
+        hours += energy / power.max(f32::EPSILON);
+        left -= energy;
+        soc += energy / capacity_kwh * 100.0;
+    }
+    Duration::from_secs_f32(hours * 3600.0)
+}
+
+/// A vehicle battery as its management system sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryPack {
+    pub capacity_kwh: f32,
+    /// Percent
+    pub soc: f32,
+    pub temperature_c: f32,
+    pub ambient_c: f32,
+    /// Most the pack takes from a DC charger
+    pub max_dc_kw: f32,
+    /// On-board charger rating, the limit on AC
+    pub max_ac_kw: f32,
+}
+
+// How the pack takes a charge only matters to the simulated vehicle
+#[cfg(not(feature = "hardware"))]
+impl BatteryPack {
+    pub fn is_full(&self) -> bool {
+        self.soc >= 100.0
+    }
+
+    /// Power the vehicle asks for right now
+    pub fn acceptance_kw(&self, supply: Supply) -> f32 {
+        let pack_kw = self.max_dc_kw * taper(self.soc);
+        let limit = match supply {
+            Supply::Dc => pack_kw,
+            Supply::Ac => pack_kw.min(self.max_ac_kw),
+        };
+        limit * temperature_factor(self.temperature_c)
+    }
+
+    /// Take `power_kw` for `seconds`; with no power the pack only cools down
+    pub fn charge(&mut self, power_kw: f32, seconds: f32) {
+        let energy_kwh = power_kw * seconds / 3600.0 * CHARGE_EFFICIENCY;
+        self.soc = (self.soc + energy_kwh / self.capacity_kwh * 100.0).min(100.0);
+
+        let minutes = seconds / 60.0;
+        let heating = power_kw * HEATING_PER_KW_MINUTE * minutes;
+        let cooling = (self.temperature_c - self.ambient_c) * (COOLING_PER_MINUTE * minutes).min(1.0);
+        self.temperature_c += heating - cooling;
+    }
+}
+
+impl Default for BatteryPack {
+    fn default() -> Self {
+        Self { capacity_kwh: 64.0, soc: 20.0, temperature_c: 25.0, ambient_c: 25.0, max_dc_kw: 100.0, max_ac_kw: 11.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "hardware"))]
+    fn power_tapers_above_eighty_percent() {
+        let mut pack = BatteryPack::default();
+        assert_eq!(pack.acceptance_kw(Supply::Dc), 100.0);
+        assert_eq!(pack.acceptance_kw(Supply::Ac), 11.0);
+
+        pack.soc = 90.0;
+        assert!((pack.acceptance_kw(Supply::Dc) - 54.0).abs() < 0.01);
+        pack.soc = 99.8;
+        assert!(pack.acceptance_kw(Supply::Dc) < 10.0);
+        // AC stays at the on-board charger limit until the taper drops below it
+        assert!(pack.acceptance_kw(Supply::Ac) < 11.0);
+        pack.soc = 100.0;
+        assert_eq!(pack.acceptance_kw(Supply::Dc), 0.0);
+    }
+
+    #[test]
+    #[cfg(not(feature = "hardware"))]
+    fn temperature_limits_the_rate() {
+        assert_eq!(temperature_factor(25.0), 1.0);
+        assert_eq!(temperature_factor(-20.0), 0.1);
+        assert!((temperature_factor(7.5) - 0.65).abs() < 0.001);
+        assert_eq!(temperature_factor(45.0), 0.75);
+        assert_eq!(temperature_factor(70.0), 0.0);
+
+        // A long fast charge warms the pack above ambient
+        let mut pack = BatteryPack::default();
+        for _ in 0..30 {
+            let power = pack.acceptance_kw(Supply::Dc);
+            pack.charge(power, 60.0);
+        }
+        assert!(pack.temperature_c > 35.0);
+        assert!(pack.soc > 60.0 && pack.soc < 100.0);
+        pack.charge(0.0, 3600.0);
+        assert!(pack.temperature_c < 30.0);
+    }
+
+    #[test]
+    fn the_last_twenty_percent_take_longer_than_the_twenty_before() {
+        let capacity = 64.0;
+        let middle = time_to_deliver(capacity, 60.0, 100.0, capacity * 0.2);
+        let top = time_to_deliver(capacity, 80.0, 100.0, capacity * 0.2);
+        assert!((middle.as_secs_f32() - 461.0).abs() < 1.0);
+        assert!(top > middle * 2);
+        assert_eq!(time_to_deliver(capacity, 50.0, 0.0, 10.0), Duration::ZERO);
+    }
+}
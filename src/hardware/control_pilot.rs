@@ -0,0 +1,297 @@
+use std::time::{Duration, Instant};
+
+/// How long a new pilot level must stay stable before the state change is accepted
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Control pilot states defined by IEC 61851-1 Annex A
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PilotState {
+    A, // +12V: vehicle not connected
+    B, // +9V: vehicle connected, not ready to accept energy
+    C, // +6V: vehicle ready, no ventilation required
+    D, // +3V: vehicle ready, ventilation required
+    E, // 0V: EVSE disconnected, utility problem or pilot short circuit
+    F, // -12V: EVSE not available
+}
+
+impl PilotState {
+    /// Classify the positive plateau of the pilot signal (±1V tolerance bands, Table A.4)
+    pub fn from_voltage(volts: f32) -> Option<PilotState> {
+        match volts {
+            v if (11.0..=13.0).contains(&v) => Some(PilotState::A),
+            v if (8.0..=10.0).contains(&v) => Some(PilotState::B),
+            v if (5.0..=7.0).contains(&v) => Some(PilotState::C),
+            v if (2.0..=4.0).contains(&v) => Some(PilotState::D),
+            v if (-1.0..=1.0).contains(&v) => Some(PilotState::E),
+            v if (-13.0..=-11.0).contains(&v) => Some(PilotState::F),
+            _ => None,
+        }
+    }
+
+    pub fn nominal_voltage(self) -> f32 {
+        match self {
+            PilotState::A => 12.0,
+            PilotState::B => 9.0,
+            PilotState::C => 6.0,
+            PilotState::D => 3.0,
+            PilotState::E => 0.0,
+            PilotState::F => -12.0,
+        }
+    }
+
+    pub fn is_vehicle_connected(self) -> bool {
+        matches!(self, PilotState::B | PilotState::C | PilotState::D)
+    }
+
+    pub fn is_energy_requested(self) -> bool {
+        matches!(self, PilotState::C | PilotState::D)
+    }
+
+    pub fn is_fault(self) -> bool {
+        matches!(self, PilotState::E | PilotState::F)
+    }
+}
+
+/// Duty cycle (percent) advertising the given maximum current (Table A.7)
+pub fn duty_cycle_for_current(amps: f32) -> Option<f32> {
+    if !(6.0..=80.0).contains(&amps) {
+        None
+    } else if amps <= 51.0 {
+        Some(amps / 0.6)
+    } else {
+        Some(amps / 2.5 + 64.0)
+    }
+}
+
+/// Maximum current (A) a vehicle may draw for the given duty cycle (Table A.8)
+pub fn current_for_duty_cycle(duty: f32) -> Option<f32> {
+    match duty {
+        d if (10.0..=85.0).contains(&d) => Some((d * 0.6).max(6.0)),
+        d if d > 85.0 && d <= 96.0 => Some(((d - 64.0) * 2.5).min(80.0)),
+        d if d > 96.0 && d <= 97.0 => Some(80.0),
+        _ => None,
+    }
+}
+
+/// One sample of the pilot line as seen by the EVSE
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PilotReading {
+    pub high: f32, // Positive plateau
+    pub low: f32,  // Negative plateau (equals `high` while PWM is off)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PilotEvent {
+    VehicleConnected,
+    EnergyRequested,
+    EnergyStopped,
+    VehicleDisconnected,
+    Fault(PilotState),
+}
+
+/// EVSE side of the control pilot: debounces the measured levels, tracks the
+/// advertised PWM duty cycle and performs the vehicle diode check.
+pub struct ControlPilot {
+    state: PilotState,
+    pending: Option<(PilotState, Instant)>,
+    duty_cycle: Option<f32>,
+    diode_verified: bool,
+}
+
+impl ControlPilot {
+    pub fn new() -> Self {
+        Self {
+            state: PilotState::A,
+            pending: None,
+            duty_cycle: None,
+            diode_verified: false,
+        }
+    }
+
+    pub fn state(&self) -> PilotState {
+        self.state
+    }
+
+    /// Current PWM output; `None` means a constant +12V (no current offered)
+    pub fn duty_cycle(&self) -> Option<f32> {
+        self.duty_cycle
+    }
+
+    pub fn is_pwm_enabled(&self) -> bool {
+        self.duty_cycle.is_some()
+    }
+
+    pub fn is_diode_verified(&self) -> bool {
+        self.diode_verified
+    }
+
+    /// Start oscillating to advertise `max_current` to the vehicle
+    pub fn enable_pwm(&mut self, max_current: f32) {
+        self.duty_cycle = duty_cycle_for_current(max_current);
+    }
+
+    pub fn disable_pwm(&mut self) {
+        self.duty_cycle = None;
+        self.diode_verified = false;
+    }
+
+    pub fn update(&mut self, reading: PilotReading, now: Instant) -> Option<PilotEvent> {
+        // Out-of-band levels are reported as state F (EVSE not available)
+        let mut measured = PilotState::from_voltage(reading.high).unwrap_or(PilotState::F);
+
+        // The negative plateau must sit at -12V while oscillating, otherwise the
+        // vehicle diode is missing or shorted. Debounced like any other level so a
+        // single noisy sample does not end the session.
+        if self.is_pwm_enabled() && measured.is_vehicle_connected() {
+            if PilotState::from_voltage(reading.low) == Some(PilotState::F) {
+                self.diode_verified = true;
+            } else {
+                measured = PilotState::F;
+            }
+        }
+
+        if measured == self.state {
+            self.pending = None;
+            return None;
+        }
+
+        match self.pending {
+            Some((pending, since)) if pending == measured => {
+                if now.duration_since(since) >= DEBOUNCE {
+                    self.pending = None;
+                    self.transition(measured)
+                } else {
+                    None
+                }
+            }
+            _ => {
+                self.pending = Some((measured, now));
+                None
+            }
+        }
+    }
+
+    fn transition(&mut self, next: PilotState) -> Option<PilotEvent> {
+        let previous = self.state;
+        if previous == next {
+            return None;
+        }
+        self.state = next;
+
+        if next == PilotState::A {
+            // Never leave the pilot oscillating towards an empty socket
+            self.disable_pwm();
+            return Some(PilotEvent::VehicleDisconnected);
+        }
+        if next.is_fault() {
+            self.disable_pwm();
+            return Some(PilotEvent::Fault(next));
+        }
+        if next.is_energy_requested() {
+            return Some(PilotEvent::EnergyRequested);
+        }
+        if previous.is_energy_requested() {
+            return Some(PilotEvent::EnergyStopped);
+        }
+        Some(PilotEvent::VehicleConnected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steady(volts: f32) -> PilotReading {
+        PilotReading { high: volts, low: volts }
+    }
+
+    fn oscillating(high: f32, low: f32) -> PilotReading {
+        PilotReading { high, low }
+    }
+
+    fn assert_close(value: Option<f32>, expected: f32) {
+        let value = value.unwrap_or(f32::NAN);
+        assert!((value - expected).abs() < 0.01, "{} instead of {}", value, expected);
+    }
+
+    #[test]
+    fn voltage_bands_map_to_states() {
+        assert_eq!(PilotState::from_voltage(12.0), Some(PilotState::A));
+        assert_eq!(PilotState::from_voltage(11.0), Some(PilotState::A));
+        assert_eq!(PilotState::from_voltage(10.5), None);
+        assert_eq!(PilotState::from_voltage(10.0), Some(PilotState::B));
+        assert_eq!(PilotState::from_voltage(8.0), Some(PilotState::B));
+        assert_eq!(PilotState::from_voltage(7.0), Some(PilotState::C));
+        assert_eq!(PilotState::from_voltage(4.0), Some(PilotState::D));
+        assert_eq!(PilotState::from_voltage(1.5), None);
+        assert_eq!(PilotState::from_voltage(-1.0), Some(PilotState::E));
+        assert_eq!(PilotState::from_voltage(-12.0), Some(PilotState::F));
+        assert_eq!(PilotState::from_voltage(-14.0), None);
+        for state in [PilotState::A, PilotState::B, PilotState::C, PilotState::D, PilotState::E, PilotState::F] {
+            assert_eq!(PilotState::from_voltage(state.nominal_voltage()), Some(state));
+        }
+    }
+
+    #[test]
+    fn duty_cycle_and_current_convert_both_ways() {
+        assert_close(duty_cycle_for_current(6.0), 10.0);
+        assert_close(duty_cycle_for_current(30.0), 50.0);
+        assert_close(duty_cycle_for_current(80.0), 96.0);
+        assert_eq!(duty_cycle_for_current(5.0), None);
+        assert_eq!(duty_cycle_for_current(81.0), None);
+
+        assert_close(current_for_duty_cycle(10.0), 6.0);
+        assert_close(current_for_duty_cycle(85.0), 51.0);
+        assert_close(current_for_duty_cycle(90.0), 65.0);
+        assert_close(current_for_duty_cycle(97.0), 80.0);
+        assert_eq!(current_for_duty_cycle(5.0), None);
+        assert_eq!(current_for_duty_cycle(100.0), None);
+        for amps in [6.0, 16.0, 32.0, 51.0, 63.0, 80.0] {
+            let duty = duty_cycle_for_current(amps).unwrap();
+            assert!((current_for_duty_cycle(duty).unwrap() - amps).abs() < 0.01, "{} A", amps);
+        }
+    }
+
+    #[test]
+    fn levels_change_only_after_the_debounce_time() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut pilot = ControlPilot::new();
+
+        // A glitch shorter than the debounce time is ignored
+        assert_eq!(pilot.update(steady(9.0), at(0)), None);
+        assert_eq!(pilot.update(steady(12.0), at(50)), None);
+        assert_eq!(pilot.update(steady(9.0), at(60)), None);
+        assert_eq!(pilot.update(steady(9.0), at(150)), None);
+        assert_eq!(pilot.state(), PilotState::A);
+        assert_eq!(pilot.update(steady(9.0), at(160)), Some(PilotEvent::VehicleConnected));
+
+        pilot.enable_pwm(32.0);
+        assert_eq!(pilot.update(oscillating(9.0, -12.0), at(200)), None);
+        assert!(pilot.is_diode_verified());
+        assert_eq!(pilot.update(oscillating(6.0, -12.0), at(210)), None);
+        assert_eq!(pilot.update(oscillating(6.0, -12.0), at(310)), Some(PilotEvent::EnergyRequested));
+    }
+
+    #[test]
+    fn diode_failure_is_debounced_before_it_faults() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut pilot = ControlPilot::new();
+        pilot.update(steady(9.0), at(0));
+        pilot.update(steady(9.0), at(100));
+        pilot.enable_pwm(16.0);
+
+        // One sample without the negative plateau does not stop the session
+        assert_eq!(pilot.update(oscillating(9.0, 0.0), at(110)), None);
+        assert_eq!(pilot.update(oscillating(9.0, -12.0), at(120)), None);
+        assert_eq!(pilot.state(), PilotState::B);
+        assert!(pilot.is_pwm_enabled());
+
+        // A missing diode that persists is a fault and stops the PWM
+        assert_eq!(pilot.update(oscillating(9.0, 0.0), at(130)), None);
+        assert_eq!(pilot.update(oscillating(9.0, 0.0), at(230)), Some(PilotEvent::Fault(PilotState::F)));
+        assert!(!pilot.is_pwm_enabled());
+        assert!(!pilot.is_diode_verified());
+    }
+}
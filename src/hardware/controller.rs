@@ -0,0 +1,267 @@
+use std::time::Instant;
+use crate::hardware::control_pilot::{ControlPilot, PilotEvent, PilotState};
+use crate::hardware::{ChargerHardware, HardwareError, MeterReading};
+
+// Current advertised to the vehicle through the pilot PWM
+const DEFAULT_MAX_CURRENT_AMPS: f32 = 32.0;
+
+/// EVSE logic on top of a `ChargerHardware`: runs the control pilot state
+/// machine and enforces the contactor/cable lock interlocks. Screens poll it
+/// instead of keeping their own timers.
+pub struct ChargerController {
+    hardware: Box<dyn ChargerHardware>,
+    pilot: ControlPilot,
+    max_current: f32,
+    fault: Option<PilotState>,
+}
+
+impl ChargerController {
+    pub fn new(hardware: Box<dyn ChargerHardware>) -> Self {
+        Self {
+            hardware,
+            pilot: ControlPilot::new(),
+            max_current: DEFAULT_MAX_CURRENT_AMPS,
+            fault: None,
+        }
+    }
+
+    /// Current offered to the vehicle, in amps
+    pub fn with_max_current(mut self, max_current: f32) -> Self {
+        self.max_current = max_current;
+        self
+    }
+
+    pub fn pilot(&self) -> &ControlPilot {
+        &self.pilot
+    }
+
+    pub fn pilot_state(&self) -> PilotState {
+        self.pilot.state()
+    }
+
+    /// Pilot fault latched until the cable is unplugged
+    pub fn fault(&self) -> Option<PilotState> {
+        self.fault
+    }
+
+    pub fn max_current(&self) -> f32 {
+        self.max_current
+    }
+
+    pub fn is_cable_locked(&self) -> bool {
+        self.hardware.is_cable_locked()
+    }
+
+    pub fn is_energy_flowing(&self) -> bool {
+        self.hardware.is_contactor_closed()
+    }
+
+    /// Sample the pilot line and apply the EVSE side of IEC 61851-1
+    pub fn poll(&mut self) -> Option<PilotEvent> {
+        let reading = self.hardware.read_pilot();
+        let event = self.pilot.update(reading, Instant::now());
+
+        match event {
+            Some(PilotEvent::Fault(state)) => self.fault = Some(state),
+            Some(PilotEvent::VehicleDisconnected) => self.fault = None,
+            _ => {}
+        }
+
+        let state = self.pilot.state();
+
+        // Offer current as soon as a vehicle is detected, unless the last attempt failed
+        if state == PilotState::B && !self.pilot.is_pwm_enabled() && self.fault.is_none() {
+            self.pilot.enable_pwm(self.max_current);
+        }
+
+        // The contactor may only stay closed while the vehicle asks for energy
+        if self.hardware.is_contactor_closed()
+            && (!state.is_energy_requested() || self.hardware.is_emergency_stop_pressed())
+        {
+            self.open_contactor();
+        }
+
+        if state == PilotState::A && self.hardware.is_cable_locked() {
+            if let Err(err) = self.hardware.set_cable_lock(false) {
+                println!("Failed to release cable lock: {}", err);
+            }
+        }
+
+        self.hardware.set_pilot_pwm(self.pilot.duty_cycle());
+        event
+    }
+
+    /// Lock the cable and close the contactor
+    pub fn start_energy(&mut self) -> Result<(), HardwareError> {
+        if self.hardware.is_emergency_stop_pressed() {
+            return Err(HardwareError::EmergencyStop);
+        }
+        if !self.pilot.state().is_energy_requested() {
+            return Err(HardwareError::VehicleNotReady);
+        }
+        self.hardware.set_cable_lock(true)?;
+        if !self.hardware.is_cable_locked() {
+            return Err(HardwareError::CableLock);
+        }
+        self.hardware.set_contactor(true)
+    }
+
+    /// Open the contactor and release the cable so the driver can unplug
+    pub fn stop_energy(&mut self) {
+        self.open_contactor();
+        if let Err(err) = self.hardware.set_cable_lock(false) {
+            println!("Failed to release cable lock: {}", err);
+        }
+    }
+
+    pub fn read_meter(&mut self) -> MeterReading {
+        self.hardware.read_meter()
+    }
+
+    pub fn is_emergency_stop_pressed(&mut self) -> bool {
+        self.hardware.is_emergency_stop_pressed()
+    }
+
+    fn open_contactor(&mut self) {
+        if let Err(err) = self.hardware.set_contactor(false) {
+            println!("Failed to open contactor: {}", err);
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "hardware")))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::hardware::control_pilot::PilotReading;
+    use crate::hardware::simulated::SimulatedHardware;
+
+    // The simulator behind the controller, still reachable by the test to move the vehicle
+    struct Wired(Arc<Mutex<SimulatedHardware>>);
+
+    impl ChargerHardware for Wired {
+        fn read_pilot(&mut self) -> PilotReading {
+            self.0.lock().unwrap().read_pilot()
+        }
+
+        fn set_pilot_pwm(&mut self, duty_cycle: Option<f32>) {
+            self.0.lock().unwrap().set_pilot_pwm(duty_cycle)
+        }
+
+        fn set_contactor(&mut self, closed: bool) -> Result<(), HardwareError> {
+            self.0.lock().unwrap().set_contactor(closed)
+        }
+
+        fn is_contactor_closed(&self) -> bool {
+            self.0.lock().unwrap().is_contactor_closed()
+        }
+
+        fn read_meter(&mut self) -> MeterReading {
+            self.0.lock().unwrap().read_meter()
+        }
+
+        fn set_cable_lock(&mut self, locked: bool) -> Result<(), HardwareError> {
+            self.0.lock().unwrap().set_cable_lock(locked)
+        }
+
+        fn is_cable_locked(&self) -> bool {
+            self.0.lock().unwrap().is_cable_locked()
+        }
+
+        fn is_emergency_stop_pressed(&mut self) -> bool {
+            self.0.lock().unwrap().is_emergency_stop_pressed()
+        }
+    }
+
+    /// Controller on a simulator whose vehicle asks for energy `ready_delay` after the PWM starts
+    fn controller(ready_delay: Duration) -> (ChargerController, Arc<Mutex<SimulatedHardware>>) {
+        let simulator = SimulatedHardware::new().with_vehicle_delays(Duration::from_millis(10), ready_delay);
+        let simulator = Arc::new(Mutex::new(simulator));
+        (ChargerController::new(Box::new(Wired(simulator.clone()))), simulator)
+    }
+
+    /// Poll as the session does until `done` holds, for at most two seconds
+    fn poll_until(controller: &mut ChargerController, done: impl Fn(&ChargerController) -> bool) {
+        for _ in 0..200 {
+            if done(controller) {
+                return;
+            }
+            controller.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(done(controller), "stuck in {:?}", controller.pilot_state());
+    }
+
+    /// Plugged in, asking for energy and charging
+    fn energized() -> (ChargerController, Arc<Mutex<SimulatedHardware>>) {
+        let (mut controller, simulator) = controller(Duration::from_millis(10));
+        poll_until(&mut controller, |controller| controller.pilot_state() == PilotState::C);
+        controller.start_energy().unwrap();
+        (controller, simulator)
+    }
+
+    #[test]
+    fn contactor_and_lock_follow_the_vehicle_from_a_through_c_and_back() {
+        let (mut controller, simulator) = controller(Duration::from_millis(500));
+        assert_eq!(controller.pilot_state(), PilotState::A);
+        assert!(!controller.is_energy_flowing() && !controller.is_cable_locked());
+
+        // Current is offered once the vehicle is seen, but energy waits for state C
+        poll_until(&mut controller, |controller| controller.pilot_state() == PilotState::B);
+        assert!(controller.pilot().is_pwm_enabled());
+        assert!(matches!(controller.start_energy(), Err(HardwareError::VehicleNotReady)));
+        assert!(!controller.is_energy_flowing() && !controller.is_cable_locked());
+
+        poll_until(&mut controller, |controller| controller.pilot_state() == PilotState::C);
+        assert!(controller.pilot().is_diode_verified());
+        controller.start_energy().unwrap();
+        assert!(controller.is_energy_flowing() && controller.is_cable_locked());
+
+        simulator.lock().unwrap().unplug();
+        poll_until(&mut controller, |controller| controller.pilot_state() == PilotState::A);
+        assert!(!controller.is_energy_flowing() && !controller.is_cable_locked());
+        assert_eq!(controller.pilot().duty_cycle(), None);
+    }
+
+    #[test]
+    fn diode_fault_opens_the_contactor_and_blocks_energy_until_unplugged() {
+        let (mut controller, simulator) = energized();
+
+        simulator.lock().unwrap().short_diode();
+        poll_until(&mut controller, |controller| controller.fault().is_some());
+        assert_eq!(controller.fault(), Some(PilotState::F));
+        assert!(!controller.is_energy_flowing());
+
+        // The vehicle falls back to B without PWM; no current is offered again
+        poll_until(&mut controller, |controller| controller.pilot_state() == PilotState::B);
+        for _ in 0..20 {
+            controller.poll();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(controller.pilot().duty_cycle(), None);
+        assert!(matches!(controller.start_energy(), Err(HardwareError::VehicleNotReady)));
+        assert!(!controller.is_energy_flowing());
+
+        simulator.lock().unwrap().unplug();
+        poll_until(&mut controller, |controller| controller.pilot_state() == PilotState::A);
+        assert_eq!(controller.fault(), None);
+        assert!(!controller.is_cable_locked());
+    }
+
+    #[test]
+    fn emergency_stop_opens_the_contactor_and_refuses_to_close_it() {
+        let (mut controller, simulator) = energized();
+
+        simulator.lock().unwrap().set_emergency_stop(true);
+        controller.poll();
+        assert!(!controller.is_energy_flowing());
+        assert!(controller.is_emergency_stop_pressed());
+        assert!(matches!(controller.start_energy(), Err(HardwareError::EmergencyStop)));
+        assert!(!controller.is_energy_flowing());
+
+        simulator.lock().unwrap().set_emergency_stop(false);
+        controller.start_energy().unwrap();
+        assert!(controller.is_energy_flowing());
+    }
+}
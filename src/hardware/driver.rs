@@ -0,0 +1,200 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::hardware::control_pilot::{PilotReading, PilotState};
+use crate::hardware::{ChargerHardware, HardwareError, MeterReading};
+
+// 1 kHz pilot oscillator, in nanoseconds as expected by the sysfs PWM class
+const PWM_PERIOD_NS: u64 = 1_000_000;
+
+/// Linux sysfs locations of the charger peripherals (IIO ADC, PWM, GPIO).
+/// The meter is read from files kept up to date by the meter daemon.
+/// Set in the `[sysfs]` section of the kiosk configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SysfsPaths {
+    pub pilot_high_adc: PathBuf,
+    pub pilot_low_adc: PathBuf,
+    pub adc_scale: f32,  // Volts per ADC count after the pilot front-end
+    pub adc_offset: f32, // Volts at ADC count 0
+    pub pilot_pwm: PathBuf,
+    pub contactor_gpio: PathBuf,
+    pub contactor_feedback_gpio: PathBuf,
+    pub cable_lock_gpio: PathBuf,
+    pub cable_lock_feedback_gpio: PathBuf,
+    pub emergency_stop_gpio: PathBuf, // Active low
+    pub meter_energy_wh: PathBuf,
+    pub meter_power_w: PathBuf,
+    pub meter_voltage_v: PathBuf,
+    pub meter_current_a: PathBuf,
+}
+
+impl Default for SysfsPaths {
+    fn default() -> Self {
+        Self {
+            pilot_high_adc: "/sys/bus/iio/devices/iio:device0/in_voltage0_raw".into(),
+            pilot_low_adc: "/sys/bus/iio/devices/iio:device0/in_voltage1_raw".into(),
+            adc_scale: 24.0 / 4095.0,
+            adc_offset: -12.0,
+            pilot_pwm: "/sys/class/pwm/pwmchip0/pwm0".into(),
+            contactor_gpio: "/sys/class/gpio/gpio17/value".into(),
+            contactor_feedback_gpio: "/sys/class/gpio/gpio27/value".into(),
+            cable_lock_gpio: "/sys/class/gpio/gpio22/value".into(),
+            cable_lock_feedback_gpio: "/sys/class/gpio/gpio23/value".into(),
+            emergency_stop_gpio: "/sys/class/gpio/gpio24/value".into(),
+            meter_energy_wh: "/run/ev-charger/meter/energy_wh".into(),
+            meter_power_w: "/run/ev-charger/meter/power_w".into(),
+            meter_voltage_v: "/run/ev-charger/meter/voltage_v".into(),
+            meter_current_a: "/run/ev-charger/meter/current_a".into(),
+        }
+    }
+}
+
+/// Real charger peripherals, enabled with the `hardware` cargo feature
+pub struct SysfsHardware {
+    paths: SysfsPaths,
+    // Duty cycle last written to the PWM, so the controller can set it every poll
+    pwm_duty_ns: Option<u64>,
+}
+
+impl SysfsHardware {
+    pub fn new(paths: SysfsPaths) -> Self {
+        Self { paths, pwm_duty_ns: None }
+    }
+
+    fn read_adc_volts(&self, path: &Path) -> io::Result<f32> {
+        let raw: f32 = read_number(path)?;
+        Ok(raw * self.paths.adc_scale + self.paths.adc_offset)
+    }
+
+    /// Only touches sysfs when the duty cycle changes; the period and enable
+    /// are written once, and again after a failed write
+    fn write_pwm(&mut self, duty_cycle: Option<f32>) -> io::Result<()> {
+        // A constant +12V is a 100% duty cycle on the oscillator
+        let duty = duty_cycle.unwrap_or(100.0).clamp(0.0, 100.0);
+        let duty_ns = (PWM_PERIOD_NS as f64 * duty as f64 / 100.0) as u64;
+        if self.pwm_duty_ns == Some(duty_ns) {
+            return Ok(());
+        }
+        let configured = self.pwm_duty_ns.take().is_some();
+        if !configured {
+            fs::write(self.paths.pilot_pwm.join("period"), PWM_PERIOD_NS.to_string())?;
+        }
+        fs::write(self.paths.pilot_pwm.join("duty_cycle"), duty_ns.to_string())?;
+        if !configured {
+            fs::write(self.paths.pilot_pwm.join("enable"), "1")?;
+        }
+        self.pwm_duty_ns = Some(duty_ns);
+        Ok(())
+    }
+}
+
+fn read_number<T: std::str::FromStr>(path: &Path) -> io::Result<T> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a number", path.display())))
+}
+
+fn read_gpio(path: &Path) -> io::Result<bool> {
+    Ok(read_number::<u8>(path)? != 0)
+}
+
+fn write_gpio(path: &Path, high: bool) -> io::Result<()> {
+    fs::write(path, if high { "1" } else { "0" })
+}
+
+impl ChargerHardware for SysfsHardware {
+    fn read_pilot(&mut self) -> PilotReading {
+        let high = self.read_adc_volts(&self.paths.pilot_high_adc);
+        let low = self.read_adc_volts(&self.paths.pilot_low_adc);
+        match (high, low) {
+            (Ok(high), Ok(low)) => PilotReading { high, low },
+            (Err(err), _) | (_, Err(err)) => {
+                // An unreadable pilot must never look like a connected vehicle
+                println!("Pilot ADC read failed: {}", err);
+                let level = PilotState::F.nominal_voltage();
+                PilotReading { high: level, low: level }
+            }
+        }
+    }
+
+    fn set_pilot_pwm(&mut self, duty_cycle: Option<f32>) {
+        if let Err(err) = self.write_pwm(duty_cycle) {
+            println!("Pilot PWM write failed: {}", err);
+        }
+    }
+
+    fn set_contactor(&mut self, closed: bool) -> Result<(), HardwareError> {
+        if closed && self.is_emergency_stop_pressed() {
+            return Err(HardwareError::EmergencyStop);
+        }
+        write_gpio(&self.paths.contactor_gpio, closed)?;
+        Ok(())
+    }
+
+    fn is_contactor_closed(&self) -> bool {
+        // Trust the auxiliary contact, not the coil output
+        read_gpio(&self.paths.contactor_feedback_gpio).unwrap_or(true)
+    }
+
+    fn read_meter(&mut self) -> MeterReading {
+        let reading = (|| -> io::Result<MeterReading> {
+            Ok(MeterReading {
+                energy_wh: read_number(&self.paths.meter_energy_wh)?,
+                power_w: read_number(&self.paths.meter_power_w)?,
+                voltage_v: read_number(&self.paths.meter_voltage_v)?,
+                current_a: read_number(&self.paths.meter_current_a)?,
+                vehicle_soc: None,
+            })
+        })();
+        reading.unwrap_or_else(|err| {
+            println!("Meter read failed: {}", err);
+            MeterReading::default()
+        })
+    }
+
+    fn set_cable_lock(&mut self, locked: bool) -> Result<(), HardwareError> {
+        write_gpio(&self.paths.cable_lock_gpio, locked)?;
+        Ok(())
+    }
+
+    fn is_cable_locked(&self) -> bool {
+        read_gpio(&self.paths.cable_lock_feedback_gpio).unwrap_or(false)
+    }
+
+    fn is_emergency_stop_pressed(&mut self) -> bool {
+        // Fail safe: an unreadable input counts as pressed
+        read_gpio(&self.paths.emergency_stop_gpio).map(|level| !level).unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pwm_is_written_only_when_the_duty_cycle_changes() {
+        let dir = std::env::temp_dir().join(format!("ev-charger-pwm-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut hardware = SysfsHardware::new(SysfsPaths { pilot_pwm: dir.clone(), ..SysfsPaths::default() });
+        let read = |name: &str| fs::read_to_string(dir.join(name)).ok();
+
+        hardware.write_pwm(Some(50.0)).unwrap();
+        assert_eq!(read("period").as_deref(), Some("1000000"));
+        assert_eq!(read("duty_cycle").as_deref(), Some("500000"));
+        assert_eq!(read("enable").as_deref(), Some("1"));
+
+        fs::remove_file(dir.join("duty_cycle")).unwrap();
+        fs::remove_file(dir.join("period")).unwrap();
+        hardware.write_pwm(Some(50.0)).unwrap();
+        assert_eq!(read("duty_cycle"), None);
+
+        // A new duty cycle only rewrites the duty cycle
+        hardware.write_pwm(None).unwrap();
+        assert_eq!(read("duty_cycle").as_deref(), Some("1000000"));
+        assert_eq!(read("period"), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::{Arc, Mutex};
+use control_pilot::PilotReading;
+use crate::config::KioskConfig;
+
+pub mod battery;
+pub mod control_pilot;
+pub mod controller;
+#[cfg(not(feature = "hardware"))]
+pub mod simulated;
+#[cfg(feature = "hardware")]
+pub mod driver;
+
+pub use controller::ChargerController;
+
+// Connector the kiosk drives unless configured otherwise
+pub const CONNECTOR_ID: u32 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MeterReading {
+    pub energy_wh: f64,           // Cumulative meter register
+    pub power_w: f32,
+    pub voltage_v: f32,
+    pub current_a: f32,
+    pub vehicle_soc: Option<f32>, // Percent, only when the vehicle reports it (DC)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HardwareError {
+    #[error("emergency stop is engaged")]
+    EmergencyStop,
+    #[error("vehicle is not requesting energy")]
+    VehicleNotReady,
+    #[error("cable lock did not engage")]
+    CableLock,
+    #[error("device I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Low-level charger peripherals. The EVSE logic (pilot state machine, safety
+/// interlocks) lives in `ChargerController`, so implementations only move bits.
+pub trait ChargerHardware: Send {
+    fn read_pilot(&mut self) -> PilotReading;
+    /// `None` drives a constant +12V, `Some(duty)` oscillates at 1 kHz
+    fn set_pilot_pwm(&mut self, duty_cycle: Option<f32>);
+    fn set_contactor(&mut self, closed: bool) -> Result<(), HardwareError>;
+    fn is_contactor_closed(&self) -> bool;
+    fn read_meter(&mut self) -> MeterReading;
+    fn set_cable_lock(&mut self, locked: bool) -> Result<(), HardwareError>;
+    fn is_cable_locked(&self) -> bool;
+    fn is_emergency_stop_pressed(&mut self) -> bool;
+}
+
+pub type SharedCharger = Arc<Mutex<ChargerController>>;
+
+/// Build the charger for this build: real peripherals with the `hardware`
+/// feature, the simulator otherwise.
+pub fn create_charger(config: &KioskConfig) -> SharedCharger {
+    #[cfg(feature = "hardware")]
+    let hardware: Box<dyn ChargerHardware> = Box::new(driver::SysfsHardware::new(config.sysfs.clone()));
+    #[cfg(not(feature = "hardware"))]
+    let hardware: Box<dyn ChargerHardware> = Box::new(
+        simulated::SimulatedHardware::new()
+            .with_max_power(config.charger.max_power_kw)
+            .with_supply(config.charger.supply())
+            .with_vehicle(config.vehicle.battery())
+            .with_time_scale(config.charger.time_scale),
+    );
+
+    let controller = ChargerController::new(hardware).with_max_current(config.connector().max_current_a);
+    Arc::new(Mutex::new(controller))
+}
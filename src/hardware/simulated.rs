@@ -0,0 +1,274 @@
+use std::time::{Duration, Instant};
+use crate::hardware::battery::{BatteryPack, Supply};
+use crate::hardware::control_pilot::{self, PilotReading, PilotState};
+use crate::hardware::{ChargerHardware, HardwareError, MeterReading};
+
+// Simulated seconds per wall-clock second, so a session finishes in minutes
+const DEFAULT_TIME_SCALE: f32 = 20.0;
+const DC_VOLTAGE: f32 = 400.0;
+// Three-phase supply, per phase
+const AC_VOLTAGE: f32 = 230.0;
+const AC_PHASES: f32 = 3.0;
+// The driver walks away this long (wall clock) after charging stops; a new vehicle then arrives
+const UNPLUG_DELAY: Duration = Duration::from_secs(20);
+
+/// Vehicle side of the pilot circuit, scripted for development without a real inlet.
+/// Plugs itself in `plug_delay` after the first reading and closes S2 (state C)
+/// once the EVSE has been oscillating for `ready_delay`; its `BatteryPack`
+/// decides how much power it asks for. `SimulatedHardware` replaces it with a
+/// fresh vehicle once it has been unplugged.
+pub struct SimulatedVehicle {
+    started_at: Option<Instant>,
+    plug_delay: Duration,
+    ready_delay: Duration,
+    plugged: bool,
+    pwm_seen_at: Option<Instant>,
+    s2_closed: bool,
+    // A missing or shorted diode leaves the negative half of the PWM unclamped
+    diode_shorted: bool,
+    battery: BatteryPack,
+}
+
+impl SimulatedVehicle {
+    pub fn new(battery: BatteryPack) -> Self {
+        Self {
+            started_at: None,
+            plug_delay: Duration::from_secs(1),
+            ready_delay: Duration::from_millis(1500),
+            plugged: false,
+            pwm_seen_at: None,
+            s2_closed: false,
+            diode_shorted: false,
+            battery,
+        }
+    }
+
+    pub fn soc(&self) -> f32 {
+        self.battery.soc
+    }
+
+    fn is_full(&self) -> bool {
+        self.battery.is_full()
+    }
+
+    /// Power the vehicle is willing to take right now
+    fn accepted_power_kw(&self, supply: Supply) -> f32 {
+        if self.s2_closed && !self.is_full() {
+            self.battery.acceptance_kw(supply)
+        } else {
+            0.0
+        }
+    }
+
+    /// Voltage seen on the pilot line for the given EVSE output
+    pub fn pilot_reading(&mut self, duty_cycle: Option<f32>, now: Instant) -> PilotReading {
+        let started_at = *self.started_at.get_or_insert(now);
+        if !self.plugged && now.duration_since(started_at) >= self.plug_delay {
+            self.plugged = true;
+        }
+
+        if !self.plugged {
+            let level = PilotState::A.nominal_voltage();
+            return PilotReading { high: level, low: if duty_cycle.is_some() { -12.0 } else { level } };
+        }
+
+        match duty_cycle {
+            Some(_) => {
+                let seen_at = *self.pwm_seen_at.get_or_insert(now);
+                // A full battery opens S2 again and falls back to state B
+                self.s2_closed = now.duration_since(seen_at) >= self.ready_delay && !self.is_full();
+            }
+            None => {
+                self.pwm_seen_at = None;
+                self.s2_closed = false;
+            }
+        }
+
+        let state = if self.s2_closed { PilotState::C } else { PilotState::B };
+        let high = state.nominal_voltage();
+        // The vehicle diode clamps the negative half of the PWM to -12V
+        let low = if duty_cycle.is_some() && !self.diode_shorted { -12.0 } else { high };
+        PilotReading { high, low }
+    }
+}
+
+/// Default `ChargerHardware` used when the `hardware` feature is off: a DC or
+/// AC power stage, meter and cable lock wired to a `SimulatedVehicle`.
+pub struct SimulatedHardware {
+    vehicle: SimulatedVehicle,
+    arriving: BatteryPack,
+    supply: Supply,
+    duty_cycle: Option<f32>,
+    contactor_closed: bool,
+    cable_locked: bool,
+    emergency_stop: bool,
+    max_power_kw: f32,
+    time_scale: f32,
+    energy_wh: f64,
+    power_w: f32,
+    last_tick: Instant,
+    charging_stopped_at: Option<Instant>,
+}
+
+impl SimulatedHardware {
+    pub fn new() -> Self {
+        Self {
+            vehicle: SimulatedVehicle::new(BatteryPack::default()),
+            arriving: BatteryPack::default(),
+            supply: Supply::Dc,
+            duty_cycle: None,
+            contactor_closed: false,
+            cable_locked: false,
+            emergency_stop: false,
+            max_power_kw: 50.0,
+            time_scale: DEFAULT_TIME_SCALE,
+            energy_wh: 0.0,
+            power_w: 0.0,
+            last_tick: Instant::now(),
+            charging_stopped_at: None,
+        }
+    }
+
+    /// Power stage limit in kW
+    pub fn with_max_power(mut self, max_power_kw: f32) -> Self {
+        self.max_power_kw = max_power_kw;
+        self
+    }
+
+    pub fn with_supply(mut self, supply: Supply) -> Self {
+        self.supply = supply;
+        self
+    }
+
+    /// Battery of every vehicle that plugs in
+    pub fn with_vehicle(mut self, battery: BatteryPack) -> Self {
+        self.vehicle = SimulatedVehicle::new(battery.clone());
+        self.arriving = battery;
+        self
+    }
+
+    pub fn with_time_scale(mut self, time_scale: f32) -> Self {
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// Vehicle that plugs in after `plug_delay` and asks for energy `ready_delay` after the PWM starts
+    #[cfg(test)]
+    pub fn with_vehicle_delays(mut self, plug_delay: Duration, ready_delay: Duration) -> Self {
+        self.vehicle.plug_delay = plug_delay;
+        self.vehicle.ready_delay = ready_delay;
+        self
+    }
+
+    /// Pull the cable out now; no other vehicle arrives
+    #[cfg(test)]
+    pub fn unplug(&mut self) {
+        self.vehicle = SimulatedVehicle::new(self.arriving.clone());
+        self.vehicle.plug_delay = Duration::MAX;
+    }
+
+    #[cfg(test)]
+    pub fn short_diode(&mut self) {
+        self.vehicle.diode_shorted = true;
+    }
+
+    #[cfg(test)]
+    pub fn set_emergency_stop(&mut self, pressed: bool) {
+        self.emergency_stop = pressed;
+    }
+
+    /// Advance the power stage up to now
+    fn tick(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_tick).as_secs_f32() * self.time_scale;
+        self.last_tick = now;
+
+        let power_kw = if self.contactor_closed && !self.emergency_stop {
+            self.output_limit_kw().min(self.vehicle.accepted_power_kw(self.supply))
+        } else {
+            0.0
+        };
+        // The pack keeps cooling down while nothing flows
+        self.vehicle.battery.charge(power_kw, elapsed);
+        self.energy_wh += (power_kw * elapsed / 3600.0) as f64 * 1000.0;
+        self.power_w = power_kw * 1000.0;
+    }
+
+    /// Most the power stage delivers: on AC the vehicle also keeps to the pilot current
+    fn output_limit_kw(&self) -> f32 {
+        match self.supply {
+            Supply::Dc => self.max_power_kw,
+            Supply::Ac => {
+                let amps = self.duty_cycle.and_then(control_pilot::current_for_duty_cycle).unwrap_or(0.0);
+                self.max_power_kw.min(amps * AC_VOLTAGE * AC_PHASES / 1000.0)
+            }
+        }
+    }
+}
+
+impl ChargerHardware for SimulatedHardware {
+    fn read_pilot(&mut self) -> PilotReading {
+        self.tick();
+        let now = Instant::now();
+        if self.charging_stopped_at.is_some_and(|stopped_at| now.duration_since(stopped_at) >= UNPLUG_DELAY) {
+            self.charging_stopped_at = None;
+            self.vehicle = SimulatedVehicle::new(self.arriving.clone());
+        }
+        self.vehicle.pilot_reading(self.duty_cycle, now)
+    }
+
+    fn set_pilot_pwm(&mut self, duty_cycle: Option<f32>) {
+        self.duty_cycle = duty_cycle;
+    }
+
+    fn set_contactor(&mut self, closed: bool) -> Result<(), HardwareError> {
+        if closed && self.emergency_stop {
+            return Err(HardwareError::EmergencyStop);
+        }
+        self.tick();
+        if self.contactor_closed && !closed {
+            self.charging_stopped_at = Some(Instant::now());
+        } else if closed {
+            self.charging_stopped_at = None;
+        }
+        self.contactor_closed = closed;
+        Ok(())
+    }
+
+    fn is_contactor_closed(&self) -> bool {
+        self.contactor_closed
+    }
+
+    fn read_meter(&mut self) -> MeterReading {
+        self.tick();
+        match self.supply {
+            Supply::Dc => MeterReading {
+                energy_wh: self.energy_wh,
+                power_w: self.power_w,
+                voltage_v: DC_VOLTAGE,
+                current_a: self.power_w / DC_VOLTAGE,
+                vehicle_soc: Some(self.vehicle.soc()),
+            },
+            Supply::Ac => MeterReading {
+                energy_wh: self.energy_wh,
+                power_w: self.power_w,
+                voltage_v: AC_VOLTAGE,
+                current_a: self.power_w / (AC_VOLTAGE * AC_PHASES),
+                vehicle_soc: Some(self.vehicle.soc()),
+            },
+        }
+    }
+
+    fn set_cable_lock(&mut self, locked: bool) -> Result<(), HardwareError> {
+        self.cable_locked = locked;
+        Ok(())
+    }
+
+    fn is_cable_locked(&self) -> bool {
+        self.cable_locked
+    }
+
+    fn is_emergency_stop_pressed(&mut self) -> bool {
+        self.emergency_stop
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CatalogError {
+    #[error("cannot parse message catalog: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("message `{0}` is not a string")]
+    NotText(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    En,
+    Ko,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Ko];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ko => "ko",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|locale| code == locale.code() || code.starts_with(&format!("{}_", locale.code())))
+    }
+
+    /// The language's own name, as shown on the language toggle
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Ko => "한국어",
+        }
+    }
+
+    /// The next language on the toggle
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&locale| locale == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../../locales/en.toml"),
+            Locale::Ko => include_str!("../../locales/ko.toml"),
+        }
+    }
+}
+
+/// Messages for one language, keyed by `section.name`
+#[derive(Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn parse(source: &str) -> Result<Self, CatalogError> {
+        let table: toml::Table = toml::from_str(source)?;
+        let mut messages = HashMap::new();
+        flatten("", &table, &mut messages)?;
+        Ok(Self { messages })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.messages.iter().map(|(key, text)| (key.as_str(), text.as_str()))
+    }
+}
+
+fn flatten(prefix: &str, table: &toml::Table, messages: &mut HashMap<String, String>) -> Result<(), CatalogError> {
+    for (name, value) in table {
+        let key = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        match value {
+            toml::Value::String(text) => {
+                messages.insert(key, text.clone());
+            }
+            toml::Value::Table(table) => flatten(&key, table, messages)?,
+            _ => return Err(CatalogError::NotText(key)),
+        }
+    }
+    Ok(())
+}
+
+struct LocaleState {
+    current: Locale,
+    default: Locale,
+}
+
+static STATE: RwLock<LocaleState> = RwLock::new(LocaleState { current: Locale::En, default: Locale::En });
+
+/// The bundled catalogs, parsed on first use
+pub fn catalog(locale: Locale) -> &'static Catalog {
+    static CATALOGS: OnceLock<HashMap<Locale, Catalog>> = OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| {
+        Locale::ALL
+            .into_iter()
+            .map(|locale| {
+                // 번들 카탈로그는 테스트로 검증되므로 실패 시 빈 카탈로그 (키가 그대로 표시됨)
+                let catalog = Catalog::parse(locale.source()).unwrap_or_else(|e| {
+                    println!("Invalid {} message catalog: {}", locale.code(), e);
+                    Catalog::default()
+                });
+                (locale, catalog)
+            })
+            .collect()
+    });
+    &catalogs[&locale]
+}
+
+/// Set the language the kiosk returns to after every session
+pub fn init(default: Locale) {
+    let mut state = STATE.write().unwrap_or_else(|e| e.into_inner());
+    state.default = default;
+    state.current = default;
+}
+
+pub fn current() -> Locale {
+    STATE.read().unwrap_or_else(|e| e.into_inner()).current
+}
+
+/// Switch language for the current customer
+pub fn set_locale(locale: Locale) {
+    STATE.write().unwrap_or_else(|e| e.into_inner()).current = locale;
+}
+
+/// Back to the default language once the customer is done
+pub fn reset_locale() {
+    let mut state = STATE.write().unwrap_or_else(|e| e.into_inner());
+    state.current = state.default;
+}
+
+/// The message for `key` in the current language, falling back to English
+/// and then to the key itself
+pub fn t(key: &str) -> String {
+    lookup(current(), key).to_string()
+}
+
+/// Like [`t`], replacing `{name}` placeholders with `args`
+pub fn t_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    format_message(lookup(current(), key), args)
+}
+
+fn lookup(locale: Locale, key: &str) -> &str {
+    catalog(locale)
+        .get(key)
+        .or_else(|| catalog(Locale::En).get(key))
+        .unwrap_or(key)
+}
+
+fn format_message(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut message = template.to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `{name}` placeholders used by a message
+    fn placeholders(message: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    #[test]
+    fn bundles_share_keys_and_placeholders() {
+        let english = catalog(Locale::En);
+        assert!(english.get("standby.full_charge").is_some());
+        for locale in Locale::ALL {
+            let bundle = Catalog::parse(locale.source()).unwrap();
+            let mut keys: Vec<&str> = bundle.iter().map(|(key, _)| key).collect();
+            let mut expected: Vec<&str> = english.iter().map(|(key, _)| key).collect();
+            keys.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(keys, expected, "{} catalog keys", locale.code());
+
+            for key in expected {
+                assert_eq!(
+                    placeholders(bundle.get(key).unwrap()),
+                    placeholders(english.get(key).unwrap()),
+                    "{} placeholders in {}",
+                    locale.code(),
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn formats_messages_and_parses_locales() {
+        let message = format_message("Idle fee {fee} ({rate}/min)", &[("fee", &"₩500"), ("rate", &100)]);
+        assert_eq!(message, "Idle fee ₩500 (100/min)");
+        assert_eq!(lookup(Locale::Ko, "no.such.key"), "no.such.key");
+
+        assert_eq!(Locale::parse("ko_KR.UTF-8"), Some(Locale::Ko));
+        assert_eq!(Locale::parse("EN"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr"), None);
+        assert_eq!(Locale::En.next(), Locale::Ko);
+        assert_eq!(Locale::Ko.next(), Locale::En);
+    }
+}
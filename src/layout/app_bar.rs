@@ -1,17 +1,18 @@
 use eframe::egui;
+use crate::theme;
+use crate::i18n;
 
 pub struct AppBar {
-    title: String,
+    /// Catalog key, looked up on every frame so a language switch applies immediately
+    title_key: String,
     show_back_button: bool,
-    back_clicked: bool,
 }
 
 impl AppBar {
-    pub fn new(title: &str) -> Self {
+    pub fn new(title_key: &str) -> Self {
         Self {
-            title: title.to_string(),
+            title_key: title_key.to_string(),
             show_back_button: false,
-            back_clicked: false,
         }
     }
 
@@ -20,17 +21,12 @@ impl AppBar {
         self
     }
 
-    pub fn is_back_clicked(&self) -> bool {
-        self.back_clicked
-    }
-
-    pub fn reset_back_clicked(&mut self) {
-        self.back_clicked = false;
-    }
-
-    pub fn show(&mut self, ui: &mut egui::Ui, scale: f32) {
+    /// Returns true when the back button was clicked this frame
+    pub fn show(&self, ui: &mut egui::Ui, scale: f32) -> bool {
+        let theme = theme::current();
+        let mut back_clicked = false;
         egui::TopBottomPanel::top("app_bar")
-            .frame(egui::Frame::default().fill(egui::Color32::from_rgba_premultiplied(20, 20, 25, 200)))
+            .frame(egui::Frame::default().fill(theme.colors.bar))
             .show(ui.ctx(), |ui| {
                 ui.horizontal(|ui| {
                     ui.spacing_mut().item_spacing = egui::vec2(15.0 * scale, 0.0);
@@ -39,35 +35,33 @@ impl AppBar {
                     if self.show_back_button {
                         let back_btn = egui::Button::new(
                             egui::RichText::new("←")
-                                .font(egui::FontId::proportional(20.0 * scale))
-                                .color(egui::Color32::WHITE),
+                                .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                .color(theme.colors.text),
                         )
                         .min_size(egui::vec2(40.0 * scale, 35.0 * scale))
-                        .fill(egui::Color32::from_rgba_premultiplied(60, 60, 80, 255))
-                        .corner_radius(egui::CornerRadius::same(8));
+                        .fill(theme.colors.secondary)
+                        .corner_radius(egui::CornerRadius::same(theme.radii.small));
 
                         let resp = ui.add(back_btn);
                         if resp.hovered() || resp.is_pointer_button_down_on() {
                             let glow_rect = resp.rect.expand(4.0 * scale);
                             let glow_shape = egui::epaint::RectShape::filled(
                                 glow_rect,
-                                egui::CornerRadius::same((8.0 * scale) as u8),
-                                egui::Color32::from_rgba_premultiplied(100, 100, 120, 80),
+                                egui::CornerRadius::same((theme.radii.small as f32 * scale) as u8),
+                                theme.glow(theme.colors.secondary),
                             ).with_blur_width(8.0 * scale);
                             ui.painter().add(glow_shape);
                         }
-                        if resp.clicked() {
-                            self.back_clicked = true;
-                        }
+                        back_clicked = resp.clicked();
                     }
 
                     // Title
                     ui.vertical_centered(|ui| {
                         ui.add_space(5.0 * scale);
                         ui.add(egui::Label::new(
-                            egui::RichText::new(&self.title)
-                                .font(egui::FontId::proportional(20.0 * scale))
-                                .color(egui::Color32::WHITE),
+                            egui::RichText::new(i18n::t(&self.title_key))
+                                .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                .color(theme.colors.text),
                         ));
                         ui.add_space(5.0 * scale);
                     });
@@ -82,5 +76,6 @@ impl AppBar {
 
                 ui.add_space(5.0 * scale);
             });
+        back_clicked
     }
 }
@@ -0,0 +1,21 @@
+use eframe::egui;
+use crate::theme;
+use crate::auth::{AuthorizationStatus, CardRead};
+use crate::i18n;
+
+/// Feedback for a card presented at the RFID reader
+pub fn card_notice(ui: &mut egui::Ui, read: &CardRead, scale: f32) {
+    let theme = theme::current();
+    let (text, color) = match read.status {
+        AuthorizationStatus::Accepted => (i18n::t_args("card.accepted", &[("token", &read.token)]), theme.colors.success_text),
+        AuthorizationStatus::Blocked => (i18n::t("card.blocked"), theme.colors.danger),
+        AuthorizationStatus::Expired => (i18n::t("card.expired"), theme.colors.danger),
+        AuthorizationStatus::Invalid => (i18n::t("card.invalid"), theme.colors.danger),
+    };
+
+    ui.add(egui::Label::new(
+        egui::RichText::new(text)
+            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+            .color(color),
+    ));
+}
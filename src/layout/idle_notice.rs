@@ -0,0 +1,51 @@
+use eframe::egui;
+use crate::theme;
+use crate::tariff::{IdleTimer, Tariff};
+use crate::i18n;
+
+/// Overstay warning shown once charging has finished: counts down the grace
+/// period, then shows the idle fee accruing until the cable is unplugged.
+pub fn idle_notice(ui: &mut egui::Ui, tariff: &Tariff, idle: &IdleTimer, scale: f32) {
+    let theme = theme::current();
+    let elapsed = idle.elapsed();
+    let fee = tariff.idle_fee(elapsed);
+
+    let (text, color) = if idle.is_stopped() {
+        if fee > 0.0 {
+            (i18n::t_args("idle.fee", &[("fee", &tariff.format_amount(fee))]), theme.colors.danger)
+        } else {
+            (i18n::t("idle.unplugged"), theme.colors.success_text)
+        }
+    } else if tariff.idle_fee_per_minute <= 0.0 {
+        (i18n::t("idle.unplug"), theme.colors.warning)
+    } else if fee <= 0.0 {
+        let remaining = tariff.idle_grace_period().saturating_sub(elapsed).as_secs();
+        (
+            i18n::t_args(
+                "idle.grace",
+                &[
+                    ("rate", &tariff.format_amount(tariff.idle_fee_per_minute)),
+                    ("remaining", &format!("{}:{:02}", remaining / 60, remaining % 60)),
+                ],
+            ),
+            theme.colors.warning,
+        )
+    } else {
+        (
+            i18n::t_args(
+                "idle.accruing",
+                &[
+                    ("fee", &tariff.format_amount(fee)),
+                    ("rate", &tariff.format_amount(tariff.idle_fee_per_minute)),
+                ],
+            ),
+            theme.colors.danger,
+        )
+    };
+
+    ui.add(egui::Label::new(
+        egui::RichText::new(text)
+            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+            .color(color),
+    ));
+}
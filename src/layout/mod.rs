@@ -3,5 +3,7 @@ pub mod stepper;
 pub mod shadow_button;
 pub mod app_bar;
 pub mod app_container;
+pub mod idle_notice;
+pub mod card_notice;
 
 
@@ -1,4 +1,5 @@
 use eframe::egui;
+use crate::theme;
 
 pub fn shadow_button(
     ui: &mut egui::Ui,
@@ -9,9 +10,10 @@ pub fn shadow_button(
     scale: f32,
     radius: f32,
 ) -> egui::Response {
+    let theme = theme::current();
     let button = egui::Button::new(
         egui::RichText::new(text)
-            .font(egui::FontId::proportional(16.0 * scale))
+            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
             .color(text_color),
     )
     .min_size(min_size)
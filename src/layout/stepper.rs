@@ -1,39 +1,72 @@
 use eframe::egui;
+use std::cmp::Ordering;
+use crate::theme;
+use crate::i18n;
 
+/// Catalog keys of the customer journey steps, in order
+pub const JOURNEY_STEPS: [&str; 5] = [
+    "stepper.select",
+    "stepper.connect",
+    "stepper.payment",
+    "stepper.charging",
+    "stepper.complete",
+];
+
+/// Step indicator centered on one row: done steps, the `current` step and the ones still ahead
 pub fn stepper(ui: &mut egui::Ui, labels: &[&str], current: usize, scale: f32) {
+    let theme = theme::current();
     let dot_size = 18.0 * scale;
-    let spacing = 16.0 * scale;
+    let gap = 6.0 * scale;
+    let spacing = 28.0 * scale;
     let line_thickness = 3.0 * scale;
+    let font = egui::FontId::proportional(theme.font_sizes.caption * scale);
+
+    let galleys: Vec<_> = labels
+        .iter()
+        .enumerate()
+        .map(|(idx, label)| {
+            let color = if idx == current { theme.colors.text } else { theme.colors.text_muted };
+            ui.painter().layout_no_wrap(label.to_string(), font.clone(), color)
+        })
+        .collect();
+    let width: f32 = galleys.iter().map(|galley| dot_size + gap + galley.size().x).sum::<f32>()
+        + spacing * labels.len().saturating_sub(1) as f32;
+
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), dot_size), egui::Sense::hover());
+    let painter = ui.painter();
+    let y = rect.center().y;
+    let mut x = rect.center().x - width / 2.0;
+
+    for (idx, galley) in galleys.into_iter().enumerate() {
+        // Dot
+        let color = match idx.cmp(&current) {
+            Ordering::Less => theme.colors.success,
+            Ordering::Equal => theme.colors.primary,
+            Ordering::Greater => theme.colors.text_subtle,
+        };
+        painter.circle_filled(egui::pos2(x + dot_size * 0.5, y), dot_size * 0.5, color);
+        x += dot_size + gap;
 
-    ui.horizontal_centered(|ui| {
-        for (idx, &label) in labels.iter().enumerate() {
-            // Dot
-            let is_active = idx == current;
-            let color = if is_active {
-                egui::Color32::from_rgb(0, 150, 255)
-            } else {
-                egui::Color32::from_gray(140)
-            };
-            let (rect, _resp) = ui.allocate_exact_size(egui::vec2(dot_size, dot_size), egui::Sense::hover());
-            ui.painter().circle_filled(rect.center(), dot_size * 0.5, color);
-
-            // Label
-            ui.vertical(|ui| {
-                ui.add_space(2.0 * scale);
-                let text = egui::RichText::new(label)
-                    .font(egui::FontId::proportional(14.0 * scale))
-                    .color(if is_active { egui::Color32::WHITE } else { egui::Color32::from_gray(200) });
-                ui.add(egui::Label::new(text));
-            });
-
-            // Line to next
-            if idx < labels.len() - 1 {
-                let (line_rect, _)
-                    = ui.allocate_exact_size(egui::vec2(spacing, line_thickness), egui::Sense::hover());
-                ui.painter().rect_filled(line_rect, 2.0, egui::Color32::from_gray(80));
-            }
+        // Label
+        let label_width = galley.size().x;
+        painter.galley(egui::pos2(x, y - galley.size().y * 0.5), galley, theme.colors.text);
+        x += label_width;
+
+        // Line to next
+        if idx < labels.len() - 1 {
+            let line_rect = egui::Rect::from_min_max(
+                egui::pos2(x + gap, y - line_thickness * 0.5),
+                egui::pos2(x + spacing - gap, y + line_thickness * 0.5),
+            );
+            painter.rect_filled(line_rect, 2.0, theme.colors.disabled);
+            x += spacing;
         }
-    });
+    }
 }
 
-
+/// The customer journey stepper (amount → connect → payment → charging → complete)
+pub fn journey_stepper(ui: &mut egui::Ui, current: usize, scale: f32) {
+    let labels: Vec<String> = JOURNEY_STEPS.iter().map(|key| i18n::t(key)).collect();
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    stepper(ui, &labels, current, scale);
+}
@@ -1,43 +1,49 @@
 use eframe::egui;
+use crate::theme;
 use crate::router::NavigationStack;
+use crate::i18n;
 
-pub fn show_top_bar(ctx: &egui::Context, scale: f32, nav_stack: Option<&NavigationStack>) {
+/// Returns true when the Settings button was clicked this frame
+pub fn show_top_bar(ctx: &egui::Context, scale: f32, nav_stack: Option<&NavigationStack>) -> bool {
+    let theme = theme::current();
+    let mut settings_clicked = false;
     egui::TopBottomPanel::top("top_bar")
-        .frame(egui::Frame::default().fill(egui::Color32::from_rgba_premultiplied(20, 20, 25, 200)))
+        .frame(egui::Frame::default().fill(theme.colors.bar))
         .show(ctx, |ui| {
             ui.horizontal_wrapped(|ui| {
                 ui.spacing_mut().item_spacing = egui::vec2(12.0, 0.0);
                 ui.label(
-                    egui::RichText::new("EV CHARGER")
-                        .font(egui::FontId::proportional(14.0 * scale))
-                        .color(egui::Color32::WHITE),
+                    egui::RichText::new(i18n::t("top_bar.brand"))
+                        .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                        .color(theme.colors.text),
                 );
                 ui.separator();
                 let now = chrono::Local::now();
                 ui.label(
                     egui::RichText::new(now.format("%Y-%m-%d %H:%M:%S").to_string())
-                        .font(egui::FontId::proportional(14.0 * scale))
-                        .color(egui::Color32::from_gray(210)),
+                        .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                        .color(theme.colors.text_muted),
                 );
                 ui.label(
-                    egui::RichText::new("Ready")
-                        .font(egui::FontId::proportional(12.0 * scale))
-                        .color(egui::Color32::from_rgb(120, 220, 120)),
+                    egui::RichText::new(i18n::t("top_bar.ready"))
+                        .font(egui::FontId::proportional(theme.font_sizes.small * scale))
+                        .color(theme.colors.success_text),
                 );
                 
                 // Navigation stack info (for debugging)
                 if let Some(stack) = nav_stack {
                     ui.label(
-                        egui::RichText::new(format!("Stack: {}", stack.len()))
-                            .font(egui::FontId::proportional(10.0 * scale))
-                            .color(egui::Color32::from_gray(150)),
+                        egui::RichText::new(i18n::t_args("top_bar.stack", &[("depth", &stack.len())]))
+                            .font(egui::FontId::proportional(theme.font_sizes.tiny * scale))
+                            .color(theme.colors.text_subtle),
                     );
                 }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    let btn = egui::Button::new("Settings").min_size(egui::vec2(100.0 * scale, 14.0 * scale));
-                    let _ = ui.add(btn);
+                    let btn = egui::Button::new(i18n::t("top_bar.settings")).min_size(egui::vec2(100.0 * scale, 14.0 * scale));
+                    settings_clicked = ui.add(btn).clicked();
                 });
             });
         });
+    settings_clicked
 }
 
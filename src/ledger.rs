@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::payment::Authorization;
+use crate::screen::{ChargeType, PaymentMethod};
+use crate::tariff::Bill;
+
+pub const DEFAULT_LEDGER_PATH: &str = "data/sessions.jsonl";
+
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerError {
+    #[error("session ledger I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot encode session record: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Where the money for a recorded session stands
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Settlement {
+    /// Charging ended but the hold has not been captured or released yet
+    Pending { authorization: Authorization },
+    #[default]
+    Settled,
+}
+
+/// A finished charging session as kept in the ledger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: Uuid,
+    pub connector_id: u32,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub charge_type: ChargeType,
+    pub target: f32,
+    pub payment_method: PaymentMethod,
+    pub energy_kwh: f64,
+    pub energy_cost: f64,
+    pub time_cost: f64,
+    pub session_fee: f64,
+    pub idle_fee: f64,
+    #[serde(default)]
+    pub discount: f64,
+    pub total: f64,
+    /// Part of `total` the payment hold did not cover
+    #[serde(default)]
+    pub uncollected: f64,
+    pub currency: String,
+    #[serde(default)]
+    pub member_id: Option<String>,
+    #[serde(default)]
+    pub settlement: Settlement,
+}
+
+impl SessionRecord {
+    /// Record for a session that stopped charging at `ended_at`, from the `AppState::Complete` data
+    pub fn new(
+        charge_type: ChargeType,
+        target: f32,
+        payment_method: PaymentMethod,
+        bill: &Bill,
+        charging_duration: Duration,
+        ended_at: DateTime<Utc>,
+        currency: &str,
+    ) -> Self {
+        let started_at = ended_at - chrono::Duration::from_std(charging_duration).unwrap_or_default();
+        Self {
+            id: Uuid::new_v4(),
+            connector_id: crate::hardware::CONNECTOR_ID,
+            started_at,
+            ended_at,
+            charge_type,
+            target,
+            payment_method,
+            energy_kwh: bill.energy_kwh,
+            energy_cost: bill.energy_cost,
+            time_cost: bill.time_cost,
+            session_fee: bill.session_fee,
+            idle_fee: bill.idle_fee,
+            discount: bill.discount,
+            total: bill.total,
+            uncollected: bill.uncollected,
+            currency: currency.to_string(),
+            member_id: None,
+            settlement: Settlement::Settled,
+        }
+    }
+
+    /// Take the amounts of the final `bill`, e.g. once the idle fee is known
+    pub fn update_bill(&mut self, bill: &Bill) {
+        self.energy_kwh = bill.energy_kwh;
+        self.energy_cost = bill.energy_cost;
+        self.time_cost = bill.time_cost;
+        self.session_fee = bill.session_fee;
+        self.idle_fee = bill.idle_fee;
+        self.discount = bill.discount;
+        self.total = bill.total;
+        self.uncollected = bill.uncollected;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self.settlement, Settlement::Pending { .. })
+    }
+}
+
+/// Which sessions to return from `SessionLedger::query`. Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct SessionFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub payment_method: Option<PaymentMethod>,
+    pub member_id: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl SessionFilter {
+    fn matches(&self, record: &SessionRecord) -> bool {
+        self.from.is_none_or(|from| record.ended_at >= from)
+            && self.to.is_none_or(|to| record.ended_at < to)
+            && self.payment_method.as_ref().is_none_or(|method| record.payment_method == *method)
+            && self.member_id.as_ref().is_none_or(|member_id| record.member_id.as_ref() == Some(member_id))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LedgerSummary {
+    pub sessions: usize,
+    pub energy_kwh: f64,
+    pub revenue: f64,
+}
+
+/// Append-only JSON Lines file of finished sessions. One record per line, so a
+/// write cut short by a power loss only ever damages the last entry. A record is
+/// amended by appending it again: the last line with an id wins.
+#[derive(Debug, Clone)]
+pub struct SessionLedger {
+    path: PathBuf,
+}
+
+impl SessionLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn append(&self, record: &SessionRecord) -> Result<(), LedgerError> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new().create(true).read(true).append(true).open(&self.path)?;
+        // Start a fresh line if the previous write was cut short
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                line.insert(0, '\n');
+            }
+        }
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Every readable record in its latest version, oldest first. Damaged lines are skipped.
+    pub fn records(&self) -> Result<Vec<SessionRecord>, LedgerError> {
+        let file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut records: Vec<SessionRecord> = Vec::new();
+        let mut positions: HashMap<Uuid, usize> = HashMap::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<SessionRecord>(&line) {
+                Ok(record) => match positions.get(&record.id) {
+                    Some(&position) => records[position] = record,
+                    None => {
+                        positions.insert(record.id, records.len());
+                        records.push(record);
+                    }
+                },
+                Err(err) => println!("Skipping damaged session record at line {}: {}", index + 1, err),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Sessions whose payment was never captured or released, e.g. after a crash on the Complete screen
+    pub fn pending(&self) -> Result<Vec<SessionRecord>, LedgerError> {
+        Ok(self.records()?.into_iter().filter(SessionRecord::is_pending).collect())
+    }
+
+    /// Matching sessions, newest first
+    pub fn query(&self, filter: &SessionFilter) -> Result<Vec<SessionRecord>, LedgerError> {
+        let mut records: Vec<SessionRecord> = self.records()?.into_iter().filter(|record| filter.matches(record)).collect();
+        records.sort_by_key(|record| std::cmp::Reverse(record.ended_at));
+        if let Some(limit) = filter.limit {
+            records.truncate(limit);
+        }
+        Ok(records)
+    }
+
+    /// Look up a single session, e.g. to reprint its receipt
+    #[allow(dead_code)] // no reprint screen yet
+    pub fn find(&self, id: Uuid) -> Result<Option<SessionRecord>, LedgerError> {
+        Ok(self.records()?.into_iter().find(|record| record.id == id))
+    }
+
+    pub fn summary(&self, filter: &SessionFilter) -> Result<LedgerSummary, LedgerError> {
+        let records = self.query(filter)?;
+        Ok(LedgerSummary {
+            sessions: records.len(),
+            energy_kwh: records.iter().map(|record| record.energy_kwh).sum(),
+            revenue: records.iter().map(|record| record.total).sum(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ended_at: DateTime<Utc>, payment_method: PaymentMethod, total: f64) -> SessionRecord {
+        let bill = Bill {
+            energy_kwh: 10.0,
+            duration: Duration::from_secs(600),
+            segments: Vec::new(),
+            energy_cost: total,
+            time_cost: 0.0,
+            session_fee: 0.0,
+            idle_fee: 0.0,
+            discount: 0.0,
+            total,
+            uncollected: 0.0,
+        };
+        SessionRecord::new(ChargeType::SpecificWatts(0.0), 10.0, payment_method, &bill, bill.duration, ended_at, "KRW")
+    }
+
+    fn temp_ledger() -> SessionLedger {
+        SessionLedger::new(std::env::temp_dir().join(format!("ev-charger-ledger-{}.jsonl", Uuid::new_v4())))
+    }
+
+    #[test]
+    fn records_survive_reopening_and_skip_damaged_lines() {
+        let ledger = temp_ledger();
+        let first = record(Utc::now(), PaymentMethod::CreditCard, 3472.0);
+        ledger.append(&first).unwrap();
+        // A write interrupted by a power loss
+        OpenOptions::new().append(true).open(&ledger.path).unwrap().write_all(b"{\"id\":\"trunc").unwrap();
+
+        let reopened = SessionLedger::new(&ledger.path);
+        assert_eq!(reopened.records().unwrap(), vec![first.clone()]);
+        let second = record(Utc::now(), PaymentMethod::Membership, 1000.0);
+        reopened.append(&second).unwrap();
+        assert_eq!(reopened.records().unwrap(), vec![first.clone(), second]);
+        assert_eq!(reopened.find(first.id).unwrap(), Some(first.clone()));
+        assert_eq!(first.ended_at - first.started_at, chrono::Duration::minutes(10));
+
+        std::fs::remove_file(&ledger.path).unwrap();
+    }
+
+    #[test]
+    fn appending_a_record_again_amends_it() {
+        let ledger = temp_ledger();
+        let mut first = record(Utc::now(), PaymentMethod::CreditCard, 3000.0);
+        first.settlement = Settlement::Pending {
+            authorization: Authorization {
+                id: "AUTH-1".to_string(),
+                method: PaymentMethod::CreditCard,
+                amount: 5000.0,
+                id_token: None,
+            },
+        };
+        let second = record(Utc::now(), PaymentMethod::MobileApp, 1000.0);
+        ledger.append(&first).unwrap();
+        ledger.append(&second).unwrap();
+        assert_eq!(ledger.pending().unwrap(), vec![first.clone()]);
+
+        // The idle fee came in and the hold was captured
+        let mut settled = first.clone();
+        settled.idle_fee = 500.0;
+        settled.total = 3500.0;
+        settled.settlement = Settlement::Settled;
+        ledger.append(&settled).unwrap();
+        assert_eq!(ledger.records().unwrap(), vec![settled, second]);
+        assert!(ledger.pending().unwrap().is_empty());
+
+        std::fs::remove_file(&ledger.path).unwrap();
+    }
+
+    #[test]
+    fn queries_filter_and_summarise_newest_first() {
+        let ledger = temp_ledger();
+        let now = Utc::now();
+        let old = record(now - chrono::Duration::days(2), PaymentMethod::CreditCard, 1000.0);
+        let card = record(now - chrono::Duration::hours(1), PaymentMethod::CreditCard, 2000.0);
+        let mut app = record(now, PaymentMethod::MobileApp, 4000.0);
+        app.member_id = Some("M0001".to_string());
+        for record in [&old, &card, &app] {
+            ledger.append(record).unwrap();
+        }
+
+        let today = SessionFilter { from: Some(now - chrono::Duration::days(1)), ..Default::default() };
+        assert_eq!(ledger.query(&today).unwrap(), vec![app.clone(), card.clone()]);
+
+        let cards = SessionFilter { payment_method: Some(PaymentMethod::CreditCard), limit: Some(1), ..Default::default() };
+        assert_eq!(ledger.query(&cards).unwrap(), vec![card.clone()]);
+
+        let member = SessionFilter { member_id: Some("M0001".to_string()), ..Default::default() };
+        assert_eq!(ledger.query(&member).unwrap(), vec![app.clone()]);
+
+        let summary = ledger.summary(&today).unwrap();
+        assert_eq!(summary, LedgerSummary { sessions: 2, energy_kwh: 20.0, revenue: 6000.0 });
+
+        std::fs::remove_file(&ledger.path).unwrap();
+    }
+}
@@ -3,24 +3,81 @@ use eframe::egui;
 mod screen;
 mod layout;
 mod router;
+mod hardware;
+mod ocpp;
+mod tariff;
+mod session;
+mod ledger;
+mod payment;
+mod auth;
+mod membership;
+mod i18n;
+mod fonts;
+mod theme;
+mod config;
+mod operator;
 use router::Router;
+use config::{Args, KioskConfig};
+use fonts::FontSet;
+use i18n::Locale;
 
 struct EvChargerApp {
     router: Router,
+    fonts: FontSet,
+    // 현재 egui에 적용된 글꼴의 언어
+    font_locale: Option<Locale>,
 }
 
 impl EvChargerApp {
-    fn new() -> Self {
+    fn new(config: KioskConfig, fonts: FontSet) -> Self {
+        theme::init(theme::Theme::load_or_default(&config.theme));
+        let router = Router::new(config);
         Self {
-            router: Router::new(),
+            router,
+            fonts,
+            font_locale: None,
+        }
+    }
+
+    /// Switch the font fallback chain when the language changes
+    fn apply_fonts(&mut self, ctx: &egui::Context) {
+        let locale = i18n::current();
+        if self.font_locale != Some(locale) {
+            ctx.set_fonts(self.fonts.definitions(locale));
+            self.font_locale = Some(locale);
         }
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
+    // 설정 오류는 창을 띄우기 전에 알림
+    let config = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) if args.help => {
+            println!("{}", config::USAGE);
+            return Ok(());
+        }
+        Ok(args) => KioskConfig::load(&args),
+        Err(err) => Err(err),
+    };
+    let config = config.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+
+    // 한글을 그릴 글꼴이 없으면 네모로 표시되므로 시작하지 않음
+    let fonts = FontSet::load(&fonts::FontConfig::load_or_default(
+        std::path::Path::new(fonts::DEFAULT_FONTS_PATH),
+        config.font.as_deref(),
+    ));
+    if let Err(err) = fonts.check() {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([800.0, 600.0])
+            .with_inner_size([config.window.width, config.window.height])
+            .with_fullscreen(config.window.fullscreen)
             .with_resizable(true)
             .with_decorations(true)
             .with_always_on_top()
@@ -31,12 +88,13 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "EV Charger",
         options,
-        Box::new(|_cc| Ok(Box::new(EvChargerApp::new()))),
+        Box::new(|_cc| Ok(Box::new(EvChargerApp::new(config, fonts)))),
     )
 }
 
 impl eframe::App for EvChargerApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.apply_fonts(ctx);
         self.router.update(ctx, frame);
     }
 }
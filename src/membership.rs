@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use crate::auth::IdToken;
+use crate::tariff::Bill;
+
+pub const DEFAULT_MEMBERS_PATH: &str = "data/members.json";
+pub const DEFAULT_MEMBERS_SEED_PATH: &str = "config/members.toml";
+
+#[derive(Debug, thiserror::Error)]
+pub enum MembershipError {
+    #[error("unknown member {0}")]
+    UnknownMember(String),
+    #[error("cannot save members: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot encode members: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid member seed file: {0}")]
+    Seed(#[from] toml::de::Error),
+}
+
+/// Membership plan; the discount applies to the energy part of the bill
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    pub name: String,
+    #[serde(default)]
+    pub energy_discount_percent: f64,
+}
+
+impl Plan {
+    /// `bill` with the discount taken off before the tariff's `minimum_charge` applies
+    pub fn apply(&self, bill: &Bill, minimum_charge: f64) -> Bill {
+        let discount = (bill.energy_cost * self.energy_discount_percent.clamp(0.0, 100.0) / 100.0).round();
+        let subtotal = bill.energy_cost + bill.time_cost + bill.session_fee + bill.idle_fee - discount;
+        Bill { discount, total: subtotal.max(minimum_charge).max(0.0).round(), ..bill.clone() }
+    }
+}
+
+/// Prepaid account identified by one or more RFID cards
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub id: String,
+    pub name: String,
+    pub cards: Vec<IdToken>,
+    pub balance: f64,
+    pub plan: Plan,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Members {
+    members: Vec<Member>,
+}
+
+/// Member accounts with their balances. Session history lives in the
+/// session ledger, keyed by member id.
+#[derive(Debug, Default)]
+pub struct MemberStore {
+    members: Vec<Member>,
+    path: Option<PathBuf>,
+}
+
+pub type SharedMembers = Arc<Mutex<MemberStore>>;
+
+impl MemberStore {
+    #[cfg(test)]
+    pub fn new(members: Vec<Member>) -> Self {
+        Self { members, path: None }
+    }
+
+    /// Accounts from `path`; the first run starts from the `seed` file instead
+    pub fn load(path: &Path, seed: &Path) -> MemberStore {
+        let loaded = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str::<Members>(&text).map_err(MembershipError::from),
+            Err(_) => std::fs::read_to_string(seed)
+                .map_err(MembershipError::from)
+                .and_then(|text| Ok(toml::from_str::<Members>(&text)?)),
+        };
+        let members = loaded.unwrap_or_else(|err| {
+            println!("No members loaded: {}", err);
+            Members::default()
+        });
+        MemberStore { members: members.members, path: Some(path.to_path_buf()) }
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.id == id)
+    }
+
+    pub fn find_by_token(&self, token: &IdToken) -> Option<&Member> {
+        self.members.iter().find(|member| member.cards.contains(token))
+    }
+
+    /// Take `amount` off a member's balance
+    pub fn charge(&mut self, id: &str, amount: f64) -> Result<f64, MembershipError> {
+        self.adjust(id, -amount)
+    }
+
+    /// Put `amount` back on a member's balance
+    pub fn credit(&mut self, id: &str, amount: f64) -> Result<f64, MembershipError> {
+        self.adjust(id, amount)
+    }
+
+    /// The new balance only takes effect once it is on disk, so a failed save
+    /// never leaves memory and the file disagreeing
+    fn adjust(&mut self, id: &str, amount: f64) -> Result<f64, MembershipError> {
+        let index = self
+            .members
+            .iter()
+            .position(|member| member.id == id)
+            .ok_or_else(|| MembershipError::UnknownMember(id.to_string()))?;
+        let mut members = self.members.clone();
+        members[index].balance += amount;
+        self.save(&members)?;
+        self.members = members;
+        Ok(self.members[index].balance)
+    }
+
+    fn save(&self, members: &[Member]) -> Result<(), MembershipError> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write a sibling file and rename so a power loss never loses balances
+        let temp = path.with_extension("json.tmp");
+        let members = Members { members: members.to_vec() };
+        std::fs::write(&temp, serde_json::to_vec_pretty(&members)?)?;
+        std::fs::rename(&temp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::tariff::{Tariff, Usage};
+
+    fn member() -> Member {
+        Member {
+            id: "M0001".to_string(),
+            name: "Demo Member".to_string(),
+            cards: vec![IdToken::new("04A1B2C3")],
+            balance: 50000.0,
+            plan: Plan { name: "Basic".to_string(), energy_discount_percent: 10.0 },
+        }
+    }
+
+    #[test]
+    fn discount_never_takes_the_total_below_the_minimum_charge() {
+        let tariff = Tariff { energy_price_per_kwh: 300.0, minimum_charge: 1000.0, ..Tariff::default() };
+        let plan = member().plan;
+
+        let mut usage = Usage::default();
+        usage.record(&tariff, chrono::Local::now(), 10.0);
+        let bill = plan.apply(&tariff.bill(&usage, Duration::ZERO), tariff.minimum_charge);
+        assert_eq!((bill.discount, bill.total), (300.0, 2700.0));
+        // Applying it again changes nothing
+        assert_eq!(plan.apply(&bill, tariff.minimum_charge).total, 2700.0);
+
+        let mut usage = Usage::default();
+        usage.record(&tariff, chrono::Local::now(), 3.0);
+        let bill = tariff.bill(&usage, Duration::ZERO);
+        assert_eq!(bill.total, 1000.0);
+        assert_eq!(plan.apply(&bill, tariff.minimum_charge).total, 1000.0);
+    }
+
+    #[test]
+    fn balance_changes_only_once_saved() {
+        let dir = std::env::temp_dir().join(format!("ev-charger-members-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("members.json");
+        let mut store = MemberStore { members: vec![member()], path: Some(path.clone()) };
+        assert_eq!(store.charge("M0001", 3000.0).unwrap(), 47000.0);
+        let reloaded = MemberStore::load(&path, Path::new("missing.toml"));
+        assert_eq!(reloaded.find("M0001").unwrap().balance, 47000.0);
+
+        // A file where the data directory should be makes every save fail
+        let blocked = dir.join("blocked");
+        std::fs::write(&blocked, b"").unwrap();
+        let mut store = MemberStore { members: vec![member()], path: Some(blocked.join("members.json")) };
+        assert!(matches!(store.charge("M0001", 3000.0), Err(MembershipError::Io(_))));
+        assert_eq!(store.find("M0001").unwrap().balance, 50000.0);
+        assert!(matches!(store.credit("M9999", 1.0), Err(MembershipError::UnknownMember(_))));
+    }
+}
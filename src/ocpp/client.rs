@@ -0,0 +1,379 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::time::{interval_at, sleep, Instant};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use crate::ocpp::rpc::{Frame, RpcError};
+use crate::ocpp::{protocol_for, ChargerEvent, OcppConfig, Protocol, RegistrationStatus, SharedBackendLink};
+use crate::auth::local_list::SharedLocalAuth;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+// Heartbeat interval until the central system tells us otherwise
+const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(300);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error(transparent)]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    #[error("connection closed by the central system")]
+    Closed,
+    #[error("{0} was not answered in time")]
+    Timeout(String),
+}
+
+/// A CALL waiting for its CALLRESULT. Kiosk events are kept so they can be
+/// sent again after a reconnect instead of being lost.
+struct PendingCall {
+    id: String,
+    action: &'static str,
+    event: Option<ChargerEvent>,
+    deadline: Instant,
+}
+
+/// Run the charge point connection until the event channel closes,
+/// reconnecting whenever the central system goes away.
+pub async fn run(
+    config: OcppConfig,
+    local_auth: SharedLocalAuth,
+    link: SharedBackendLink,
+    mut events: mpsc::UnboundedReceiver<ChargerEvent>,
+) {
+    let mut session = protocol_for(&config, local_auth, link.clone());
+    let mut queue = VecDeque::new();
+
+    loop {
+        match connect(&config).await {
+            Ok(socket) => {
+                println!("OCPP: connected to {}", config.csms_url);
+                let result = drive(&config, socket, session.as_mut(), &link, &mut events, &mut queue).await;
+                link.lock().unwrap().set_connected(false);
+                match result {
+                    Ok(()) => return,
+                    Err(err) => println!("OCPP: connection lost: {}", err),
+                }
+            }
+            Err(err) => println!("OCPP: cannot reach {}: {}", config.csms_url, err),
+        }
+
+        // Keep collecting events while offline so transactions reach the backend later
+        let retry = sleep(RECONNECT_DELAY);
+        tokio::pin!(retry);
+        loop {
+            tokio::select! {
+                _ = &mut retry => break,
+                event = events.recv() => match event {
+                    Some(event) => enqueue(session.as_mut(), &mut queue, event),
+                    None => return,
+                },
+            }
+        }
+    }
+}
+
+async fn connect(config: &OcppConfig) -> Result<Socket, ClientError> {
+    let url = format!("{}/{}", config.csms_url.trim_end_matches('/'), config.charge_point_id);
+    let mut request = url.into_client_request()?;
+    request
+        .headers_mut()
+        .insert("Sec-WebSocket-Protocol", HeaderValue::from_static(config.version.subprotocol()));
+    let (socket, _response) = tokio_tungstenite::connect_async(request).await?;
+    Ok(socket)
+}
+
+fn enqueue(session: &mut dyn Protocol, queue: &mut VecDeque<ChargerEvent>, event: ChargerEvent) {
+    match event {
+        ChargerEvent::MeterSample { .. } => session.record_sample(event),
+        event => queue.push_back(event),
+    }
+}
+
+async fn send_call(
+    socket: &mut Socket,
+    action: &'static str,
+    payload: Value,
+    event: Option<ChargerEvent>,
+    timeout: Duration,
+) -> Result<PendingCall, ClientError> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let frame = Frame::Call { id: id.clone(), action: action.to_string(), payload };
+    socket.send(Message::Text(frame.to_text())).await?;
+    Ok(PendingCall { id, action, event, deadline: Instant::now() + timeout })
+}
+
+async fn drive(
+    config: &OcppConfig,
+    mut socket: Socket,
+    session: &mut dyn Protocol,
+    link: &SharedBackendLink,
+    events: &mut mpsc::UnboundedReceiver<ChargerEvent>,
+    queue: &mut VecDeque<ChargerEvent>,
+) -> Result<(), ClientError> {
+    let (action, payload) = session.boot_notification();
+    let mut pending = Some(send_call(&mut socket, action, payload, None, config.call_timeout).await?);
+    let mut accepted = false;
+    let mut boot_retry_at = Instant::now() + DEFAULT_HEARTBEAT;
+    let mut heartbeat = interval_at(Instant::now() + DEFAULT_HEARTBEAT, DEFAULT_HEARTBEAT);
+    let mut meter_tick = interval_at(Instant::now() + config.meter_interval, config.meter_interval);
+
+    let result = loop {
+        // One CALL in flight at a time, as OCPP-J requires
+        if accepted && pending.is_none() {
+            if let Some((action, payload)) = session.next_request() {
+                pending = Some(send_call(&mut socket, action, payload, None, config.call_timeout).await?);
+                continue;
+            }
+            if let Some(event) = queue.pop_front() {
+                if let Some((action, payload)) = session.request_for(&event) {
+                    pending = Some(send_call(&mut socket, action, payload, Some(event), config.call_timeout).await?);
+                }
+                continue;
+            }
+        }
+        let deadline = pending.as_ref().map(|call| call.deadline).unwrap_or_else(|| Instant::now() + config.call_timeout);
+
+        tokio::select! {
+            message = socket.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break Err(ClientError::Closed),
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => break Err(err.into()),
+                };
+                match Frame::parse(&text) {
+                    Ok(Frame::CallResult { id, payload }) => {
+                        let Some(call) = pending.take_if(|call| call.id == id) else {
+                            println!("OCPP: unexpected result {}", id);
+                            continue;
+                        };
+                        if let Some(registration) = session.handle_response(call.action, payload) {
+                            accepted = registration.status == RegistrationStatus::Accepted;
+                            link.lock().unwrap().set_connected(accepted);
+                            if accepted {
+                                heartbeat = interval_at(Instant::now() + registration.interval, registration.interval);
+                            } else {
+                                println!("OCPP: registration {:?}, retrying", registration.status);
+                                boot_retry_at = Instant::now() + registration.interval;
+                            }
+                        }
+                    }
+                    Ok(Frame::CallError { id, code, description }) => {
+                        if let Some(call) = pending.take_if(|call| call.id == id) {
+                            println!("OCPP: {} failed: {} {}", call.action, code, description);
+                        }
+                    }
+                    Ok(Frame::Call { id, action, payload }) => {
+                        let reply = match session.handle_request(&action, payload) {
+                            Ok(payload) => Frame::CallResult { id, payload },
+                            Err((code, description)) => Frame::CallError { id, code: code.to_string(), description },
+                        };
+                        socket.send(Message::Text(reply.to_text())).await?;
+                    }
+                    Err(err) => println!("OCPP: {}", err),
+                }
+            }
+            event = events.recv() => match event {
+                Some(event) => enqueue(session, queue, event),
+                None => break Ok(()),
+            },
+            _ = heartbeat.tick(), if accepted && pending.is_none() => {
+                let (action, payload) = session.heartbeat();
+                pending = Some(send_call(&mut socket, action, payload, None, config.call_timeout).await?);
+            }
+            _ = meter_tick.tick(), if accepted && pending.is_none() => {
+                if let Some((action, payload)) = session.meter_values() {
+                    pending = Some(send_call(&mut socket, action, payload, None, config.call_timeout).await?);
+                }
+            }
+            _ = tokio::time::sleep_until(boot_retry_at), if !accepted && pending.is_none() => {
+                let (action, payload) = session.boot_notification();
+                pending = Some(send_call(&mut socket, action, payload, None, config.call_timeout).await?);
+            }
+            _ = tokio::time::sleep_until(deadline), if pending.is_some() => {
+                let action = pending.as_ref().map(|call| call.action).unwrap_or_default();
+                break Err(ClientError::Timeout(action.to_string()));
+            }
+        }
+    };
+
+    // Requeue an unanswered kiosk event so it survives the reconnect
+    if let Some(PendingCall { event: Some(event), .. }) = pending {
+        queue.push_front(event);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::ocpp::mock_csms::{MockCsms, ReceivedCall};
+    use crate::ocpp::{ConnectorStatus, OcppVersion, StopReason};
+    use crate::auth::{AuthorizationStatus, IdToken};
+    use crate::auth::local_list::LocalAuthorization;
+    use std::sync::{Arc, Mutex};
+
+    fn config(url: String, version: OcppVersion) -> OcppConfig {
+        OcppConfig {
+            csms_url: url,
+            version,
+            charge_point_id: "TEST-01".to_string(),
+            connector_id: 1,
+            vendor: "test".to_string(),
+            model: "test".to_string(),
+            meter_interval: Duration::from_millis(50),
+            call_timeout: Duration::from_secs(30),
+        }
+    }
+
+    async fn wait_for(mock: &MockCsms, count: usize) -> Vec<ReceivedCall> {
+        for _ in 0..100 {
+            let calls = mock.received();
+            if calls.len() >= count {
+                return calls;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        mock.received()
+    }
+
+    #[tokio::test]
+    async fn reports_a_charging_session_to_the_central_system() {
+        let mock = MockCsms::start().await.unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let local_auth = Arc::new(Mutex::new(LocalAuthorization::default()));
+        let client = tokio::spawn(run(config(mock.url(), OcppVersion::V16), local_auth.clone(), Default::default(), rx));
+
+        tx.send(ChargerEvent::ConnectorStatus(ConnectorStatus::Preparing)).unwrap();
+        tx.send(ChargerEvent::Authorize { id_tag: "RFID-1234".to_string() }).unwrap();
+        tx.send(ChargerEvent::SessionStarted {
+            id_tag: "RFID-1234".to_string(),
+            meter_start_wh: 1000.0,
+            timestamp: Utc::now(),
+        })
+        .unwrap();
+        wait_for(&mock, 4).await;
+
+        tx.send(ChargerEvent::MeterSample { energy_wh: 6000.0, power_w: 50000.0, soc: Some(42.0), timestamp: Utc::now() })
+            .unwrap();
+        wait_for(&mock, 5).await;
+        tx.send(ChargerEvent::SessionStopped { meter_stop_wh: 11000.0, reason: StopReason::Local, timestamp: Utc::now() })
+            .unwrap();
+        tx.send(ChargerEvent::ConnectorStatus(ConnectorStatus::Finishing)).unwrap();
+
+        let calls = wait_for(&mock, 7).await;
+        let actions: Vec<&str> = calls.iter().map(|call| call.action.as_str()).collect();
+        assert_eq!(
+            actions,
+            [
+                "BootNotification",
+                "StatusNotification",
+                "Authorize",
+                "StartTransaction",
+                "MeterValues",
+                "StopTransaction",
+                "StatusNotification",
+            ]
+        );
+        assert_eq!(calls[3].payload["meterStart"], 1000);
+        assert_eq!(calls[4].payload["transactionId"], 1);
+        assert_eq!(calls[4].payload["meterValue"][0]["sampledValue"][0]["value"], "6000");
+        assert_eq!(calls[5].payload["transactionId"], 1);
+        assert_eq!(calls[5].payload["meterStop"], 11000);
+        assert_eq!(calls[6].payload["status"], "Finishing");
+
+        // Demo list from the mock, plus the tag it accepted in Authorize, now work offline
+        {
+            let local_auth = local_auth.lock().unwrap();
+            let now = Utc::now();
+            assert_eq!(local_auth.list_version(), 1);
+            assert_eq!(local_auth.authorize_offline(&IdToken::new("04a1b2c3"), now), AuthorizationStatus::Accepted);
+            assert_eq!(local_auth.authorize_offline(&IdToken::new("DEADBEEF"), now), AuthorizationStatus::Blocked);
+            assert_eq!(local_auth.authorize_offline(&IdToken::new("RFID-1234"), now), AuthorizationStatus::Accepted);
+        }
+
+        drop(tx);
+        client.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reports_a_charging_session_over_ocpp_201() {
+        let mock = MockCsms::start().await.unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let local_auth = Arc::new(Mutex::new(LocalAuthorization::default()));
+        let client = tokio::spawn(run(config(mock.url(), OcppVersion::V201), local_auth.clone(), Default::default(), rx));
+
+        // The mock asks for a base report right after boot
+        wait_for(&mock, 2).await;
+        tx.send(ChargerEvent::ConnectorStatus(ConnectorStatus::Preparing)).unwrap();
+        tx.send(ChargerEvent::SessionStarted {
+            id_tag: "RFID-1234".to_string(),
+            meter_start_wh: 1000.0,
+            timestamp: Utc::now(),
+        })
+        .unwrap();
+        wait_for(&mock, 4).await;
+
+        tx.send(ChargerEvent::MeterSample { energy_wh: 6000.0, power_w: 50000.0, soc: Some(42.0), timestamp: Utc::now() })
+            .unwrap();
+        wait_for(&mock, 5).await;
+        tx.send(ChargerEvent::SessionStopped { meter_stop_wh: 11000.0, reason: StopReason::EvDisconnected, timestamp: Utc::now() })
+            .unwrap();
+
+        let calls = wait_for(&mock, 6).await;
+        let actions: Vec<&str> = calls.iter().map(|call| call.action.as_str()).collect();
+        assert_eq!(
+            actions,
+            [
+                "BootNotification",
+                "NotifyReport",
+                "StatusNotification",
+                "TransactionEvent",
+                "TransactionEvent",
+                "TransactionEvent",
+            ]
+        );
+        assert_eq!(calls[0].payload["chargingStation"]["vendorName"], "test");
+        let report = calls[1].payload["reportData"].as_array().unwrap();
+        assert!(report.iter().any(|data| data["variable"]["name"] == "HeartbeatInterval"
+            && data["variableAttribute"][0]["value"] == "300"));
+        assert_eq!(calls[2].payload["connectorStatus"], "Occupied");
+
+        let events: Vec<&Value> = calls[3..].iter().map(|call| &call.payload).collect();
+        assert_eq!(events[0]["eventType"], "Started");
+        assert_eq!(events[0]["idToken"]["idToken"], "RFID-1234");
+        assert_eq!(events[0]["meterValue"][0]["sampledValue"][0]["value"], 1000.0);
+        assert_eq!(events[1]["eventType"], "Updated");
+        assert_eq!(events[2]["eventType"], "Ended");
+        assert_eq!(events[2]["transactionInfo"]["stoppedReason"], "EVDisconnected");
+        assert_eq!(events[2]["meterValue"][0]["sampledValue"][0]["value"], 11000.0);
+        let transaction_id = &events[0]["transactionInfo"]["transactionId"];
+        assert!(events.iter().all(|event| &event["transactionInfo"]["transactionId"] == transaction_id));
+        let seq_nos: Vec<&Value> = events.iter().map(|event| &event["seqNo"]).collect();
+        assert_eq!(seq_nos, [0, 1, 2]);
+        assert_eq!(
+            local_auth.lock().unwrap().authorize_offline(&IdToken::new("04A1B2C3"), Utc::now()),
+            AuthorizationStatus::Accepted
+        );
+
+        drop(tx);
+        client.await.unwrap();
+    }
+
+    #[test]
+    fn rpc_frames_round_trip() {
+        let call = Frame::Call { id: "42".to_string(), action: "Heartbeat".to_string(), payload: serde_json::json!({}) };
+        assert_eq!(call.to_text(), r#"[2,"42","Heartbeat",{}]"#);
+        assert_eq!(Frame::parse(&call.to_text()).unwrap(), call);
+        assert!(Frame::parse(r#"[9,"1"]"#).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use crate::ocpp::rpc::Frame;
+use crate::ocpp::OcppVersion;
+
+/// A CALL received from a charge point
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedCall {
+    pub action: String,
+    pub payload: Value,
+}
+
+/// Minimal local central system for running and testing the kiosk offline.
+/// Speaks OCPP 1.6 and 2.0.1, accepts every charge point and id tag and
+/// records what it was sent. After boot, 2.0.1 stations are asked for a base
+/// report and every charge point gets a demo local authorization list
+/// (`04A1B2C3` accepted, `DEADBEEF` blocked).
+pub struct MockCsms {
+    addr: SocketAddr,
+    #[cfg(test)]
+    received: Arc<Mutex<Vec<ReceivedCall>>>,
+}
+
+impl MockCsms {
+    /// Listen on an ephemeral localhost port
+    pub async fn start() -> std::io::Result<MockCsms> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let next_transaction_id = Arc::new(AtomicI32::new(1));
+
+        let calls = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, calls.clone(), next_transaction_id.clone()));
+            }
+        });
+
+        println!("Mock central system listening on ws://{}", addr);
+        Ok(MockCsms {
+            addr,
+            #[cfg(test)]
+            received,
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("ws://{}/ocpp", self.addr)
+    }
+
+    #[cfg(test)]
+    pub fn received(&self) -> Vec<ReceivedCall> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn serve(stream: TcpStream, received: Arc<Mutex<Vec<ReceivedCall>>>, next_transaction_id: Arc<AtomicI32>) {
+    let mut version = None;
+    // The error type is fixed by tungstenite's handshake callback
+    #[allow(clippy::result_large_err)]
+    let negotiate = |request: &Request, response: Response| negotiate_subprotocol(request, response, &mut version);
+    let Ok(mut socket) = tokio_tungstenite::accept_hdr_async(stream, negotiate).await else {
+        return;
+    };
+    let version = version.unwrap_or(OcppVersion::V16);
+
+    while let Some(Ok(message)) = socket.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(Frame::Call { id, action, payload }) = Frame::parse(&text) else {
+            continue;
+        };
+
+        println!("Mock central system <- {} {}", action, payload);
+        let reply = match version {
+            OcppVersion::V16 => respond_v16(&action, &next_transaction_id),
+            OcppVersion::V201 => respond_v201(&action),
+        };
+        let booted = action == "BootNotification";
+        received.lock().unwrap().push(ReceivedCall { action, payload });
+        if socket.send(Message::Text(Frame::CallResult { id, payload: reply }.to_text())).await.is_err() {
+            break;
+        }
+
+        if booted {
+            let mut requests = Vec::new();
+            if version == OcppVersion::V201 {
+                requests.push(("GetBaseReport", json!({ "requestId": 1, "reportBase": "FullInventory" })));
+            }
+            requests.push(("SendLocalList", demo_local_list(version)));
+            for (action, payload) in requests {
+                let request = Frame::Call { id: uuid::Uuid::new_v4().to_string(), action: action.to_string(), payload };
+                if socket.send(Message::Text(request.to_text())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn demo_local_list(version: OcppVersion) -> Value {
+    match version {
+        OcppVersion::V16 => json!({
+            "listVersion": 1,
+            "updateType": "Full",
+            "localAuthorizationList": [
+                { "idTag": "04A1B2C3", "idTagInfo": { "status": "Accepted" } },
+                { "idTag": "DEADBEEF", "idTagInfo": { "status": "Blocked" } },
+            ],
+        }),
+        OcppVersion::V201 => json!({
+            "versionNumber": 1,
+            "updateType": "Full",
+            "localAuthorizationList": [
+                { "idToken": { "idToken": "04A1B2C3", "type": "ISO14443" }, "idTokenInfo": { "status": "Accepted" } },
+                { "idToken": { "idToken": "DEADBEEF", "type": "ISO14443" }, "idTokenInfo": { "status": "Blocked" } },
+            ],
+        }),
+    }
+}
+
+/// Pick the newest protocol version the charge point offers
+#[allow(clippy::result_large_err)]
+fn negotiate_subprotocol(
+    request: &Request,
+    mut response: Response,
+    version: &mut Option<OcppVersion>,
+) -> Result<Response, ErrorResponse> {
+    let offered = request
+        .headers()
+        .get("Sec-WebSocket-Protocol")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    *version = [OcppVersion::V201, OcppVersion::V16]
+        .into_iter()
+        .find(|version| offered.split(',').any(|protocol| protocol.trim() == version.subprotocol()));
+    if let Some(version) = version {
+        response
+            .headers_mut()
+            .insert("Sec-WebSocket-Protocol", HeaderValue::from_static(version.subprotocol()));
+    }
+    Ok(response)
+}
+
+fn respond_v16(action: &str, next_transaction_id: &AtomicI32) -> Value {
+    let accepted = json!({ "status": "Accepted" });
+    match action {
+        "BootNotification" => json!({
+            "status": "Accepted",
+            "currentTime": Utc::now(),
+            "interval": 300,
+        }),
+        "Heartbeat" => json!({ "currentTime": Utc::now() }),
+        "Authorize" => json!({ "idTagInfo": accepted }),
+        "StartTransaction" => json!({
+            "transactionId": next_transaction_id.fetch_add(1, Ordering::Relaxed),
+            "idTagInfo": accepted,
+        }),
+        "StopTransaction" => json!({ "idTagInfo": accepted }),
+        _ => json!({}),
+    }
+}
+
+fn respond_v201(action: &str) -> Value {
+    let accepted = json!({ "status": "Accepted" });
+    match action {
+        "BootNotification" => json!({
+            "status": "Accepted",
+            "currentTime": Utc::now(),
+            "interval": 300,
+        }),
+        "Heartbeat" => json!({ "currentTime": Utc::now() }),
+        "Authorize" => json!({ "idTokenInfo": accepted }),
+        "TransactionEvent" => json!({ "idTokenInfo": accepted }),
+        _ => json!({}),
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use crate::auth::{AuthorizationStatus, IdToken};
+use crate::auth::local_list::{LocalAuthError, SharedLocalAuth, UpdateType};
+use crate::config::KioskConfig;
+
+pub mod client;
+pub mod mock_csms;
+pub mod rpc;
+pub mod v16;
+pub mod v201;
+
+/// Connector status as reported to the central system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectorStatus {
+    Available,
+    Preparing,
+    Charging,
+    Finishing,
+    Faulted,
+    /// Taken out of service by an operator
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Local,
+    EvDisconnected,
+    EmergencyStop,
+}
+
+/// Kiosk events the backend connection translates into OCPP messages
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChargerEvent {
+    ConnectorStatus(ConnectorStatus),
+    Authorize { id_tag: String },
+    SessionStarted { id_tag: String, meter_start_wh: f64, timestamp: DateTime<Utc> },
+    MeterSample { energy_wh: f64, power_w: f32, soc: Option<f32>, timestamp: DateTime<Utc> },
+    SessionStopped { meter_stop_wh: f64, reason: StopReason, timestamp: DateTime<Utc> },
+}
+
+/// OCPP protocol version spoken with the central system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcppVersion {
+    V16,
+    V201,
+}
+
+impl OcppVersion {
+    pub fn parse(value: &str) -> Option<OcppVersion> {
+        match value.trim() {
+            "1.6" | "ocpp1.6" => Some(OcppVersion::V16),
+            "2.0.1" | "ocpp2.0.1" => Some(OcppVersion::V201),
+            _ => None,
+        }
+    }
+
+    pub fn subprotocol(self) -> &'static str {
+        match self {
+            OcppVersion::V16 => v16::SUBPROTOCOL,
+            OcppVersion::V201 => v201::SUBPROTOCOL,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum RegistrationStatus {
+    Accepted,
+    Pending,
+    Rejected,
+}
+
+/// Result of a BootNotification exchange
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Registration {
+    pub status: RegistrationStatus,
+    pub interval: Duration,
+}
+
+/// Version-specific message mapping used by the connection in `client`.
+/// Requests are returned as `(action, payload)` pairs.
+pub trait Protocol: Send {
+    fn boot_notification(&self) -> (&'static str, Value);
+    fn heartbeat(&self) -> (&'static str, Value);
+    fn record_sample(&mut self, sample: ChargerEvent);
+    fn meter_values(&mut self) -> Option<(&'static str, Value)>;
+    fn request_for(&mut self, event: &ChargerEvent) -> Option<(&'static str, Value)>;
+    fn handle_response(&mut self, action: &str, payload: Value) -> Option<Registration>;
+    fn handle_request(&mut self, action: &str, payload: Value) -> Result<Value, (&'static str, String)>;
+
+    /// Request the charge point owes the central system on its own account,
+    /// e.g. the report promised when answering GetBaseReport
+    fn next_request(&mut self) -> Option<(&'static str, Value)> {
+        None
+    }
+}
+
+pub fn protocol_for(config: &OcppConfig, local_auth: SharedLocalAuth, link: SharedBackendLink) -> Box<dyn Protocol> {
+    match config.version {
+        OcppVersion::V16 => Box::new(v16::ChargePointSession::new(config, local_auth, link)),
+        OcppVersion::V201 => Box::new(v201::ChargingStationSession::new(config, local_auth, link)),
+    }
+}
+
+/// What the kiosk side can see of the backend connection: whether the central
+/// system has accepted the charge point, and its answers to Authorize
+#[derive(Debug, Default)]
+pub struct BackendLink {
+    connected: bool,
+    answers: HashMap<IdToken, AuthorizationStatus>,
+}
+
+pub type SharedBackendLink = Arc<Mutex<BackendLink>>;
+
+impl BackendLink {
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub(crate) fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+    }
+
+    pub(crate) fn answer(&mut self, token: IdToken, status: AuthorizationStatus) {
+        self.answers.insert(token, status);
+    }
+
+    pub fn take_answer(&mut self, token: &IdToken) -> Option<AuthorizationStatus> {
+        self.answers.remove(token)
+    }
+}
+
+/// Id tag / id token status as sent by either protocol version. Anything the
+/// kiosk has no use for (ConcurrentTx, NoCredit, ...) refuses the token.
+pub(crate) fn authorization_status(status: &str) -> AuthorizationStatus {
+    match status {
+        "Accepted" => AuthorizationStatus::Accepted,
+        "Blocked" => AuthorizationStatus::Blocked,
+        "Expired" => AuthorizationStatus::Expired,
+        _ => AuthorizationStatus::Invalid,
+    }
+}
+
+pub(crate) fn update_type(update_type: &str) -> Option<UpdateType> {
+    match update_type {
+        "Full" => Some(UpdateType::Full),
+        "Differential" => Some(UpdateType::Differential),
+        _ => None,
+    }
+}
+
+/// SendLocalList response status for the result of applying the list
+pub(crate) fn send_local_list_status(result: Result<(), LocalAuthError>) -> &'static str {
+    match result {
+        Ok(()) => "Accepted",
+        Err(LocalAuthError::VersionMismatch { .. }) => "VersionMismatch",
+        Err(err) => {
+            println!("OCPP: SendLocalList failed: {}", err);
+            "Failed"
+        }
+    }
+}
+
+pub(crate) fn to_payload<T: Serialize>(request: &T) -> Value {
+    // Request structs only hold strings and numbers, serialization cannot fail
+    serde_json::to_value(request).unwrap_or_default()
+}
+
+#[derive(Debug, Clone)]
+pub struct OcppConfig {
+    pub csms_url: String, // `mock` starts the built-in central system
+    pub version: OcppVersion,
+    pub charge_point_id: String,
+    pub connector_id: u32,
+    pub vendor: String,
+    pub model: String,
+    pub meter_interval: Duration,
+    pub call_timeout: Duration,
+}
+
+impl OcppConfig {
+    /// Backend settings of the kiosk configuration; `None` runs the kiosk offline
+    pub fn from_config(config: &KioskConfig) -> Option<OcppConfig> {
+        Some(OcppConfig {
+            csms_url: config.backend.url.clone()?,
+            version: config.backend_version(),
+            charge_point_id: config.charger.id.clone(),
+            connector_id: config.connector().id,
+            vendor: "daiboom".to_string(),
+            model: "ev-charger-kiosk".to_string(),
+            meter_interval: Duration::from_secs_f64(config.backend.meter_interval_secs),
+            call_timeout: config.timeouts.backend_call(),
+        })
+    }
+}
+
+/// UI-side handle of the backend connection running on its own thread
+#[derive(Clone)]
+pub struct OcppHandle {
+    events: mpsc::UnboundedSender<ChargerEvent>,
+    link: SharedBackendLink,
+}
+
+impl OcppHandle {
+    pub fn send(&self, event: ChargerEvent) {
+        // The client thread only goes away when the app shuts down
+        let _ = self.events.send(event);
+    }
+
+    pub fn link(&self) -> &SharedBackendLink {
+        &self.link
+    }
+}
+
+pub fn spawn(mut config: OcppConfig, local_auth: SharedLocalAuth) -> OcppHandle {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let link = SharedBackendLink::default();
+    let client_link = link.clone();
+
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                println!("OCPP runtime failed to start: {}", err);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            // Keep the mock alive for as long as the client runs
+            let _mock = if config.csms_url == "mock" {
+                match mock_csms::MockCsms::start().await {
+                    Ok(mock) => {
+                        config.csms_url = mock.url();
+                        Some(mock)
+                    }
+                    Err(err) => {
+                        println!("Mock central system failed to start: {}", err);
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+            client::run(config, local_auth, client_link, rx).await;
+        });
+    });
+
+    OcppHandle { events: tx, link }
+}
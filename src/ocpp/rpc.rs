@@ -0,0 +1,67 @@
+use serde_json::{json, Value};
+
+const CALL: u64 = 2;
+const CALL_RESULT: u64 = 3;
+const CALL_ERROR: u64 = 4;
+
+/// OCPP-J RPC frame: `[2, id, action, payload]`, `[3, id, payload]` or
+/// `[4, id, errorCode, errorDescription, errorDetails]`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Call { id: String, action: String, payload: Value },
+    CallResult { id: String, payload: Value },
+    CallError { id: String, code: String, description: String },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RpcError {
+    #[error("malformed frame: {0}")]
+    Malformed(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl Frame {
+    pub fn parse(text: &str) -> Result<Frame, RpcError> {
+        let value: Value = serde_json::from_str(text)?;
+        let items = value
+            .as_array()
+            .ok_or_else(|| RpcError::Malformed("frame is not an array".to_string()))?;
+        let field = |idx: usize| -> Result<&Value, RpcError> {
+            items.get(idx).ok_or_else(|| RpcError::Malformed(format!("missing element {}", idx)))
+        };
+        let text_field = |idx: usize| -> Result<String, RpcError> {
+            field(idx)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| RpcError::Malformed(format!("element {} is not a string", idx)))
+        };
+
+        match field(0)?.as_u64() {
+            Some(CALL) => Ok(Frame::Call {
+                id: text_field(1)?,
+                action: text_field(2)?,
+                payload: field(3)?.clone(),
+            }),
+            Some(CALL_RESULT) => Ok(Frame::CallResult {
+                id: text_field(1)?,
+                payload: field(2)?.clone(),
+            }),
+            Some(CALL_ERROR) => Ok(Frame::CallError {
+                id: text_field(1)?,
+                code: text_field(2)?,
+                description: text_field(3).unwrap_or_default(),
+            }),
+            _ => Err(RpcError::Malformed(format!("unknown message type in {}", text))),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let value = match self {
+            Frame::Call { id, action, payload } => json!([CALL, id, action, payload]),
+            Frame::CallResult { id, payload } => json!([CALL_RESULT, id, payload]),
+            Frame::CallError { id, code, description } => json!([CALL_ERROR, id, code, description, {}]),
+        };
+        value.to_string()
+    }
+}
@@ -0,0 +1,350 @@
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::ocpp::{
+    authorization_status, send_local_list_status, to_payload, update_type, ChargerEvent, ConnectorStatus, OcppConfig,
+    Protocol, Registration, RegistrationStatus, SharedBackendLink, StopReason,
+};
+use crate::auth::IdToken;
+use crate::auth::local_list::{self, SharedLocalAuth};
+
+pub const SUBPROTOCOL: &str = "ocpp1.6";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BootNotificationRequest<'a> {
+    charge_point_vendor: &'a str,
+    charge_point_model: &'a str,
+    charge_point_serial_number: &'a str,
+    firmware_version: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BootNotificationResponse {
+    status: RegistrationStatus,
+    interval: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusNotificationRequest {
+    connector_id: u32,
+    error_code: &'static str,
+    status: &'static str,
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizeRequest<'a> {
+    id_tag: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdTagInfo {
+    status: String,
+    expiry_date: Option<DateTime<Utc>>,
+}
+
+impl IdTagInfo {
+    fn to_local(&self) -> local_list::IdTagInfo {
+        local_list::IdTagInfo { status: authorization_status(&self.status), expiry: self.expiry_date }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizeResponse {
+    id_tag_info: IdTagInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizationData {
+    id_tag: String,
+    id_tag_info: Option<IdTagInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendLocalListRequest {
+    list_version: i32,
+    update_type: String,
+    #[serde(default)]
+    local_authorization_list: Vec<AuthorizationData>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StartTransactionRequest<'a> {
+    connector_id: u32,
+    id_tag: &'a str,
+    meter_start: i64,
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StartTransactionResponse {
+    transaction_id: i32,
+    id_tag_info: IdTagInfo,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SampledValue {
+    value: String,
+    measurand: &'static str,
+    unit: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MeterValue {
+    timestamp: DateTime<Utc>,
+    sampled_value: Vec<SampledValue>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MeterValuesRequest {
+    connector_id: u32,
+    transaction_id: i32,
+    meter_value: Vec<MeterValue>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StopTransactionRequest<'a> {
+    transaction_id: i32,
+    id_tag: Option<&'a str>,
+    meter_stop: i64,
+    timestamp: DateTime<Utc>,
+    reason: &'static str,
+}
+
+/// OCPP 1.6 charge point state: builds requests for kiosk events and keeps the
+/// transaction id handed out by the central system.
+pub struct ChargePointSession {
+    vendor: String,
+    model: String,
+    serial_number: String,
+    connector_id: u32,
+    transaction_id: Option<i32>,
+    id_tag: Option<String>,
+    // Id tag of the Authorize request awaiting its answer
+    authorizing: Option<String>,
+    latest_sample: Option<ChargerEvent>,
+    local_auth: SharedLocalAuth,
+    link: SharedBackendLink,
+}
+
+impl ChargePointSession {
+    pub fn new(config: &OcppConfig, local_auth: SharedLocalAuth, link: SharedBackendLink) -> Self {
+        Self {
+            vendor: config.vendor.clone(),
+            model: config.model.clone(),
+            serial_number: config.charge_point_id.clone(),
+            connector_id: config.connector_id,
+            transaction_id: None,
+            id_tag: None,
+            authorizing: None,
+            latest_sample: None,
+            local_auth,
+            link,
+        }
+    }
+
+    /// Cache the central system's verdict so the card also works offline
+    fn remember(&self, id_tag: &str, info: &IdTagInfo) {
+        self.local_auth.lock().unwrap().remember(IdToken::new(id_tag), info.to_local(), Utc::now());
+    }
+}
+
+impl Protocol for ChargePointSession {
+    fn boot_notification(&self) -> (&'static str, Value) {
+        let request = BootNotificationRequest {
+            charge_point_vendor: &self.vendor,
+            charge_point_model: &self.model,
+            charge_point_serial_number: &self.serial_number,
+            firmware_version: env!("CARGO_PKG_VERSION"),
+        };
+        ("BootNotification", to_payload(&request))
+    }
+
+    fn heartbeat(&self) -> (&'static str, Value) {
+        ("Heartbeat", json!({}))
+    }
+
+    /// Meter samples are coalesced: only the latest one is sent on each interval
+    fn record_sample(&mut self, sample: ChargerEvent) {
+        self.latest_sample = Some(sample);
+    }
+
+    fn meter_values(&mut self) -> Option<(&'static str, Value)> {
+        let transaction_id = self.transaction_id?;
+        let Some(ChargerEvent::MeterSample { energy_wh, power_w, soc, timestamp }) = self.latest_sample.take() else {
+            return None;
+        };
+
+        let mut sampled_value = vec![
+            SampledValue {
+                value: format!("{:.0}", energy_wh),
+                measurand: "Energy.Active.Import.Register",
+                unit: "Wh",
+            },
+            SampledValue {
+                value: format!("{:.0}", power_w),
+                measurand: "Power.Active.Import",
+                unit: "W",
+            },
+        ];
+        if let Some(soc) = soc {
+            sampled_value.push(SampledValue { value: format!("{:.0}", soc), measurand: "SoC", unit: "Percent" });
+        }
+
+        let request = MeterValuesRequest {
+            connector_id: self.connector_id,
+            transaction_id,
+            meter_value: vec![MeterValue { timestamp, sampled_value }],
+        };
+        Some(("MeterValues", to_payload(&request)))
+    }
+
+    /// Request for a queued kiosk event; built at send time so that messages
+    /// queued while offline pick up the transaction id once it is known
+    fn request_for(&mut self, event: &ChargerEvent) -> Option<(&'static str, Value)> {
+        match event {
+            ChargerEvent::ConnectorStatus(status) => {
+                let (status, error_code) = match status {
+                    ConnectorStatus::Available => ("Available", "NoError"),
+                    ConnectorStatus::Preparing => ("Preparing", "NoError"),
+                    ConnectorStatus::Charging => ("Charging", "NoError"),
+                    ConnectorStatus::Finishing => ("Finishing", "NoError"),
+                    ConnectorStatus::Faulted => ("Faulted", "OtherError"),
+                    ConnectorStatus::Unavailable => ("Unavailable", "NoError"),
+                };
+                let request = StatusNotificationRequest {
+                    connector_id: self.connector_id,
+                    error_code,
+                    status,
+                    timestamp: Utc::now(),
+                };
+                Some(("StatusNotification", to_payload(&request)))
+            }
+            ChargerEvent::Authorize { id_tag } => {
+                self.authorizing = Some(id_tag.clone());
+                Some(("Authorize", to_payload(&AuthorizeRequest { id_tag })))
+            }
+            ChargerEvent::SessionStarted { id_tag, meter_start_wh, timestamp } => {
+                self.id_tag = Some(id_tag.clone());
+                let request = StartTransactionRequest {
+                    connector_id: self.connector_id,
+                    id_tag,
+                    meter_start: meter_start_wh.round() as i64,
+                    timestamp: *timestamp,
+                };
+                Some(("StartTransaction", to_payload(&request)))
+            }
+            ChargerEvent::MeterSample { .. } => {
+                self.record_sample(event.clone());
+                None
+            }
+            ChargerEvent::SessionStopped { meter_stop_wh, reason, timestamp } => {
+                let Some(transaction_id) = self.transaction_id else {
+                    println!("OCPP: StopTransaction without an accepted StartTransaction, dropped");
+                    return None;
+                };
+                self.latest_sample = None;
+                let request = StopTransactionRequest {
+                    transaction_id,
+                    id_tag: self.id_tag.as_deref(),
+                    meter_stop: meter_stop_wh.round() as i64,
+                    timestamp: *timestamp,
+                    reason: match reason {
+                        StopReason::Local => "Local",
+                        StopReason::EvDisconnected => "EVDisconnected",
+                        StopReason::EmergencyStop => "EmergencyStop",
+                    },
+                };
+                Some(("StopTransaction", to_payload(&request)))
+            }
+        }
+    }
+
+    /// Apply the central system's answer to one of our requests
+    fn handle_response(&mut self, action: &str, payload: Value) -> Option<Registration> {
+        match action {
+            "BootNotification" => match serde_json::from_value::<BootNotificationResponse>(payload) {
+                Ok(response) => Some(Registration {
+                    status: response.status,
+                    interval: Duration::from_secs(response.interval.max(1)),
+                }),
+                Err(err) => {
+                    println!("OCPP: invalid BootNotification response: {}", err);
+                    None
+                }
+            },
+            "Authorize" => {
+                match serde_json::from_value::<AuthorizeResponse>(payload) {
+                    Ok(response) => {
+                        println!("OCPP: Authorize {}", response.id_tag_info.status);
+                        if let Some(id_tag) = self.authorizing.take() {
+                            self.remember(&id_tag, &response.id_tag_info);
+                            let status = authorization_status(&response.id_tag_info.status);
+                            self.link.lock().unwrap().answer(IdToken::new(&id_tag), status);
+                        }
+                    }
+                    Err(err) => println!("OCPP: invalid Authorize response: {}", err),
+                }
+                None
+            }
+            "StartTransaction" => {
+                match serde_json::from_value::<StartTransactionResponse>(payload) {
+                    Ok(response) => {
+                        println!(
+                            "OCPP: transaction {} started ({})",
+                            response.transaction_id, response.id_tag_info.status
+                        );
+                        self.transaction_id = Some(response.transaction_id);
+                        if let Some(ref id_tag) = self.id_tag {
+                            self.remember(id_tag, &response.id_tag_info);
+                        }
+                    }
+                    Err(err) => println!("OCPP: invalid StartTransaction response: {}", err),
+                }
+                None
+            }
+            "StopTransaction" => {
+                self.transaction_id = None;
+                self.id_tag = None;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Answer a request initiated by the central system
+    fn handle_request(&mut self, action: &str, payload: Value) -> Result<Value, (&'static str, String)> {
+        match action {
+            "SendLocalList" => {
+                let request: SendLocalListRequest = serde_json::from_value(payload)
+                    .map_err(|err| ("FormationViolation", err.to_string()))?;
+                let update = update_type(&request.update_type)
+                    .ok_or_else(|| ("PropertyConstraintViolation", format!("unknown updateType {}", request.update_type)))?;
+                let entries = request
+                    .local_authorization_list
+                    .iter()
+                    .map(|data| (IdToken::new(&data.id_tag), data.id_tag_info.as_ref().map(IdTagInfo::to_local)))
+                    .collect();
+                let result = self.local_auth.lock().unwrap().update_list(request.list_version, update, entries);
+                Ok(json!({ "status": send_local_list_status(result) }))
+            }
+            "GetLocalListVersion" => Ok(json!({ "listVersion": self.local_auth.lock().unwrap().list_version() })),
+            _ => Err(("NotImplemented", format!("{} is not supported by this charge point", action))),
+        }
+    }
+}
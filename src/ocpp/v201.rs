@@ -0,0 +1,633 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::ocpp::{
+    authorization_status, send_local_list_status, to_payload, update_type, ChargerEvent, ConnectorStatus, OcppConfig,
+    Protocol, Registration, RegistrationStatus, SharedBackendLink, StopReason,
+};
+use crate::auth::local_list::{self, SharedLocalAuth};
+
+pub const SUBPROTOCOL: &str = "ocpp2.0.1";
+
+// The kiosk is a single EVSE; its connector id comes from the configuration
+const EVSE_ID: u32 = 1;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChargingStation<'a> {
+    model: &'a str,
+    vendor_name: &'a str,
+    serial_number: &'a str,
+    firmware_version: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BootNotificationRequest<'a> {
+    reason: &'static str,
+    charging_station: ChargingStation<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BootNotificationResponse {
+    status: RegistrationStatus,
+    interval: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusNotificationRequest {
+    timestamp: DateTime<Utc>,
+    connector_status: &'static str,
+    evse_id: u32,
+    connector_id: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IdToken<'a> {
+    id_token: &'a str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizeRequest<'a> {
+    id_token: IdToken<'a>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdTokenInfo {
+    status: String,
+    cache_expiry_date_time: Option<DateTime<Utc>>,
+}
+
+impl IdTokenInfo {
+    fn to_local(&self) -> local_list::IdTagInfo {
+        local_list::IdTagInfo { status: authorization_status(&self.status), expiry: self.cache_expiry_date_time }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceivedIdToken {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizationData {
+    id_token: ReceivedIdToken,
+    id_token_info: Option<IdTokenInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendLocalListRequest {
+    version_number: i32,
+    update_type: String,
+    #[serde(default)]
+    local_authorization_list: Vec<AuthorizationData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizeResponse {
+    id_token_info: IdTokenInfo,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnitOfMeasure {
+    unit: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SampledValue {
+    value: f64,
+    context: &'static str,
+    measurand: &'static str,
+    unit_of_measure: UnitOfMeasure,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MeterValue {
+    timestamp: DateTime<Utc>,
+    sampled_value: Vec<SampledValue>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Evse {
+    id: u32,
+    connector_id: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionInfo<'a> {
+    transaction_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    charging_state: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stopped_reason: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionEventRequest<'a> {
+    event_type: &'static str,
+    timestamp: DateTime<Utc>,
+    trigger_reason: &'static str,
+    seq_no: u32,
+    transaction_info: TransactionInfo<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_token: Option<IdToken<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    evse: Option<Evse>,
+    meter_value: Vec<MeterValue>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionEventResponse {
+    id_token_info: Option<IdTokenInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvseRef {
+    id: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connector_id: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Component {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    evse: Option<EvseRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VariableRef {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetBaseReportRequest {
+    request_id: i32,
+    report_base: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetVariableData {
+    component: Component,
+    variable: VariableRef,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetVariablesRequest {
+    get_variable_data: Vec<GetVariableData>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetVariableResult {
+    attribute_status: &'static str,
+    component: Component,
+    variable: VariableRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attribute_value: Option<String>,
+}
+
+/// One variable of the device model. Everything the kiosk reports is read-only.
+#[derive(Debug, Clone)]
+struct Variable {
+    component: Component,
+    name: &'static str,
+    value: String,
+    data_type: &'static str,
+}
+
+/// The components and variables the station exposes to GetBaseReport and GetVariables
+#[derive(Debug, Clone)]
+struct DeviceModel {
+    variables: Vec<Variable>,
+}
+
+impl DeviceModel {
+    fn new(config: &OcppConfig) -> Self {
+        let station = |name: &str| Component { name: name.to_string(), evse: None };
+        let evse = Component { name: "EVSE".to_string(), evse: Some(EvseRef { id: EVSE_ID, connector_id: None }) };
+        let connector = Component {
+            name: "Connector".to_string(),
+            evse: Some(EvseRef { id: EVSE_ID, connector_id: Some(config.connector_id) }),
+        };
+        let variable = |component: &Component, name, value: String, data_type| Variable {
+            component: component.clone(),
+            name,
+            value,
+            data_type,
+        };
+
+        Self {
+            variables: vec![
+                variable(&station("ChargingStation"), "Model", config.model.clone(), "string"),
+                variable(&station("ChargingStation"), "VendorName", config.vendor.clone(), "string"),
+                variable(&station("SecurityCtrlr"), "Identity", config.charge_point_id.clone(), "string"),
+                variable(&station("OCPPCommCtrlr"), "HeartbeatInterval", "0".to_string(), "integer"),
+                variable(
+                    &station("SampledDataCtrlr"),
+                    "TxUpdatedInterval",
+                    config.meter_interval.as_secs().to_string(),
+                    "integer",
+                ),
+                variable(
+                    &station("SampledDataCtrlr"),
+                    "TxUpdatedMeasurands",
+                    "Energy.Active.Import.Register,Power.Active.Import,SoC".to_string(),
+                    "MemberList",
+                ),
+                variable(&evse, "AvailabilityState", "Available".to_string(), "OptionList"),
+                variable(&connector, "AvailabilityState", "Available".to_string(), "OptionList"),
+            ],
+        }
+    }
+
+    fn set(&mut self, component: &str, name: &str, value: String) {
+        for variable in &mut self.variables {
+            if variable.component.name == component && variable.name == name {
+                variable.value = value.clone();
+            }
+        }
+    }
+
+    fn get(&self, component: &Component, name: &str) -> GetVariableResult {
+        let matches_component = |variable: &&Variable| {
+            variable.component.name == component.name
+                && component.evse.as_ref().is_none_or(|evse| variable.component.evse.as_ref() == Some(evse))
+        };
+        let mut candidates = self.variables.iter().filter(matches_component).peekable();
+        let (attribute_status, attribute_value) = if candidates.peek().is_none() {
+            ("UnknownComponent", None)
+        } else {
+            match candidates.find(|variable| variable.name == name) {
+                Some(variable) => ("Accepted", Some(variable.value.clone())),
+                None => ("UnknownVariable", None),
+            }
+        };
+        GetVariableResult {
+            attribute_status,
+            component: component.clone(),
+            variable: VariableRef { name: name.to_string() },
+            attribute_value,
+        }
+    }
+
+    fn report(&self, request_id: i32) -> Value {
+        let report_data: Vec<Value> = self
+            .variables
+            .iter()
+            .map(|variable| {
+                json!({
+                    "component": variable.component,
+                    "variable": { "name": variable.name },
+                    "variableAttribute": [{ "type": "Actual", "value": variable.value, "mutability": "ReadOnly" }],
+                    "variableCharacteristics": { "dataType": variable.data_type, "supportsMonitoring": false },
+                })
+            })
+            .collect();
+        json!({
+            "requestId": request_id,
+            "generatedAt": Utc::now(),
+            "tbc": false,
+            "seqNo": 0,
+            "reportData": report_data,
+        })
+    }
+}
+
+struct Transaction {
+    id: String,
+    seq_no: u32,
+    // Kept until the Ended event is answered so it can be resent after a reconnect
+    ended: bool,
+}
+
+impl Transaction {
+    fn next_seq_no(&mut self) -> u32 {
+        let seq_no = self.seq_no;
+        self.seq_no += 1;
+        seq_no
+    }
+}
+
+/// OCPP 2.0.1 charging station state. Unlike 1.6 the station generates the
+/// transaction id itself, so transaction events never wait on the central system.
+pub struct ChargingStationSession {
+    vendor: String,
+    model: String,
+    serial_number: String,
+    connector_id: u32,
+    device_model: DeviceModel,
+    transaction: Option<Transaction>,
+    latest_sample: Option<ChargerEvent>,
+    reports: VecDeque<(&'static str, Value)>,
+    // Id token of the Authorize request awaiting its answer
+    authorizing: Option<String>,
+    local_auth: SharedLocalAuth,
+    link: SharedBackendLink,
+}
+
+impl ChargingStationSession {
+    pub fn new(config: &OcppConfig, local_auth: SharedLocalAuth, link: SharedBackendLink) -> Self {
+        Self {
+            vendor: config.vendor.clone(),
+            model: config.model.clone(),
+            serial_number: config.charge_point_id.clone(),
+            connector_id: config.connector_id,
+            device_model: DeviceModel::new(config),
+            transaction: None,
+            latest_sample: None,
+            reports: VecDeque::new(),
+            authorizing: None,
+            local_auth,
+            link,
+        }
+    }
+}
+
+fn energy_sample(energy_wh: f64, context: &'static str) -> SampledValue {
+    SampledValue {
+        value: energy_wh.round(),
+        context,
+        measurand: "Energy.Active.Import.Register",
+        unit_of_measure: UnitOfMeasure { unit: "Wh" },
+    }
+}
+
+impl Protocol for ChargingStationSession {
+    fn boot_notification(&self) -> (&'static str, Value) {
+        let request = BootNotificationRequest {
+            reason: "PowerUp",
+            charging_station: ChargingStation {
+                model: &self.model,
+                vendor_name: &self.vendor,
+                serial_number: &self.serial_number,
+                firmware_version: env!("CARGO_PKG_VERSION"),
+            },
+        };
+        ("BootNotification", to_payload(&request))
+    }
+
+    fn heartbeat(&self) -> (&'static str, Value) {
+        ("Heartbeat", json!({}))
+    }
+
+    /// Meter samples are coalesced: only the latest one is sent on each interval
+    fn record_sample(&mut self, sample: ChargerEvent) {
+        self.latest_sample = Some(sample);
+    }
+
+    fn meter_values(&mut self) -> Option<(&'static str, Value)> {
+        let transaction = self.transaction.as_mut().filter(|transaction| !transaction.ended)?;
+        let Some(ChargerEvent::MeterSample { energy_wh, power_w, soc, timestamp }) = self.latest_sample.take() else {
+            return None;
+        };
+
+        let mut sampled_value = vec![
+            energy_sample(energy_wh, "Sample.Periodic"),
+            SampledValue {
+                value: power_w.round() as f64,
+                context: "Sample.Periodic",
+                measurand: "Power.Active.Import",
+                unit_of_measure: UnitOfMeasure { unit: "W" },
+            },
+        ];
+        if let Some(soc) = soc {
+            sampled_value.push(SampledValue {
+                value: soc.round() as f64,
+                context: "Sample.Periodic",
+                measurand: "SoC",
+                unit_of_measure: UnitOfMeasure { unit: "Percent" },
+            });
+        }
+
+        let request = TransactionEventRequest {
+            event_type: "Updated",
+            timestamp,
+            trigger_reason: "MeterValuePeriodic",
+            seq_no: transaction.next_seq_no(),
+            transaction_info: TransactionInfo {
+                transaction_id: &transaction.id,
+                charging_state: Some("Charging"),
+                stopped_reason: None,
+            },
+            id_token: None,
+            evse: None,
+            meter_value: vec![MeterValue { timestamp, sampled_value }],
+        };
+        Some(("TransactionEvent", to_payload(&request)))
+    }
+
+    fn request_for(&mut self, event: &ChargerEvent) -> Option<(&'static str, Value)> {
+        match event {
+            ChargerEvent::ConnectorStatus(status) => {
+                // 2.0.1 only knows whether the connector is in use; charging
+                // progress is carried by the transaction events instead
+                let connector_status = match status {
+                    ConnectorStatus::Available => "Available",
+                    ConnectorStatus::Preparing | ConnectorStatus::Charging | ConnectorStatus::Finishing => "Occupied",
+                    ConnectorStatus::Faulted => "Faulted",
+                    ConnectorStatus::Unavailable => "Unavailable",
+                };
+                let availability = match status {
+                    ConnectorStatus::Faulted => "Faulted",
+                    ConnectorStatus::Unavailable => "Unavailable",
+                    _ => "Available",
+                };
+                self.device_model.set("EVSE", "AvailabilityState", availability.to_string());
+                self.device_model.set("Connector", "AvailabilityState", connector_status.to_string());
+
+                let request = StatusNotificationRequest {
+                    timestamp: Utc::now(),
+                    connector_status,
+                    evse_id: EVSE_ID,
+                    connector_id: self.connector_id,
+                };
+                Some(("StatusNotification", to_payload(&request)))
+            }
+            ChargerEvent::Authorize { id_tag } => {
+                self.authorizing = Some(id_tag.clone());
+                let request = AuthorizeRequest { id_token: IdToken { id_token: id_tag, kind: "Local" } };
+                Some(("Authorize", to_payload(&request)))
+            }
+            ChargerEvent::SessionStarted { id_tag, meter_start_wh, timestamp } => {
+                // A Started event resent after a reconnect keeps its transaction id
+                if self.transaction.as_ref().is_none_or(|transaction| transaction.ended) {
+                    self.transaction = Some(Transaction { id: uuid::Uuid::new_v4().to_string(), seq_no: 0, ended: false });
+                }
+                let transaction = self.transaction.as_mut()?;
+                let request = TransactionEventRequest {
+                    event_type: "Started",
+                    timestamp: *timestamp,
+                    trigger_reason: "Authorized",
+                    seq_no: transaction.next_seq_no(),
+                    transaction_info: TransactionInfo {
+                        transaction_id: &transaction.id,
+                        charging_state: Some("Charging"),
+                        stopped_reason: None,
+                    },
+                    id_token: Some(IdToken { id_token: id_tag, kind: "Local" }),
+                    evse: Some(Evse { id: EVSE_ID, connector_id: self.connector_id }),
+                    meter_value: vec![MeterValue {
+                        timestamp: *timestamp,
+                        sampled_value: vec![energy_sample(*meter_start_wh, "Transaction.Begin")],
+                    }],
+                };
+                Some(("TransactionEvent", to_payload(&request)))
+            }
+            ChargerEvent::MeterSample { .. } => {
+                self.record_sample(event.clone());
+                None
+            }
+            ChargerEvent::SessionStopped { meter_stop_wh, reason, timestamp } => {
+                let Some(transaction) = self.transaction.as_mut() else {
+                    println!("OCPP: TransactionEvent Ended without a started transaction, dropped");
+                    return None;
+                };
+                self.latest_sample = None;
+                transaction.ended = true;
+                let (trigger_reason, stopped_reason) = match reason {
+                    StopReason::Local => ("StopAuthorized", "Local"),
+                    StopReason::EvDisconnected => ("EVCommunicationLost", "EVDisconnected"),
+                    StopReason::EmergencyStop => ("AbnormalCondition", "EmergencyStop"),
+                };
+                let request = TransactionEventRequest {
+                    event_type: "Ended",
+                    timestamp: *timestamp,
+                    trigger_reason,
+                    seq_no: transaction.next_seq_no(),
+                    transaction_info: TransactionInfo {
+                        transaction_id: &transaction.id,
+                        charging_state: None,
+                        stopped_reason: Some(stopped_reason),
+                    },
+                    id_token: None,
+                    evse: None,
+                    meter_value: vec![MeterValue {
+                        timestamp: *timestamp,
+                        sampled_value: vec![energy_sample(*meter_stop_wh, "Transaction.End")],
+                    }],
+                };
+                Some(("TransactionEvent", to_payload(&request)))
+            }
+        }
+    }
+
+    fn handle_response(&mut self, action: &str, payload: Value) -> Option<Registration> {
+        match action {
+            "BootNotification" => match serde_json::from_value::<BootNotificationResponse>(payload) {
+                Ok(response) => {
+                    let interval = Duration::from_secs(response.interval.max(1));
+                    self.device_model.set("OCPPCommCtrlr", "HeartbeatInterval", interval.as_secs().to_string());
+                    Some(Registration { status: response.status, interval })
+                }
+                Err(err) => {
+                    println!("OCPP: invalid BootNotification response: {}", err);
+                    None
+                }
+            },
+            "Authorize" => {
+                match serde_json::from_value::<AuthorizeResponse>(payload) {
+                    Ok(response) => {
+                        println!("OCPP: Authorize {}", response.id_token_info.status);
+                        // Cache the verdict so the token also works offline
+                        if let Some(id_token) = self.authorizing.take() {
+                            let info = response.id_token_info.to_local();
+                            let token = crate::auth::IdToken::new(&id_token);
+                            self.link.lock().unwrap().answer(token.clone(), info.status);
+                            self.local_auth.lock().unwrap().remember(token, info, Utc::now());
+                        }
+                    }
+                    Err(err) => println!("OCPP: invalid Authorize response: {}", err),
+                }
+                None
+            }
+            "TransactionEvent" => {
+                if self.transaction.as_ref().is_some_and(|transaction| transaction.ended) {
+                    self.transaction = None;
+                }
+                match serde_json::from_value::<TransactionEventResponse>(payload) {
+                    Ok(TransactionEventResponse { id_token_info: Some(info) }) => {
+                        println!("OCPP: transaction id token {}", info.status)
+                    }
+                    Ok(_) => {}
+                    Err(err) => println!("OCPP: invalid TransactionEvent response: {}", err),
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_request(&mut self, action: &str, payload: Value) -> Result<Value, (&'static str, String)> {
+        match action {
+            "GetBaseReport" => {
+                let request: GetBaseReportRequest = serde_json::from_value(payload)
+                    .map_err(|err| ("FormationViolation", err.to_string()))?;
+                // Nothing in the device model is writable, so a configuration
+                // inventory would always be empty
+                if request.report_base == "ConfigurationInventory" {
+                    return Ok(json!({ "status": "EmptyResultSet" }));
+                }
+                self.reports.push_back(("NotifyReport", self.device_model.report(request.request_id)));
+                Ok(json!({ "status": "Accepted" }))
+            }
+            "GetVariables" => {
+                let request: GetVariablesRequest = serde_json::from_value(payload)
+                    .map_err(|err| ("FormationViolation", err.to_string()))?;
+                let results: Vec<GetVariableResult> = request
+                    .get_variable_data
+                    .iter()
+                    .map(|data| self.device_model.get(&data.component, &data.variable.name))
+                    .collect();
+                Ok(json!({ "getVariableResult": results }))
+            }
+            "SendLocalList" => {
+                let request: SendLocalListRequest = serde_json::from_value(payload)
+                    .map_err(|err| ("FormationViolation", err.to_string()))?;
+                let update = update_type(&request.update_type)
+                    .ok_or_else(|| ("PropertyConstraintViolation", format!("unknown updateType {}", request.update_type)))?;
+                let entries = request
+                    .local_authorization_list
+                    .iter()
+                    .map(|data| {
+                        (crate::auth::IdToken::new(&data.id_token.id_token), data.id_token_info.as_ref().map(IdTokenInfo::to_local))
+                    })
+                    .collect();
+                let result = self.local_auth.lock().unwrap().update_list(request.version_number, update, entries);
+                Ok(json!({ "status": send_local_list_status(result) }))
+            }
+            "GetLocalListVersion" => Ok(json!({ "versionNumber": self.local_auth.lock().unwrap().list_version() })),
+            _ => Err(("NotImplemented", format!("{} is not supported by this charging station", action))),
+        }
+    }
+
+    fn next_request(&mut self) -> Option<(&'static str, Value)> {
+        self.reports.pop_front()
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_AVAILABILITY_PATH: &str = "data/availability.json";
+
+// 연속으로 틀리면 잠금
+const MAX_PIN_ATTEMPTS: u32 = 3;
+const PIN_LOCKOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum OperatorError {
+    #[error("cannot save connector availability: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("cannot encode connector availability: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinResult {
+    Accepted,
+    Rejected { attempts_left: u32 },
+    LockedOut(Duration),
+}
+
+/// Operator PIN check, locked for a while after too many wrong entries.
+/// Kept across visits to the operator screen so leaving does not reset it.
+#[derive(Debug)]
+pub struct PinLock {
+    pin: String,
+    failures: u32,
+    locked_until: Option<Instant>,
+}
+
+impl PinLock {
+    pub fn new(pin: &str) -> Self {
+        Self { pin: pin.to_string(), failures: 0, locked_until: None }
+    }
+
+    pub fn enter(&mut self, attempt: &str, now: Instant) -> PinResult {
+        if let Some(remaining) = self.lockout_remaining(now) {
+            return PinResult::LockedOut(remaining);
+        }
+        if attempt == self.pin {
+            self.failures = 0;
+            return PinResult::Accepted;
+        }
+        self.failures += 1;
+        if self.failures >= MAX_PIN_ATTEMPTS {
+            self.failures = 0;
+            self.locked_until = Some(now + PIN_LOCKOUT);
+            return PinResult::LockedOut(PIN_LOCKOUT);
+        }
+        PinResult::Rejected { attempts_left: MAX_PIN_ATTEMPTS - self.failures }
+    }
+
+    pub fn lockout_remaining(&self, now: Instant) -> Option<Duration> {
+        self.locked_until.filter(|until| *until > now).map(|until| until - now)
+    }
+}
+
+/// Connectors an operator took out of service, kept across restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Availability {
+    unavailable: BTreeSet<u32>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+pub type SharedAvailability = Arc<Mutex<Availability>>;
+
+impl Availability {
+    /// Saved state from `path`; every connector is available if there is none
+    pub fn load(path: &Path) -> Availability {
+        let mut availability = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+                println!("Ignoring connector availability in {}: {}", path.display(), err);
+                Availability::default()
+            }),
+            Err(_) => Availability::default(),
+        };
+        availability.path = Some(path.to_path_buf());
+        availability
+    }
+
+    pub fn is_available(&self, connector_id: u32) -> bool {
+        !self.unavailable.contains(&connector_id)
+    }
+
+    pub fn set_available(&mut self, connector_id: u32, available: bool) -> Result<(), OperatorError> {
+        if available {
+            self.unavailable.remove(&connector_id);
+        } else {
+            self.unavailable.insert(connector_id);
+        }
+        println!("Connector {} is now {}", connector_id, if available { "available" } else { "unavailable" });
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), OperatorError> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Start a fresh copy of the kiosk with the same command line; the caller
+/// then closes this one
+pub fn restart() -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    std::process::Command::new(exe).args(std::env::args_os().skip(1)).spawn()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrong_pins_lock_the_pad() {
+        let mut lock = PinLock::new("2580");
+        let start = Instant::now();
+        assert_eq!(lock.enter("1111", start), PinResult::Rejected { attempts_left: 2 });
+        assert_eq!(lock.enter("2580", start), PinResult::Accepted);
+
+        lock.enter("1111", start);
+        lock.enter("2222", start);
+        assert_eq!(lock.enter("3333", start), PinResult::LockedOut(PIN_LOCKOUT));
+        // 잠긴 동안에는 맞는 PIN도 거부
+        let later = start + Duration::from_secs(10);
+        assert_eq!(lock.enter("2580", later), PinResult::LockedOut(PIN_LOCKOUT - Duration::from_secs(10)));
+        assert_eq!(lock.enter("2580", start + PIN_LOCKOUT), PinResult::Accepted);
+    }
+
+    #[test]
+    fn availability_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("ev-charger-availability-{}.json", uuid::Uuid::new_v4()));
+        let mut availability = Availability::load(&path);
+        assert!(availability.is_available(1));
+        availability.set_available(1, false).unwrap();
+        availability.set_available(2, false).unwrap();
+        availability.set_available(2, true).unwrap();
+
+        let reloaded = Availability::load(&path);
+        assert!(!reloaded.is_available(1));
+        assert!(reloaded.is_available(2));
+        std::fs::remove_file(&path).ok();
+    }
+}
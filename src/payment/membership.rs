@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use crate::auth::IdToken;
+use crate::membership::SharedMembers;
+use crate::payment::{Authorization, Capture, PaymentError, PaymentProvider};
+use crate::screen::PaymentMethod;
+
+/// Pays sessions from a member's prepaid balance. Holds only reserve the
+/// amount; the balance changes on capture.
+pub struct MembershipProvider {
+    members: SharedMembers,
+    next_id: u32,
+    // Authorization id -> (member id, held amount)
+    holds: HashMap<String, (String, f64)>,
+    // Capture id -> member id, for refunds
+    captures: HashMap<String, String>,
+}
+
+impl MembershipProvider {
+    pub fn new(members: SharedMembers) -> Self {
+        Self { members, next_id: 1, holds: HashMap::new(), captures: HashMap::new() }
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        let id = format!("{}-{:06}", prefix, self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    fn held_for(&self, member_id: &str) -> f64 {
+        self.holds.values().filter(|(id, _)| id == member_id).map(|(_, amount)| amount).sum()
+    }
+}
+
+impl PaymentProvider for MembershipProvider {
+    fn pre_authorize(
+        &mut self,
+        method: &PaymentMethod,
+        id_token: Option<&IdToken>,
+        amount: f64,
+    ) -> Result<Authorization, PaymentError> {
+        let token = id_token.ok_or_else(|| PaymentError::Declined("tap your membership card".to_string()))?;
+        let (member_id, balance) = {
+            let members = self.members.lock().unwrap();
+            let member = members
+                .find_by_token(token)
+                .ok_or_else(|| PaymentError::Declined("card is not linked to a membership".to_string()))?;
+            (member.id.clone(), member.balance)
+        };
+        let available = balance - self.held_for(&member_id);
+        if available < amount {
+            return Err(PaymentError::Declined(format!("insufficient balance ({:.0} available)", available.max(0.0))));
+        }
+
+        let id = self.next_id("MBR");
+        self.holds.insert(id.clone(), (member_id, amount));
+        Ok(Authorization { id, method: method.clone(), amount, id_token: Some(token.clone()) })
+    }
+
+    fn capture(&mut self, authorization: &Authorization, amount: f64) -> Result<Capture, PaymentError> {
+        let (member_id, held) = self
+            .holds
+            .remove(&authorization.id)
+            .ok_or_else(|| PaymentError::Declined(format!("unknown authorization {}", authorization.id)))?;
+        if amount > held {
+            self.holds.insert(authorization.id.clone(), (member_id, held));
+            return Err(PaymentError::Declined(format!("{:.0} exceeds the held {:.0}", amount, held)));
+        }
+        let balance = self
+            .members
+            .lock()
+            .unwrap()
+            .charge(&member_id, amount)
+            .map_err(|err| PaymentError::Communication(err.to_string()))?;
+        println!("Member {}: charged {:.0}, balance {:.0}", member_id, amount, balance);
+
+        let id = self.next_id("MCAP");
+        self.captures.insert(id.clone(), member_id);
+        Ok(Capture { id, authorization_id: authorization.id.clone(), method: authorization.method.clone(), amount })
+    }
+
+    fn void(&mut self, authorization: &Authorization) -> Result<(), PaymentError> {
+        self.holds
+            .remove(&authorization.id)
+            .map(|_| ())
+            .ok_or_else(|| PaymentError::Declined(format!("unknown authorization {}", authorization.id)))
+    }
+
+    fn refund(&mut self, capture: &Capture, amount: f64) -> Result<(), PaymentError> {
+        if amount > capture.amount {
+            return Err(PaymentError::Declined(format!("{:.0} exceeds the captured {:.0}", amount, capture.amount)));
+        }
+        let member_id = self
+            .captures
+            .get(&capture.id)
+            .ok_or_else(|| PaymentError::Declined(format!("unknown capture {}", capture.id)))?;
+        self.members
+            .lock()
+            .unwrap()
+            .credit(member_id, amount)
+            .map_err(|err| PaymentError::Communication(err.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::membership::{Member, MemberStore, Plan};
+
+    #[test]
+    fn sessions_draw_down_the_member_balance() {
+        let card = IdToken::new("04A1B2C3");
+        let members = Arc::new(Mutex::new(MemberStore::new(vec![Member {
+            id: "M0001".to_string(),
+            name: "Demo".to_string(),
+            cards: vec![card.clone()],
+            balance: 10000.0,
+            plan: Plan { name: "Basic".to_string(), energy_discount_percent: 10.0 },
+        }])));
+        let mut provider = MembershipProvider::new(members.clone());
+        let method = PaymentMethod::Membership;
+
+        let first = provider.pre_authorize(&method, Some(&card), 6000.0).unwrap();
+        // The first hold leaves only 4000 available
+        assert!(matches!(provider.pre_authorize(&method, Some(&card), 6000.0), Err(PaymentError::Declined(_))));
+        assert!(matches!(
+            provider.pre_authorize(&method, Some(&IdToken::new("CAFEBABE")), 100.0),
+            Err(PaymentError::Declined(_))
+        ));
+
+        let capture = provider.capture(&first, 2500.0).unwrap();
+        assert_eq!(members.lock().unwrap().find_by_token(&card).unwrap().balance, 7500.0);
+        provider.refund(&capture, 500.0).unwrap();
+        assert_eq!(members.lock().unwrap().find_by_token(&card).unwrap().balance, 8000.0);
+        assert!(provider.pre_authorize(&method, Some(&card), 8000.0).is_ok());
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::auth::IdToken;
+use crate::payment::{Authorization, Capture, PaymentError, PaymentProvider};
+use crate::screen::PaymentMethod;
+
+/// What the mock answers to every pre-authorization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockOutcome {
+    Approve,
+    Decline,
+    Timeout,
+    CommunicationError,
+}
+
+impl MockOutcome {
+    pub fn parse(value: &str) -> Option<MockOutcome> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "approve" => Some(MockOutcome::Approve),
+            "decline" => Some(MockOutcome::Decline),
+            "timeout" => Some(MockOutcome::Timeout),
+            "error" => Some(MockOutcome::CommunicationError),
+            _ => None,
+        }
+    }
+}
+
+/// Stand-in payment terminal for development. Holds and captures are kept in
+/// memory and logged to the console.
+pub struct MockPaymentProvider {
+    outcome: MockOutcome,
+    latency: Duration,
+    timeout: Duration,
+    next_id: u32,
+    holds: HashMap<String, f64>,
+}
+
+impl MockPaymentProvider {
+    pub fn new(outcome: MockOutcome, latency: Duration) -> Self {
+        Self {
+            outcome,
+            latency,
+            timeout: Duration::from_secs(5),
+            next_id: 1,
+            holds: HashMap::new(),
+        }
+    }
+
+    /// How long a `Timeout` outcome keeps the customer waiting
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn next_id(&mut self, prefix: &str) -> String {
+        let id = format!("{}-{:06}", prefix, self.next_id);
+        self.next_id += 1;
+        id
+    }
+}
+
+impl PaymentProvider for MockPaymentProvider {
+    fn pre_authorize(
+        &mut self,
+        method: &PaymentMethod,
+        id_token: Option<&IdToken>,
+        amount: f64,
+    ) -> Result<Authorization, PaymentError> {
+        match self.outcome {
+            MockOutcome::Approve => {
+                std::thread::sleep(self.latency);
+                let id = self.next_id("AUTH");
+                self.holds.insert(id.clone(), amount);
+                println!("Mock payment: {:?} hold {} for {:.0}", method, id, amount);
+                Ok(Authorization { id, method: method.clone(), amount, id_token: id_token.cloned() })
+            }
+            MockOutcome::Decline => {
+                std::thread::sleep(self.latency);
+                Err(PaymentError::Declined("insufficient funds".to_string()))
+            }
+            MockOutcome::Timeout => {
+                std::thread::sleep(self.timeout);
+                Err(PaymentError::Timeout)
+            }
+            MockOutcome::CommunicationError => {
+                std::thread::sleep(self.latency);
+                Err(PaymentError::Communication("gateway unreachable".to_string()))
+            }
+        }
+    }
+
+    fn capture(&mut self, authorization: &Authorization, amount: f64) -> Result<Capture, PaymentError> {
+        let held = self
+            .holds
+            .remove(&authorization.id)
+            .ok_or_else(|| PaymentError::Declined(format!("unknown authorization {}", authorization.id)))?;
+        if amount > held {
+            self.holds.insert(authorization.id.clone(), held);
+            return Err(PaymentError::Declined(format!("{:.0} exceeds the held {:.0}", amount, held)));
+        }
+        let id = self.next_id("CAP");
+        println!("Mock payment: captured {:.0} of {:.0} on {}", amount, held, authorization.id);
+        Ok(Capture { id, authorization_id: authorization.id.clone(), method: authorization.method.clone(), amount })
+    }
+
+    fn void(&mut self, authorization: &Authorization) -> Result<(), PaymentError> {
+        match self.holds.remove(&authorization.id) {
+            Some(_) => {
+                println!("Mock payment: released hold {}", authorization.id);
+                Ok(())
+            }
+            None => Err(PaymentError::Declined(format!("unknown authorization {}", authorization.id))),
+        }
+    }
+
+    fn refund(&mut self, capture: &Capture, amount: f64) -> Result<(), PaymentError> {
+        if amount > capture.amount {
+            return Err(PaymentError::Declined(format!("{:.0} exceeds the captured {:.0}", amount, capture.amount)));
+        }
+        println!("Mock payment: refunded {:.0} of capture {}", amount, capture.id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_up_to_the_held_amount_once() {
+        let mut provider = MockPaymentProvider::new(MockOutcome::Approve, Duration::ZERO);
+        let authorization = provider.pre_authorize(&PaymentMethod::CreditCard, None, 5000.0).unwrap();
+
+        assert!(matches!(provider.capture(&authorization, 6000.0), Err(PaymentError::Declined(_))));
+        let capture = provider.capture(&authorization, 4200.0).unwrap();
+        assert_eq!(capture.amount, 4200.0);
+        assert!(provider.capture(&authorization, 100.0).is_err());
+        assert!(provider.void(&authorization).is_err());
+        assert!(provider.refund(&capture, 5000.0).is_err());
+        assert_eq!(provider.refund(&capture, 1000.0), Ok(()));
+    }
+
+    #[test]
+    fn reports_the_configured_failure() {
+        let mut provider = MockPaymentProvider::new(MockOutcome::Decline, Duration::ZERO);
+        assert!(matches!(provider.pre_authorize(&PaymentMethod::MobileApp, None, 1000.0), Err(PaymentError::Declined(_))));
+
+        let mut provider = MockPaymentProvider::new(MockOutcome::CommunicationError, Duration::ZERO);
+        assert!(matches!(provider.pre_authorize(&PaymentMethod::MobileApp, None, 1000.0), Err(PaymentError::Communication(_))));
+
+        let mut provider = MockPaymentProvider::new(MockOutcome::Approve, Duration::ZERO);
+        let authorization = provider.pre_authorize(&PaymentMethod::MobileApp, None, 1000.0).unwrap();
+        assert_eq!(provider.void(&authorization), Ok(()));
+    }
+}
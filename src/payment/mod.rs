@@ -0,0 +1,175 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use crate::auth::IdToken;
+use crate::membership::SharedMembers;
+use crate::screen::PaymentMethod;
+
+pub mod membership;
+pub mod mock;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PaymentError {
+    #[error("payment declined: {0}")]
+    Declined(String),
+    #[error("payment terminal did not respond in time")]
+    Timeout,
+    #[error("cannot reach the payment service: {0}")]
+    Communication(String),
+    #[error("payment cancelled")]
+    Cancelled,
+}
+
+/// Funds held on the customer's account before charging starts
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Authorization {
+    pub id: String,
+    pub method: PaymentMethod,
+    pub amount: f64,
+    // Card the customer identified with, for RFID and membership payments
+    pub id_token: Option<IdToken>,
+}
+
+/// Money actually taken from an authorization
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub id: String,
+    pub authorization_id: String,
+    pub method: PaymentMethod,
+    pub amount: f64,
+}
+
+/// Card terminal / payment gateway. Calls block until the provider answers,
+/// so the UI runs them through `PaymentRequest`.
+pub trait PaymentProvider: Send {
+    /// Hold `amount` before the session starts
+    fn pre_authorize(
+        &mut self,
+        method: &PaymentMethod,
+        id_token: Option<&IdToken>,
+        amount: f64,
+    ) -> Result<Authorization, PaymentError>;
+    /// Take the final session cost, at most the held amount
+    fn capture(&mut self, authorization: &Authorization, amount: f64) -> Result<Capture, PaymentError>;
+    /// Release a hold that will not be captured
+    fn void(&mut self, authorization: &Authorization) -> Result<(), PaymentError>;
+    /// Return money from an earlier capture
+    #[allow(dead_code)] // no refund flow in the kiosk yet
+    fn refund(&mut self, capture: &Capture, amount: f64) -> Result<(), PaymentError>;
+}
+
+pub type SharedPaymentProvider = Arc<Mutex<Box<dyn PaymentProvider>>>;
+
+/// Membership payments go to the member accounts, everything else to the card terminal
+pub struct PaymentGateway {
+    terminal: Box<dyn PaymentProvider>,
+    members: membership::MembershipProvider,
+}
+
+impl PaymentGateway {
+    pub fn new(terminal: Box<dyn PaymentProvider>, members: SharedMembers) -> Self {
+        Self { terminal, members: membership::MembershipProvider::new(members) }
+    }
+
+    fn provider_for(&mut self, method: &PaymentMethod) -> &mut dyn PaymentProvider {
+        match method {
+            PaymentMethod::Membership => &mut self.members,
+            _ => self.terminal.as_mut(),
+        }
+    }
+}
+
+impl PaymentProvider for PaymentGateway {
+    fn pre_authorize(
+        &mut self,
+        method: &PaymentMethod,
+        id_token: Option<&IdToken>,
+        amount: f64,
+    ) -> Result<Authorization, PaymentError> {
+        self.provider_for(method).pre_authorize(method, id_token, amount)
+    }
+
+    fn capture(&mut self, authorization: &Authorization, amount: f64) -> Result<Capture, PaymentError> {
+        self.provider_for(&authorization.method).capture(authorization, amount)
+    }
+
+    fn void(&mut self, authorization: &Authorization) -> Result<(), PaymentError> {
+        self.provider_for(&authorization.method).void(authorization)
+    }
+
+    fn refund(&mut self, capture: &Capture, amount: f64) -> Result<(), PaymentError> {
+        self.provider_for(&capture.method).refund(capture, amount)
+    }
+}
+
+// How long the development terminal takes to answer
+const MOCK_LATENCY: Duration = Duration::from_millis(1500);
+
+pub fn create_payment_provider(members: SharedMembers, outcome: mock::MockOutcome, timeout: Duration) -> SharedPaymentProvider {
+    let terminal = Box::new(mock::MockPaymentProvider::new(outcome, MOCK_LATENCY).with_timeout(timeout));
+    Arc::new(Mutex::new(Box::new(PaymentGateway::new(terminal, members))))
+}
+
+/// A provider call running on its own thread
+pub struct PaymentRequest<T> {
+    result: mpsc::Receiver<Result<T, PaymentError>>,
+}
+
+impl<T: Send + 'static> PaymentRequest<T> {
+    pub fn spawn<F>(provider: SharedPaymentProvider, call: F) -> Self
+    where
+        F: FnOnce(&mut dyn PaymentProvider) -> Result<T, PaymentError> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut provider = provider.lock().unwrap();
+            let _ = tx.send(call(provider.as_mut()));
+        });
+        Self { result: rx }
+    }
+
+    /// The provider's answer, once it has arrived
+    pub fn poll(&self) -> Option<Result<T, PaymentError>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Some(Err(PaymentError::Communication("payment worker stopped".to_string())))
+            }
+        }
+    }
+}
+
+impl PaymentRequest<Authorization> {
+    /// Give up on a pre-authorization the customer cancelled. The terminal may
+    /// still approve it, so any hold that arrives later is released.
+    pub fn cancel(self, provider: SharedPaymentProvider) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            if let Ok(Ok(authorization)) = self.result.recv() {
+                match provider.lock().unwrap().void(&authorization) {
+                    Ok(()) => println!("Released cancelled payment hold {}", authorization.id),
+                    Err(err) => println!("Failed to release cancelled payment hold {}: {}", authorization.id, err),
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use mock::{MockOutcome, MockPaymentProvider};
+
+    #[test]
+    fn cancelled_authorization_is_released_when_it_arrives() {
+        let provider: SharedPaymentProvider =
+            Arc::new(Mutex::new(Box::new(MockPaymentProvider::new(MockOutcome::Approve, Duration::from_millis(50)))));
+        let request = PaymentRequest::spawn(provider.clone(), |provider| provider.pre_authorize(&PaymentMethod::CreditCard, None, 1000.0));
+        request.cancel(provider.clone()).join().unwrap();
+
+        // The mock numbers its holds, so the late approval was AUTH-000001
+        let authorization =
+            Authorization { id: "AUTH-000001".to_string(), method: PaymentMethod::CreditCard, amount: 1000.0, id_token: None };
+        assert!(matches!(provider.lock().unwrap().capture(&authorization, 1000.0), Err(PaymentError::Declined(_))));
+    }
+}
@@ -1,22 +1,46 @@
 use eframe::egui;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use crate::screen::{
-    SplashScreen, ConnectScreen, StandbyScreen, FullChargeScreen,
-    SelectAmountScreen, PaymentScreen, ChargingScreen, CompleteScreen,
-    ChargeType, PaymentMethod
+    SplashScreen, ConnectScreen, StandbyScreen,
+    SelectAmountScreen, PaymentScreen, PaymentFailedScreen, ChargingScreen, CompleteScreen, MemberScreen,
+    OperatorScreen,
+    ChargeType, PaymentMethod, Screen, ScreenAction
 };
+use crate::hardware::{self, SharedCharger};
+use crate::hardware::control_pilot::PilotState;
+use crate::ocpp::{self, ChargerEvent, ConnectorStatus, OcppConfig, OcppHandle, StopReason};
+use crate::tariff::{Bill, Tariff};
+use crate::session::{ChargingSession, SessionEnd, SessionSnapshot};
+use crate::ledger::{SessionFilter, SessionLedger, SessionRecord, Settlement};
+use crate::payment::{self, Authorization, PaymentError, PaymentProvider, PaymentRequest, SharedPaymentProvider};
+use crate::auth::{self, AuthorizationStatus, Authorizer, AuthorizeRequest, CardRead, IdToken};
+use crate::auth::local_list::{LocalAuthorization, SharedLocalAuth};
+use crate::membership::{Member, MemberStore, SharedMembers};
+use crate::auth::wedge::KeyboardWedge;
+use crate::i18n;
+use crate::config::KioskConfig;
+use crate::operator::{self, Availability, SharedAvailability};
+
+mod screens;
+mod transitions;
+
+use screens::Entry;
+use transitions::Conditions;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppState {
     Splash,
-    Connect(ChargeType),
+    Connect(ChargeType, f32),
     Standby,
-    FullCharge,
     SelectAmount(ChargeType),
     Payment(ChargeType, f32),
-    Charging(ChargeType, f32, PaymentMethod),
-    Complete(ChargeType, f32, PaymentMethod, f32, Duration),
+    PaymentFailed(ChargeType, f32, PaymentMethod, PaymentError),
+    Charging(ChargeType, f32, PaymentMethod, Authorization),
+    Complete(ChargeType, f32, PaymentMethod, Bill, Duration, Authorization),
+    Member(String),
+    Operator,
 }
 
 // Navigation stack for maintaining screen history
@@ -24,316 +48,1001 @@ pub type NavigationStack = Vec<AppState>;
 
 pub struct Router {
     pub state: AppState,
+    pub config: KioskConfig,
+    pub charger: SharedCharger,
+    pub tariff: Arc<Tariff>,
+    pub ledger: SessionLedger,
+    pub payment: SharedPaymentProvider,
+    pub members: SharedMembers,
+    // Connectors an operator took out of service
+    pub availability: SharedAvailability,
+    // The hold the current session is charging against, until it is captured or released
+    active_authorization: Option<Authorization>,
+    // Runs on its own thread from payment approval until it is billed
+    session: Option<ChargingSession>,
+    // Ledger entry of the session on the Complete screen, written when charging
+    // ended and settled once the cable is unplugged
+    open_record: Option<SessionRecord>,
+    card_reader: KeyboardWedge,
+    pub authorizer: Box<dyn Authorizer>,
+    // Cards read but not yet answered by the central system
+    card_checks: Vec<AuthorizeRequest>,
+    // Card the current customer identified with, used as the OCPP id tag
+    id_token: Option<IdToken>,
+    pub backend: Option<OcppHandle>,
+    reported_status: Option<ConnectorStatus>,
     pub navigation_stack: NavigationStack,  // Screen history stack
     pub splash_screen: SplashScreen,
-    pub connect_screen: ConnectScreen,
+    pub connect_screen: Option<ConnectScreen>,
     pub standby_screen: StandbyScreen,
-    pub full_charge_screen: FullChargeScreen,
     pub select_amount_screen: Option<SelectAmountScreen>,
     pub payment_screen: Option<PaymentScreen>,
+    pub payment_failed_screen: Option<PaymentFailedScreen>,
     pub charging_screen: Option<ChargingScreen>,
     pub complete_screen: Option<CompleteScreen>,
+    pub member_screen: Option<MemberScreen>,
+    pub operator_screen: OperatorScreen,
 }
 
 impl Router {
-    pub fn new() -> Self {
+    pub fn new(config: KioskConfig) -> Self {
         // Background image path setup (optional)
-        let splash_bg_path = std::path::PathBuf::from("assets/images/splash_bg.jpg");
-        let connect_bg_path = std::path::PathBuf::from("assets/images/connect_bg.jpg");
-        let standby_bg_path = std::path::PathBuf::from("assets/images/standby_bg.jpg");
-        let full_charge_bg_path = std::path::PathBuf::from("assets/images/full_charge_bg.jpg");
+        let splash_bg_path = config.assets_dir.join("images/splash_bg.jpg");
+        let standby_bg_path = config.assets_dir.join("images/standby_bg.jpg");
+        let operator_bg_path = config.assets_dir.join("images/operator_bg.jpg");
+        let charger = hardware::create_charger(&config);
+        let tariff = Arc::new(config.tariff());
+        i18n::init(config.locale());
+        let ledger = SessionLedger::new(&config.data.sessions);
+        Self::log_daily_summary(&ledger, &tariff);
+        let members: SharedMembers =
+            Arc::new(std::sync::Mutex::new(MemberStore::load(&config.data.members, &config.data.members_seed)));
+        let local_auth: SharedLocalAuth = Arc::new(std::sync::Mutex::new(LocalAuthorization::load(&config.data.local_auth)));
+        let availability: SharedAvailability = Arc::new(std::sync::Mutex::new(Availability::load(&config.data.availability)));
+        let backend = OcppConfig::from_config(&config).map(|config| ocpp::spawn(config, local_auth.clone()));
+        let authorizer = auth::create_authorizer(local_auth, members.clone(), backend.clone());
+        let operator_screen = OperatorScreen::new(config.clone(), charger.clone(), tariff.clone(), availability.clone());
         
-        Self {
+        let mut router = Self {
             state: AppState::Splash,
             navigation_stack: vec![AppState::Splash],  // Initialize with splash screen
             splash_screen: if splash_bg_path.exists() {
-                SplashScreen::new().with_background_image(splash_bg_path)
-            } else {
-                SplashScreen::new()
-            },
-            connect_screen: if connect_bg_path.exists() {
-                ConnectScreen::new().with_background_image(connect_bg_path)
+                SplashScreen::new().with_duration(config.timeouts.splash()).with_background_image(splash_bg_path)
             } else {
-                ConnectScreen::new()
+                SplashScreen::new().with_duration(config.timeouts.splash())
             },
+            connect_screen: None,
             standby_screen: if standby_bg_path.exists() {
                 StandbyScreen::new().with_background_image(standby_bg_path)
             } else {
                 StandbyScreen::new()
             },
-            full_charge_screen: if full_charge_bg_path.exists() {
-                FullChargeScreen::new().with_background_image(full_charge_bg_path)
-            } else {
-                FullChargeScreen::new()
-            },
             select_amount_screen: None,
             payment_screen: None,
+            payment_failed_screen: None,
             charging_screen: None,
             complete_screen: None,
+            member_screen: None,
+            operator_screen: if operator_bg_path.exists() {
+                operator_screen.with_background_image(operator_bg_path)
+            } else {
+                operator_screen
+            },
+            charger,
+            tariff,
+            ledger,
+            payment: payment::create_payment_provider(
+                members.clone(),
+                config.payment.mock_outcome(),
+                config.timeouts.payment(),
+            ),
+            members,
+            availability,
+            active_authorization: None,
+            session: None,
+            open_record: None,
+            card_reader: KeyboardWedge::new(),
+            authorizer,
+            card_checks: Vec::new(),
+            id_token: None,
+            backend,
+            reported_status: None,
+            config,
+        };
+        router.settle_unfinished();
+        router
+    }
+
+    /// Settle sessions left unpaid by a crash or restart while on the Complete
+    /// screen, for the amount known when charging ended
+    fn settle_unfinished(&mut self) {
+        let pending = self.ledger.pending().unwrap_or_else(|err| {
+            println!("Cannot read session ledger: {}", err);
+            Vec::new()
+        });
+        for record in pending {
+            let Settlement::Pending { ref authorization } = record.settlement else {
+                continue;
+            };
+            println!("Settling session {} left open before the restart", record.id);
+            match (&record.payment_method, &record.member_id) {
+                // Balance holds lived only in memory, so debit the member directly
+                (PaymentMethod::Membership, Some(member_id)) => {
+                    let amount = record.total.min(authorization.amount);
+                    self.settle_member(record.clone(), member_id, amount);
+                }
+                _ => self.settle_payment(authorization.clone(), record.total, Some(record.clone())),
+            }
+        }
+    }
+
+    fn settle_member(&mut self, mut record: SessionRecord, member_id: &str, amount: f64) {
+        match self.members.lock().unwrap().charge(member_id, amount) {
+            Ok(balance) => println!("Member {}: charged {:.0}, balance {:.0}", member_id, amount, balance),
+            Err(err) => {
+                println!("Failed to charge member {} for session {}: {}", member_id, record.id, err);
+                return;
+            }
+        }
+        record.settlement = Settlement::Settled;
+        if let Err(err) = self.ledger.append(&record) {
+            println!("Failed to record settlement of session {}: {}", record.id, err);
         }
     }
 
     // Navigation stack management methods
     pub fn push_screen(&mut self, new_state: AppState) {
         // Don't push if it's the same as current state
-        if self.state != new_state {
-            self.navigation_stack.push(self.state.clone());
-            self.state = new_state;
+        if self.state == new_state || !self.allows(&new_state) {
+            return;
         }
+        self.navigation_stack.push(self.state.clone());
+        self.enter(new_state, Entry::Forward);
     }
 
     pub fn pop_screen(&mut self) -> Option<AppState> {
-        if self.navigation_stack.len() > 1 {
-            let previous_state = self.navigation_stack.pop();
-            if let Some(prev_state) = previous_state {
-                self.state = prev_state;
-                return Some(self.state.clone());
+        while self.navigation_stack.len() > 1 {
+            let prev_state = self.navigation_stack.last()?.clone();
+            // A charging session whose payment is settled can't be resumed
+            if let AppState::Charging(_, _, _, ref authorization) = prev_state {
+                if self.active_authorization.as_ref() != Some(authorization) {
+                    self.navigation_stack.pop();
+                    continue;
+                }
             }
+            if !self.allows(&prev_state) {
+                return None;
+            }
+            self.navigation_stack.pop();
+            self.enter(prev_state, Entry::Back);
+            return Some(self.state.clone());
         }
         None
     }
 
-    pub fn can_go_back(&self) -> bool {
-        self.navigation_stack.len() > 1
+    /// Show `state` with a live screen, and drop the screens that left the history
+    fn enter(&mut self, state: AppState, entry: Entry) {
+        self.build_screen(&state, entry);
+        self.state = state;
+        self.tear_down_screens();
     }
 
-    pub fn get_navigation_stack(&self) -> &NavigationStack {
-        &self.navigation_stack
+    /// Check `to` against the transition table, logging why it is refused
+    fn allows(&self, to: &AppState) -> bool {
+        let conditions = Conditions {
+            energy_flowing: self.charger.lock().unwrap().is_energy_flowing(),
+            authorization: self.active_authorization.as_ref(),
+        };
+        match transitions::check(&self.state, to, &conditions) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("Refusing transition: {}", err);
+                false
+            }
+        }
     }
 
     pub fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let current_state = self.state.clone();
-        match current_state {
-            AppState::Splash => {
-                self.splash_screen.show(ctx);
-                
-                // Transition to connect screen when splash finishes
-                if self.splash_screen.is_finished() {
-                    self.push_screen(AppState::Connect(ChargeType::SpecificWatts(0.0)));
-                }
-            }
-            AppState::Connect(_charge_type) => {
-                self.connect_screen.show(ctx);
-                
-                // Transition to standby screen when connection is complete
-                if self.connect_screen.is_connection_complete() {
-                    self.push_screen(AppState::Standby);
-                }
+        self.read_cards(ctx);
+        self.render_state(ctx);
+        self.report_to_backend(&current_state);
+    }
+
+    fn render_state(&mut self, ctx: &egui::Context) {
+        if self.state == AppState::Standby {
+            let connector_id = self.config.connector().id;
+            self.standby_screen.set_available(self.availability.lock().unwrap().is_available(connector_id));
+        }
+
+        let action = self.current_screen().and_then(|screen| screen.show(ctx));
+        if let Some(action) = action {
+            self.apply_action(ctx, action);
+        }
+    }
+
+    /// The screen that draws the current state, if it has been created
+    fn current_screen(&mut self) -> Option<&mut dyn Screen> {
+        match self.state {
+            AppState::Splash => Some(&mut self.splash_screen),
+            AppState::Connect(_, _) => self.connect_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::Standby => Some(&mut self.standby_screen),
+            AppState::SelectAmount(_) => self.select_amount_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::Payment(_, _) => self.payment_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::PaymentFailed(_, _, _, _) => self.payment_failed_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::Charging(_, _, _, _) => self.charging_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::Complete(_, _, _, _, _, _) => self.complete_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::Member(_) => self.member_screen.as_mut().map(|screen| screen as &mut dyn Screen),
+            AppState::Operator => Some(&mut self.operator_screen),
+        }
+    }
+
+    /// Carry out what the current screen asked for
+    fn apply_action(&mut self, ctx: &egui::Context, action: ScreenAction) {
+        match (action, self.state.clone()) {
+            (ScreenAction::Navigate(state), _) => self.push_screen(state),
+            (ScreenAction::Back, _) => {
+                self.pop_screen();
             }
-            AppState::Standby => {
-                self.standby_screen.show(ctx);
-                
-                // Transition to full charge screen when full charge button is clicked
-                if self.standby_screen.is_full_charge_clicked() {
-                    self.standby_screen.reset_full_charge_clicked();
-                    self.push_screen(AppState::FullCharge);
-                }
-                 
-                // Transition to connect screen when specific watts charge button is clicked
-                if self.standby_screen.is_specific_watts_clicked() {
-                    self.standby_screen.reset_specific_watts_clicked();
-                    self.push_screen(AppState::Connect(ChargeType::SpecificWatts(0.0)));
-                }
-                 
-                // Transition to connect screen when percent charge button is clicked
-                if self.standby_screen.is_percent_clicked() {
-                    self.standby_screen.reset_percent_clicked();
-                    self.push_screen(AppState::Connect(ChargeType::Percent(0.0)));
+            (ScreenAction::Home, _) => self.return_home(),
+            (ScreenAction::NextLanguage, _) => i18n::set_locale(i18n::current().next()),
+            (ScreenAction::OpenOperator, _) => self.push_screen(AppState::Operator),
+            (ScreenAction::Restart, _) => match operator::restart() {
+                Ok(()) => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+                Err(err) => println!("Failed to restart: {}", err),
+            },
+            // Charging only starts against an approved pre-authorization, and only
+            // once the Charging screen is sure to show it
+            (ScreenAction::PaymentApproved(authorization), AppState::Payment(charge_type, amount)) => {
+                let charging = AppState::Charging(charge_type.clone(), amount, authorization.method.clone(), authorization.clone());
+                self.active_authorization = Some(authorization.clone());
+                if !self.allows(&charging) {
+                    self.settle_payment(authorization, 0.0, None);
+                    return;
                 }
+                self.session = Some(ChargingSession::start(
+                    self.charger.clone(),
+                    self.tariff.clone(),
+                    charge_type,
+                    amount,
+                    Some(authorization.amount),
+                ));
+                self.push_screen(charging);
             }
-            AppState::FullCharge => {
-                self.full_charge_screen.show(ctx);
-                
-                // Return to previous screen when back button is clicked
-                if self.full_charge_screen.is_back_clicked() {
-                    self.full_charge_screen.reset_back_clicked();
-                    self.pop_screen();
-                }
-                
-                // Return to standby screen when charging is complete
-                if self.full_charge_screen.is_charging_complete() {
-                    self.state = AppState::Standby;
-                }
+            (ScreenAction::PaymentFailed(payment_method, error), AppState::Payment(charge_type, amount)) => {
+                self.push_screen(AppState::PaymentFailed(charge_type, amount, payment_method, error));
             }
-            AppState::SelectAmount(charge_type) => {
-                let mut should_proceed = false;
-                let mut should_go_back = false;
-                let mut proceed_amount = 0.0;
-                
-                if let Some(ref mut screen) = self.select_amount_screen {
-                    screen.show(ctx);
-                    
-                    if screen.is_proceed_clicked() {
-                        screen.reset_proceed_clicked();
-                        proceed_amount = screen.get_input_value();
-                        should_proceed = true;
-                    }
-                    
-                    if screen.is_back_clicked() {
-                        screen.reset_back_clicked();
-                        should_go_back = true;
-                    }
-                }
-                
-                if should_proceed {
-                    self.push_screen(AppState::Payment(charge_type.clone(), proceed_amount));
-                }
-                if should_go_back {
-                    self.pop_screen();
+            // Both return to the payment screen; retry starts the same method again
+            (ScreenAction::RetryPayment, AppState::PaymentFailed(_, _, payment_method, _)) => {
+                self.pop_screen();
+                if let Some(ref mut screen) = self.payment_screen {
+                    screen.retry(payment_method);
                 }
             }
-            AppState::Payment(charge_type, amount) => {
-                let mut should_proceed = false;
-                let mut should_go_back = false;
-                let mut proceed_payment_method = None;
-                
+            (ScreenAction::ChooseAnotherMethod, AppState::PaymentFailed(_, _, _, _)) => {
+                self.pop_screen();
                 if let Some(ref mut screen) = self.payment_screen {
-                    screen.show(ctx);
-                    
-                    if screen.is_proceed_clicked() {
-                        screen.reset_proceed_clicked();
-                        proceed_payment_method = screen.get_selected_payment();
-                        should_proceed = true;
-                    }
-                    
-                    if screen.is_back_clicked() {
-                        screen.reset_back_clicked();
-                        should_go_back = true;
-                    }
+                    screen.choose_another_method();
                 }
-                
-                if should_proceed {
-                    if let Some(payment_method) = proceed_payment_method {
-                        self.push_screen(AppState::Charging(charge_type.clone(), amount, payment_method));
-                    }
-                }
-                if should_go_back {
-                    self.pop_screen();
+            }
+            (ScreenAction::ChargingComplete, AppState::Charging(charge_type, amount, payment_method, authorization)) => {
+                let Some(snapshot) = self.session.as_ref().map(ChargingSession::snapshot) else {
+                    println!("No charging session to complete");
+                    return;
+                };
+                self.finish_session(snapshot, charge_type, amount, payment_method, authorization);
+            }
+            // Stopped early: bill and record what was delivered exactly like a finished session
+            (ScreenAction::ChargingStopped, AppState::Charging(charge_type, amount, payment_method, authorization)) => {
+                let Some(snapshot) = self.session.as_mut().map(ChargingSession::stop) else {
+                    println!("No charging session to stop");
+                    return;
+                };
+                self.finish_session(snapshot, charge_type, amount, payment_method, authorization);
+            }
+            // The idle fee is known once the cable is out: amend the record, then capture
+            (ScreenAction::SessionEnded(bill), AppState::Complete(_, _, _, _, _, authorization)) => {
+                // Only the hold can be captured; the rest stays on record as uncollected
+                let bill = bill.capped_at(authorization.amount);
+                let Some(mut record) = self.open_record.take() else {
+                    println!("No session record to settle");
+                    self.settle_payment(authorization, bill.total, None);
+                    return;
+                };
+                record.update_bill(&bill);
+                if let Err(err) = self.ledger.append(&record) {
+                    println!("Failed to record session {}: {}", record.id, err);
                 }
+                self.settle_payment(authorization, bill.total, Some(record));
+            }
+            (action, state) => println!("Ignoring {:?} on {:?}", action, state),
+        }
+    }
+
+    /// Session over: Standby with an empty history, ready for the next customer
+    fn return_home(&mut self) {
+        if !self.allows(&AppState::Standby) {
+            return;
+        }
+        self.id_token = None;
+        i18n::reset_locale();
+        self.navigation_stack.clear();
+        self.navigation_stack.push(AppState::Standby);
+        self.enter(AppState::Standby, Entry::Forward);
+    }
+
+    /// Capture `amount` from the hold, or release it if nothing is owed. Runs in
+    /// the background; once it succeeds, `record` is marked settled in the ledger.
+    /// Record a session that has ended, show its receipt and leave the hold pending until the cable is out
+    fn finish_session(&mut self, snapshot: SessionSnapshot, charge_type: ChargeType, amount: f32, payment_method: PaymentMethod, authorization: Authorization) {
+        // Nothing was delivered: release the hold instead of billing a session fee or minimum
+        if snapshot.energy_kwh <= 0.0 || matches!(snapshot.ended, Some(SessionEnd::Failed(_))) {
+            self.session = None;
+            self.settle_payment(authorization, 0.0, None);
+            self.pop_screen();
+            return;
+        }
+        let bill = self.member_bill(&authorization, self.tariff.bill(&snapshot.usage, snapshot.elapsed));
+        // Recorded now so the session and its hold survive a restart before the cable is unplugged
+        let mut record = SessionRecord::new(
+            charge_type.clone(),
+            amount,
+            payment_method.clone(),
+            &bill.capped_at(authorization.amount),
+            snapshot.elapsed,
+            snapshot.ended_at.unwrap_or_else(chrono::Utc::now),
+            &self.tariff.currency,
+        );
+        record.connector_id = self.config.connector().id;
+        record.member_id = self.member_for(&authorization).map(|member| member.id);
+        record.settlement = Settlement::Pending { authorization: authorization.clone() };
+        self.open_record = Some(record.clone());
+        self.push_screen(AppState::Complete(charge_type, amount, payment_method, bill, snapshot.elapsed, authorization));
+        if matches!(self.state, AppState::Complete(..)) {
+            self.session = None;
+            if let Err(err) = self.ledger.append(&record) {
+                println!("Failed to record session {}: {}", record.id, err);
             }
-            AppState::Charging(charge_type, amount, payment_method) => {
-                let mut should_complete = false;
-                let mut should_go_back = false;
-                
-                if let Some(ref mut screen) = self.charging_screen {
-                    screen.show(ctx);
-                    
-                    if screen.is_charging_complete() {
-                        should_complete = true;
-                    }
-                    
-                    if screen.is_back_clicked() {
-                        screen.reset_back_clicked();
-                        should_go_back = true;
-                    }
+        } else {
+            self.open_record = None;
+        }
+    }
+
+    fn settle_payment(&mut self, authorization: Authorization, amount: f64, record: Option<SessionRecord>) {
+        if self.active_authorization.as_ref() == Some(&authorization) {
+            self.active_authorization = None;
+        }
+        let tariff = self.tariff.clone();
+        let ledger = self.ledger.clone();
+        PaymentRequest::spawn(self.payment.clone(), move |provider| {
+            let result = Self::settle(provider, &tariff, &authorization, amount);
+            if let (Ok(()), Some(mut record)) = (&result, record) {
+                record.settlement = Settlement::Settled;
+                if let Err(err) = ledger.append(&record) {
+                    println!("Failed to record settlement of session {}: {}", record.id, err);
                 }
-                
-                if should_complete {
-                    // Transition to complete screen when charging is finished
-                    let total_cost = match charge_type {
-                        ChargeType::SpecificWatts(watts) => watts * 0.2,
-                        ChargeType::Percent(percent) => percent * 0.1,
-                    };
-                    let charging_duration = Duration::from_secs(300); // 5 minute simulation
-                    self.push_screen(AppState::Complete(
-                        charge_type.clone(),
-                        amount,
-                        payment_method.clone(),
-                        total_cost,
-                        charging_duration,
-                    ));
+            }
+            result
+        });
+    }
+
+    fn settle(provider: &mut dyn PaymentProvider, tariff: &Tariff, authorization: &Authorization, amount: f64) -> Result<(), PaymentError> {
+        if amount <= 0.0 {
+            let result = provider.void(authorization);
+            match result {
+                Ok(()) => println!("Released payment hold {}", authorization.id),
+                Err(ref err) => println!("Failed to release payment hold {}: {}", authorization.id, err),
+            }
+            return result;
+        }
+        let captured = amount.min(authorization.amount);
+        if captured < amount {
+            println!(
+                "Session cost {} exceeds the hold {}; {} not collected",
+                tariff.format_amount(amount),
+                authorization.id,
+                tariff.format_amount(amount - captured)
+            );
+        }
+        match provider.capture(authorization, captured) {
+            Ok(capture) => {
+                println!("Captured {} ({})", tariff.format_amount(capture.amount), capture.id);
+                // Capturing less than the hold gives the rest back to the customer
+                if captured < authorization.amount {
+                    println!(
+                        "Released unused {} of hold {}",
+                        tariff.format_amount(authorization.amount - captured),
+                        authorization.id
+                    );
                 }
-                if should_go_back {
-                    self.pop_screen();
+                Ok(())
+            }
+            Err(err) => {
+                println!("Failed to capture payment {}: {}", authorization.id, err);
+                Err(err)
+            }
+        }
+    }
+
+    // Backend (OCPP) reporting driven by state changes
+    fn report_to_backend(&mut self, previous_state: &AppState) {
+        let Some(ref backend) = self.backend else {
+            return;
+        };
+        let mut charger = self.charger.lock().unwrap();
+
+        let available = self.availability.lock().unwrap().is_available(self.config.connector().id);
+
+        let status = if charger.fault().is_some() {
+            Some(ConnectorStatus::Faulted)
+        } else {
+            match self.state {
+                AppState::Splash => None,
+                AppState::Standby | AppState::Member(_) | AppState::Operator if !available => Some(ConnectorStatus::Unavailable),
+                AppState::Standby | AppState::Member(_) | AppState::Operator => Some(ConnectorStatus::Available),
+                AppState::Connect(_, _)
+                | AppState::SelectAmount(_)
+                | AppState::Payment(_, _)
+                | AppState::PaymentFailed(_, _, _, _) => Some(ConnectorStatus::Preparing),
+                AppState::Charging(_, _, _, _) => Some(ConnectorStatus::Charging),
+                AppState::Complete(_, _, _, _, _, _) => Some(ConnectorStatus::Finishing),
+            }
+        };
+        if let Some(status) = status {
+            if self.reported_status != Some(status) {
+                self.reported_status = Some(status);
+                backend.send(ChargerEvent::ConnectorStatus(status));
+            }
+        }
+
+        let was_charging = Self::is_session_state(previous_state);
+        let is_charging = Self::is_session_state(&self.state);
+        let meter = charger.read_meter();
+        let timestamp = chrono::Utc::now();
+
+        if is_charging && !was_charging {
+            let id_tag = match self.state {
+                AppState::Charging(_, _, ref payment_method, _) => self.id_tag_for(payment_method),
+                _ => "KIOSK".to_string(),
+            };
+            backend.send(ChargerEvent::Authorize { id_tag: id_tag.clone() });
+            backend.send(ChargerEvent::SessionStarted { id_tag, meter_start_wh: meter.energy_wh, timestamp });
+        } else if was_charging && !is_charging {
+            let reason = if charger.is_emergency_stop_pressed() {
+                StopReason::EmergencyStop
+            } else if charger.pilot_state() == PilotState::A {
+                StopReason::EvDisconnected
+            } else {
+                StopReason::Local
+            };
+            backend.send(ChargerEvent::SessionStopped { meter_stop_wh: meter.energy_wh, reason, timestamp });
+        } else if is_charging {
+            backend.send(ChargerEvent::MeterSample {
+                energy_wh: meter.energy_wh,
+                power_w: meter.power_w,
+                soc: meter.vehicle_soc,
+                timestamp,
+            });
+        }
+    }
+
+    // RFID reader input: cards are only taken on the standby and payment screens,
+    // everywhere else (e.g. the amount field) they are dropped rather than typed
+    fn read_cards(&mut self, ctx: &egui::Context) {
+        for token in self.card_reader.capture(ctx) {
+            if !matches!(self.state, AppState::Standby | AppState::Payment(_, _)) {
+                println!("Ignoring card {} on this screen", token);
+                continue;
+            }
+            self.card_checks.push(self.authorizer.authorize(&token));
+        }
+
+        // Cards still waiting for the central system are polled again next frame
+        let mut checks = std::mem::take(&mut self.card_checks);
+        checks.retain_mut(|request| match request.poll() {
+            Some(status) => {
+                self.card_read(request.token.clone(), status);
+                false
+            }
+            None => true,
+        });
+        self.card_checks.append(&mut checks);
+        if !self.card_checks.is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(50));
+        }
+    }
+
+    fn card_read(&mut self, token: IdToken, status: AuthorizationStatus) {
+        println!("Card {}: {:?}", token, status);
+        if !matches!(self.state, AppState::Standby | AppState::Payment(_, _)) {
+            println!("Card {} answered after leaving the screen", token);
+            return;
+        }
+        if status == AuthorizationStatus::Accepted {
+            self.id_token = Some(token.clone());
+        }
+        let read = CardRead::new(token, status);
+        match self.state {
+            AppState::Standby => {
+                let member = read.is_accepted().then(|| self.members.lock().unwrap().find_by_token(&read.token).cloned());
+                self.standby_screen.card_presented(read);
+                if let Some(member) = member.flatten() {
+                    self.push_screen(AppState::Member(member.id));
                 }
             }
-            AppState::Complete(_, _, _, _, _) => {
-                if let Some(ref mut screen) = self.complete_screen {
-                    screen.show(ctx);
-                    
-                    if screen.is_return_home_clicked() {
-                        screen.reset_return_home_clicked();
-                        // Clear the navigation stack and go to standby
-                        self.navigation_stack.clear();
-                        self.navigation_stack.push(AppState::Standby);
-                        self.state = AppState::Standby;
-                    }
+            _ => {
+                if let Some(ref mut screen) = self.payment_screen {
+                    screen.card_presented(read);
                 }
             }
         }
     }
 
-    // Helper methods for screen transitions
-    pub fn go_to_standby(&mut self) {
-        self.state = AppState::Standby;
+    /// Member whose card paid for or identified the session
+    fn member_for(&self, authorization: &Authorization) -> Option<Member> {
+        let token = authorization.id_token.as_ref()?;
+        self.members.lock().unwrap().find_by_token(token).cloned()
     }
 
-    pub fn go_to_full_charge(&mut self) {
-        self.state = AppState::FullCharge;
+    /// `bill` with the member's plan discount, if the customer is a member
+    fn member_bill(&self, authorization: &Authorization, bill: Bill) -> Bill {
+        match self.member_for(authorization) {
+            Some(member) => member.plan.apply(&bill, self.tariff.minimum_charge),
+            None => bill,
+        }
     }
 
-    pub fn go_to_splash(&mut self) {
-        self.state = AppState::Splash;
+    fn log_daily_summary(ledger: &SessionLedger, tariff: &Tariff) {
+        let midnight = chrono::Local::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest());
+        let filter = SessionFilter { from: midnight.map(|time| time.with_timezone(&chrono::Utc)), ..Default::default() };
+        match ledger.summary(&filter) {
+            Ok(summary) => println!(
+                "Sessions today: {} ({:.1} kWh, {})",
+                summary.sessions,
+                summary.energy_kwh,
+                tariff.format_amount(summary.revenue)
+            ),
+            Err(err) => println!("Cannot read session ledger: {}", err),
+        }
     }
 
-    pub fn go_to_select_amount(&mut self, charge_type: ChargeType) {
-        let bg_path = std::path::PathBuf::from("assets/images/select_amount_bg.jpg");
-        self.select_amount_screen = Some(
-            if bg_path.exists() {
-                SelectAmountScreen::new(charge_type.clone()).with_background_image(bg_path)
-            } else {
-                SelectAmountScreen::new(charge_type.clone())
-            }
-        );
-        self.state = AppState::SelectAmount(charge_type);
+    fn is_session_state(state: &AppState) -> bool {
+        matches!(state, AppState::Charging(_, _, _, _))
     }
 
-    pub fn go_to_payment(&mut self, charge_type: ChargeType, amount: f32) {
-        let bg_path = std::path::PathBuf::from("assets/images/payment_bg.jpg");
-        self.payment_screen = Some(
-            if bg_path.exists() {
-                PaymentScreen::new(charge_type.clone(), amount).with_background_image(bg_path)
-            } else {
-                PaymentScreen::new(charge_type.clone(), amount)
+    fn id_tag_for(&self, payment_method: &PaymentMethod) -> String {
+        match payment_method {
+            PaymentMethod::CreditCard => "CREDIT-CARD",
+            PaymentMethod::MobileApp => "MOBILE-APP",
+            PaymentMethod::RFID => self.id_token.as_ref().map_or("RFID", IdToken::as_str),
+            PaymentMethod::Membership => "MEMBERSHIP",
+        }
+        .to_string()
+    }
+}
+
+// Drive the simulated charger, so they only build without the `hardware` feature
+#[cfg(all(test, not(feature = "hardware")))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::config::DataConfig;
+    use crate::payment::{Capture, PaymentProvider};
+    use crate::session::SessionSnapshot;
+    use crate::membership::Plan;
+    use crate::tariff::Usage;
+    use chrono::Utc;
+
+    /// Payment terminal that approves everything and notes each call
+    struct Recorder {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl PaymentProvider for Recorder {
+        fn pre_authorize(
+            &mut self,
+            method: &PaymentMethod,
+            id_token: Option<&IdToken>,
+            amount: f64,
+        ) -> Result<Authorization, PaymentError> {
+            self.calls.lock().unwrap().push(format!("hold {}", amount));
+            Ok(Authorization { id: "AUTH-1".to_string(), method: method.clone(), amount, id_token: id_token.cloned() })
+        }
+
+        fn capture(&mut self, authorization: &Authorization, amount: f64) -> Result<Capture, PaymentError> {
+            self.calls.lock().unwrap().push(format!("capture {}", amount));
+            Ok(Capture {
+                id: "CAP-1".to_string(),
+                authorization_id: authorization.id.clone(),
+                method: authorization.method.clone(),
+                amount,
+            })
+        }
+
+        fn void(&mut self, _authorization: &Authorization) -> Result<(), PaymentError> {
+            self.calls.lock().unwrap().push("void".to_string());
+            Ok(())
+        }
+
+        fn refund(&mut self, _capture: &Capture, _amount: f64) -> Result<(), PaymentError> {
+            Ok(())
+        }
+    }
+
+    /// Router on the simulator with its records in a scratch directory and a recording terminal
+    fn router(config: KioskConfig) -> (Router, Arc<Mutex<Vec<String>>>) {
+        let dir = std::env::temp_dir().join(format!("ev-charger-router-{}", uuid::Uuid::new_v4()));
+        let config = KioskConfig {
+            data: DataConfig {
+                sessions: dir.join("sessions.jsonl"),
+                members: dir.join("members.json"),
+                local_auth: dir.join("local_auth.json"),
+                availability: dir.join("availability.json"),
+                ..config.data
+            },
+            ..config
+        };
+        let mut router = Router::new(config);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        router.payment = Arc::new(Mutex::new(Box::new(Recorder { calls: calls.clone() })));
+        (router, calls)
+    }
+
+    /// Payment calls once the background settlement has captured or released the hold
+    fn settled(calls: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+        for _ in 0..200 {
+            if calls.lock().unwrap().iter().any(|call| call.starts_with("capture") || call == "void") {
+                break;
             }
-        );
-        self.state = AppState::Payment(charge_type, amount);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        calls.lock().unwrap().clone()
     }
 
-    pub fn go_to_charging(&mut self, charge_type: ChargeType, amount: f32, payment_method: PaymentMethod) {
-        let bg_path = std::path::PathBuf::from("assets/images/charging_bg.jpg");
-        self.charging_screen = Some(
-            if bg_path.exists() {
-                ChargingScreen::new(charge_type.clone(), amount, payment_method.clone()).with_background_image(bg_path)
-            } else {
-                ChargingScreen::new(charge_type.clone(), amount, payment_method.clone())
+    fn authorization(amount: f64) -> Authorization {
+        Authorization { id: "AUTH-1".to_string(), method: PaymentMethod::CreditCard, amount, id_token: None }
+    }
+
+    /// Take the router from Splash to Charging for `charge_type`, approved for `hold`
+    fn charge(router: &mut Router, ctx: &egui::Context, charge_type: ChargeType, amount: f32, hold: f64) {
+        let mut steps = vec![ScreenAction::Navigate(AppState::Standby)];
+        if charge_type != ChargeType::Full {
+            steps.push(ScreenAction::Navigate(AppState::SelectAmount(charge_type.clone())));
+        }
+        steps.push(ScreenAction::Navigate(AppState::Connect(charge_type.clone(), amount)));
+        steps.push(ScreenAction::Navigate(AppState::Payment(charge_type.clone(), amount)));
+        steps.push(ScreenAction::PaymentApproved(authorization(hold)));
+        for step in steps {
+            router.apply_action(ctx, step);
+        }
+        assert_eq!(router.state, AppState::Charging(charge_type, amount, PaymentMethod::CreditCard, authorization(hold)));
+    }
+
+    /// Draw frames, as the app would, until `done` holds or ten seconds pass
+    fn run_until(router: &mut Router, ctx: &egui::Context, done: impl Fn(&Router) -> bool) {
+        for _ in 0..1000 {
+            if done(router) {
+                return;
             }
-        );
-        self.state = AppState::Charging(charge_type, amount, payment_method);
+            let _ = ctx.run(egui::RawInput::default(), |ctx| router.render_state(ctx));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(done(router), "stuck in {:?}", router.state);
     }
 
-    pub fn go_to_complete(&mut self, charge_type: ChargeType, amount: f32, payment_method: PaymentMethod, total_cost: f32, duration: Duration) {
-        let bg_path = std::path::PathBuf::from("assets/images/complete_bg.jpg");
-        self.complete_screen = Some(
-            if bg_path.exists() {
-                CompleteScreen::new(charge_type.clone(), amount, payment_method.clone(), total_cost, duration).with_background_image(bg_path)
-            } else {
-                CompleteScreen::new(charge_type.clone(), amount, payment_method.clone(), total_cost, duration)
+    /// Replace the running session with one that ended as `snapshot` says
+    fn end_session(router: &mut Router, snapshot: SessionSnapshot) {
+        router.session = Some(ChargingSession::finished(snapshot));
+    }
+
+    /// Ledger rows once the background settlement has marked them settled
+    fn settled_records(router: &Router) -> Vec<SessionRecord> {
+        for _ in 0..200 {
+            if router.ledger.pending().unwrap().is_empty() {
+                break;
             }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        router.ledger.records().unwrap()
+    }
+
+    #[test]
+    fn customer_goes_from_amount_through_connection_and_payment_to_charging() {
+        let ctx = egui::Context::default();
+        let (mut router, calls) = router(KioskConfig::default());
+        router.tariff = Arc::new(Tariff::default());
+        let watts = ChargeType::SpecificWatts(10.0);
+
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Standby));
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::SelectAmount(watts.clone())));
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Connect(watts.clone(), 10.0)));
+        // The connect screen moves on by itself once the simulated vehicle is plugged in
+        run_until(&mut router, &ctx, |router| router.state == AppState::Payment(watts.clone(), 10.0));
+
+        router.payment_screen.as_mut().unwrap().retry(PaymentMethod::CreditCard);
+        run_until(&mut router, &ctx, |router| matches!(router.state, AppState::Charging(..)));
+
+        // 10 kWh at 347.2 plus the 20% pre-authorization margin
+        assert_eq!(calls.lock().unwrap().clone(), vec!["hold 4167".to_string()]);
+        let AppState::Charging(charge_type, amount, method, authorization) = router.state.clone() else {
+            unreachable!();
+        };
+        assert_eq!((charge_type, amount, method), (watts, 10.0, PaymentMethod::CreditCard));
+        assert_eq!(router.active_authorization, Some(authorization));
+        assert!(router.session.is_some());
+    }
+
+    #[test]
+    fn screens_are_built_from_the_state_and_dropped_once_out_of_the_history() {
+        let ctx = egui::Context::default();
+        let (mut router, _) = router(KioskConfig::default());
+        let watts = ChargeType::SpecificWatts(10.0);
+
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Standby));
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::SelectAmount(watts.clone())));
+        assert_eq!(router.select_amount_screen.as_ref().map(SelectAmountScreen::get_charge_type), Some(watts.clone()));
+        assert!(router.connect_screen.is_none());
+
+        // Screens behind the current one stay alive for the way back
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Connect(watts.clone(), 10.0)));
+        assert!(router.connect_screen.is_some() && router.select_amount_screen.is_some());
+        router.apply_action(&ctx, ScreenAction::Back);
+        assert_eq!(router.state, AppState::SelectAmount(watts.clone()));
+        assert!(router.connect_screen.is_none());
+        assert!(router.select_amount_screen.is_some());
+
+        router.apply_action(&ctx, ScreenAction::Back);
+        assert!(router.select_amount_screen.is_none());
+
+        // A new payload starts a new screen
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::SelectAmount(ChargeType::Percent(80.0))));
+        assert_eq!(router.select_amount_screen.as_ref().map(SelectAmountScreen::get_charge_type), Some(ChargeType::Percent(80.0)));
+        router.apply_action(&ctx, ScreenAction::Back);
+
+        // The member screen is only built for an account that exists
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Member("M0001".to_string())));
+        assert!(router.member_screen.is_some());
+        router.apply_action(&ctx, ScreenAction::Back);
+        assert!(router.member_screen.is_none());
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Member("M9999".to_string())));
+        assert!(router.member_screen.is_none());
+    }
+
+    #[test]
+    fn connect_moves_on_to_payment_once_and_waits_when_the_customer_comes_back() {
+        let ctx = egui::Context::default();
+        let (mut router, _) = router(KioskConfig::default());
+        let watts = ChargeType::SpecificWatts(10.0);
+        let connect = AppState::Connect(watts.clone(), 10.0);
+
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Standby));
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::SelectAmount(watts.clone())));
+        router.apply_action(&ctx, ScreenAction::Navigate(connect.clone()));
+        run_until(&mut router, &ctx, |router| router.state == AppState::Payment(watts.clone(), 10.0));
+
+        // Back from payment the vehicle is still connected, but the customer continues by hand
+        router.apply_action(&ctx, ScreenAction::Back);
+        assert_eq!(router.state, connect);
+        for _ in 0..20 {
+            let _ = ctx.run(egui::RawInput::default(), |ctx| router.render_state(ctx));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(router.state, connect);
+
+        // Each step goes back to the one before it
+        router.apply_action(&ctx, ScreenAction::Back);
+        assert_eq!(router.state, AppState::SelectAmount(watts));
+        router.apply_action(&ctx, ScreenAction::Back);
+        assert_eq!(router.state, AppState::Standby);
+    }
+
+    #[test]
+    fn full_charge_is_held_for_the_configured_amount_and_stops_there() {
+        let ctx = egui::Context::default();
+        let mut config = KioskConfig::default();
+        config.payment.full_charge_hold = 12000.0;
+        let (mut router, calls) = router(config);
+        router.tariff = Arc::new(Tariff::default());
+
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Standby));
+        router.apply_action(&ctx, ScreenAction::Navigate(AppState::Connect(ChargeType::Full, 100.0)));
+        run_until(&mut router, &ctx, |router| router.state == AppState::Payment(ChargeType::Full, 100.0));
+        router.payment_screen.as_mut().unwrap().retry(PaymentMethod::CreditCard);
+        run_until(&mut router, &ctx, |router| matches!(router.state, AppState::Charging(..)));
+        assert_eq!(calls.lock().unwrap().clone(), vec!["hold 12000".to_string()]);
+
+        // The session stops once its cost reaches the hold; all of it is captured
+        let mut usage = Usage::default();
+        usage.record(&router.tariff, chrono::Local::now(), 34.562);
+        let snapshot = SessionSnapshot {
+            energy_kwh: 34.562,
+            usage,
+            ended: Some(SessionEnd::CostLimit),
+            ended_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        end_session(&mut router, snapshot);
+        router.apply_action(&ctx, ScreenAction::ChargingComplete);
+        let AppState::Complete(_, _, _, ref bill, _, _) = router.state else {
+            panic!("not complete: {:?}", router.state);
+        };
+        assert_eq!(bill.total, 12000.0);
+        router.apply_action(&ctx, ScreenAction::SessionEnded(bill.clone()));
+        assert_eq!(settled(&calls), vec!["hold 12000".to_string(), "capture 12000".to_string()]);
+    }
+
+    #[test]
+    fn finished_session_is_recorded_before_it_is_settled() {
+        let ctx = egui::Context::default();
+        let (mut router, calls) = router(KioskConfig::default());
+        router.tariff = Arc::new(Tariff { session_fee: 500.0, minimum_charge: 1000.0, ..Tariff::default() });
+        charge(&mut router, &ctx, ChargeType::SpecificWatts(30.0), 30.0, 20000.0);
+
+        let ended_at = Utc::now() - chrono::Duration::minutes(20);
+        let snapshot = SessionSnapshot {
+            energy_kwh: 30.0,
+            elapsed: Duration::from_secs(1800),
+            ended: Some(SessionEnd::TargetReached),
+            ended_at: Some(ended_at),
+            ..Default::default()
+        };
+        end_session(&mut router, snapshot);
+        router.apply_action(&ctx, ScreenAction::ChargingComplete);
+        assert!(matches!(router.state, AppState::Complete(..)));
+
+        // Until the cable is out the hold stays open, but the session is already on record
+        let pending = router.ledger.pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].ended_at, ended_at);
+        assert_eq!(pending[0].settlement, Settlement::Pending { authorization: authorization(20000.0) });
+        assert!(calls.lock().unwrap().is_empty());
+
+        let bill = Bill { idle_fee: 2000.0, total: 3000.0, ..router.tariff.bill(&Usage::default(), Duration::from_secs(1800)) };
+        router.apply_action(&ctx, ScreenAction::SessionEnded(bill));
+
+        assert_eq!(settled(&calls), vec!["capture 3000".to_string()]);
+        let records = settled_records(&router);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, pending[0].id);
+        assert_eq!((records[0].idle_fee, records[0].total), (2000.0, 3000.0));
+        assert_eq!(records[0].settlement, Settlement::Settled);
+    }
+
+    #[test]
+    fn idle_fee_above_the_hold_is_recorded_as_uncollected() {
+        let ctx = egui::Context::default();
+        let (mut router, calls) = router(KioskConfig::default());
+        router.tariff = Arc::new(Tariff { minimum_charge: 1000.0, ..Tariff::default() });
+        charge(&mut router, &ctx, ChargeType::SpecificWatts(10.0), 10.0, 5000.0);
+        end_session(
+            &mut router,
+            SessionSnapshot { energy_kwh: 10.0, ended: Some(SessionEnd::TargetReached), ..Default::default() },
         );
-        self.state = AppState::Complete(charge_type, amount, payment_method, total_cost, duration);
+        router.apply_action(&ctx, ScreenAction::ChargingComplete);
+
+        let bill = Bill { idle_fee: 6000.0, total: 7000.0, ..router.tariff.bill(&Usage::default(), Duration::ZERO) };
+        router.apply_action(&ctx, ScreenAction::SessionEnded(bill));
+
+        assert_eq!(settled(&calls), vec!["capture 5000".to_string()]);
+        let records = settled_records(&router);
+        assert_eq!((records[0].total, records[0].uncollected), (7000.0, 2000.0));
+    }
+
+    #[test]
+    fn session_stopped_early_is_recorded_and_shows_its_receipt() {
+        let ctx = egui::Context::default();
+        let (mut router, calls) = router(KioskConfig::default());
+        router.tariff = Arc::new(Tariff::default());
+        let watts = ChargeType::SpecificWatts(30.0);
+        charge(&mut router, &ctx, watts.clone(), 30.0, 20000.0);
+        run_until(&mut router, &ctx, |router| router.session.as_ref().unwrap().snapshot().energy_kwh > 0.0);
+
+        router.apply_action(&ctx, ScreenAction::ChargingStopped);
+
+        let AppState::Complete(ref charge_type, _, _, ref bill, _, _) = router.state else {
+            panic!("not complete: {:?}", router.state);
+        };
+        assert_eq!(*charge_type, watts);
+        assert!(bill.energy_kwh > 0.0);
+        assert!(router.session.is_none());
+        assert!(router.active_authorization.is_some());
+        let pending = router.ledger.pending().unwrap();
+        assert_eq!(pending.len(), 1);
+        // The ledger round-trips through JSON, which may not keep the last bit
+        assert!((pending[0].energy_kwh - bill.energy_kwh).abs() < 1e-9);
+        assert_eq!(pending[0].settlement, Settlement::Pending { authorization: authorization(20000.0) });
+
+        let bill = bill.clone();
+        router.apply_action(&ctx, ScreenAction::SessionEnded(bill.clone()));
+        assert_eq!(settled(&calls), vec![format!("capture {}", bill.total)]);
+        assert_eq!(settled_records(&router)[0].settlement, Settlement::Settled);
     }
 
-    // Current state check methods
-    pub fn is_splash(&self) -> bool {
-        matches!(self.state, AppState::Splash)
+    #[test]
+    fn session_left_pending_is_settled_after_a_restart() {
+        let (mut router, calls) = router(KioskConfig::default());
+        let bill = Bill { total: 4200.0, ..router.tariff.bill(&Usage::default(), Duration::from_secs(3600)) };
+        let mut record = SessionRecord::new(
+            ChargeType::Full,
+            100.0,
+            PaymentMethod::CreditCard,
+            &bill,
+            Duration::from_secs(3600),
+            Utc::now(),
+            "KRW",
+        );
+        record.settlement = Settlement::Pending { authorization: authorization(20000.0) };
+        router.ledger.append(&record).unwrap();
+
+        router.settle_unfinished();
+
+        assert_eq!(settled(&calls), vec!["capture 4200".to_string()]);
+        let records = settled_records(&router);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].settlement, Settlement::Settled);
     }
 
-    pub fn is_standby(&self) -> bool {
-        matches!(self.state, AppState::Standby)
+    #[test]
+    fn member_session_left_pending_is_debited_after_a_restart() {
+        let (mut router, calls) = router(KioskConfig::default());
+        let card = IdToken::new("04A1B2C3");
+        router.members = Arc::new(Mutex::new(MemberStore::new(vec![Member {
+            id: "M0001".to_string(),
+            name: "Demo".to_string(),
+            cards: vec![card.clone()],
+            balance: 10000.0,
+            plan: Plan { name: "Basic".to_string(), energy_discount_percent: 0.0 },
+        }])));
+        let bill = Bill { total: 4200.0, ..router.tariff.bill(&Usage::default(), Duration::from_secs(3600)) };
+        let mut record = SessionRecord::new(
+            ChargeType::Full,
+            100.0,
+            PaymentMethod::Membership,
+            &bill,
+            Duration::from_secs(3600),
+            Utc::now(),
+            "KRW",
+        );
+        record.member_id = Some("M0001".to_string());
+        // The hold this id names was lost with the process
+        record.settlement = Settlement::Pending {
+            authorization: Authorization {
+                id: "MBR-000001".to_string(),
+                method: PaymentMethod::Membership,
+                amount: 10000.0,
+                id_token: Some(card.clone()),
+            },
+        };
+        router.ledger.append(&record).unwrap();
+
+        router.settle_unfinished();
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert_eq!(router.members.lock().unwrap().find_by_token(&card).unwrap().balance, 5800.0);
+        let records = router.ledger.records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].settlement, Settlement::Settled);
+        assert!(router.ledger.pending().unwrap().is_empty());
     }
 
-    pub fn is_full_charge(&self) -> bool {
-        matches!(self.state, AppState::FullCharge)
+    #[test]
+    fn session_that_delivered_nothing_releases_the_hold() {
+        let ctx = egui::Context::default();
+        for ended in [SessionEnd::Failed("contactor stuck".to_string()), SessionEnd::VehicleStopped] {
+            let (mut router, calls) = router(KioskConfig::default());
+            router.tariff = Arc::new(Tariff { session_fee: 500.0, minimum_charge: 1000.0, ..Tariff::default() });
+            let watts = ChargeType::SpecificWatts(30.0);
+            charge(&mut router, &ctx, watts.clone(), 30.0, 20000.0);
+
+            end_session(&mut router, SessionSnapshot { ended: Some(ended), ..Default::default() });
+            router.apply_action(&ctx, ScreenAction::ChargingComplete);
+
+            assert_eq!(settled(&calls), vec!["void".to_string()]);
+            assert_eq!(router.state, AppState::Payment(watts, 30.0));
+            assert!(router.session.is_none());
+        }
     }
 }
@@ -0,0 +1,148 @@
+use std::iter;
+use super::{AppState, Router};
+use super::transitions::StateKind;
+use crate::ledger::SessionFilter;
+use crate::screen::{
+    ConnectScreen, SelectAmountScreen, PaymentScreen, PaymentFailedScreen, ChargingScreen, CompleteScreen, MemberScreen,
+};
+
+/// How a state was reached: going forward starts its screen over, going back
+/// returns to the screen as it was left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry {
+    Forward,
+    Back,
+}
+
+impl Router {
+    /// Build the screen for `state` from its payload, unless going back to one that is still alive
+    pub(super) fn build_screen(&mut self, state: &AppState, entry: Entry) {
+        let fresh = entry == Entry::Forward;
+        let images = self.config.assets_dir.join("images");
+        match state {
+            AppState::SelectAmount(charge_type) if fresh || self.select_amount_screen.is_none() => {
+                let bg_path = images.join("select_amount_bg.jpg");
+                self.select_amount_screen = Some(if bg_path.exists() {
+                    SelectAmountScreen::new(charge_type.clone()).with_background_image(bg_path)
+                } else {
+                    SelectAmountScreen::new(charge_type.clone())
+                });
+            }
+            AppState::Connect(charge_type, amount) if fresh || self.connect_screen.is_none() => {
+                let bg_path = images.join("connect_bg.jpg");
+                self.connect_screen = Some(if bg_path.exists() {
+                    ConnectScreen::new(self.charger.clone(), charge_type.clone(), *amount).with_background_image(bg_path)
+                } else {
+                    ConnectScreen::new(self.charger.clone(), charge_type.clone(), *amount)
+                });
+            }
+            AppState::Payment(charge_type, amount) if fresh || self.payment_screen.is_none() => {
+                let bg_path = images.join("payment_bg.jpg");
+                let mut screen = if bg_path.exists() {
+                    PaymentScreen::new(charge_type.clone(), *amount, self.tariff.clone(), self.payment.clone())
+                        .with_background_image(bg_path)
+                } else {
+                    PaymentScreen::new(charge_type.clone(), *amount, self.tariff.clone(), self.payment.clone())
+                };
+                screen = screen.with_full_charge_hold(self.config.payment.full_charge_hold);
+                if let Some(ref token) = self.id_token {
+                    screen = screen.with_id_token(token.clone());
+                }
+                self.payment_screen = Some(screen);
+            }
+            AppState::PaymentFailed(_, _, payment_method, error) if fresh || self.payment_failed_screen.is_none() => {
+                let bg_path = images.join("payment_failed_bg.jpg");
+                self.payment_failed_screen = Some(if bg_path.exists() {
+                    PaymentFailedScreen::new(payment_method.clone(), error.clone()).with_background_image(bg_path)
+                } else {
+                    PaymentFailedScreen::new(payment_method.clone(), error.clone())
+                });
+            }
+            // Renders the session the router started when the payment was approved
+            AppState::Charging(charge_type, _, payment_method, _) if fresh || self.charging_screen.is_none() => {
+                let Some(ref session) = self.session else {
+                    println!("No charging session to show");
+                    self.charging_screen = None;
+                    return;
+                };
+                let bg_path = images.join("charging_bg.jpg");
+                let screen = ChargingScreen::new(charge_type.clone(), payment_method.clone(), session.snapshots(), self.tariff.clone());
+                self.charging_screen = Some(if bg_path.exists() { screen.with_background_image(bg_path) } else { screen });
+            }
+            AppState::Complete(charge_type, amount, payment_method, bill, duration, authorization) if fresh || self.complete_screen.is_none() => {
+                let bg_path = images.join("complete_bg.jpg");
+                let screen = CompleteScreen::new(
+                    charge_type.clone(),
+                    *amount,
+                    payment_method.clone(),
+                    bill.clone(),
+                    *duration,
+                    self.tariff.clone(),
+                    self.charger.clone(),
+                )
+                .with_hold(authorization.amount);
+                // Idle time counts from when charging stopped, not from when this screen was built
+                let screen = match self.open_record {
+                    Some(ref record) => screen.with_charging_ended_at(record.ended_at),
+                    None => screen,
+                };
+                self.complete_screen = Some(if bg_path.exists() { screen.with_background_image(bg_path) } else { screen });
+            }
+            // Account overview with the member's latest sessions from the ledger
+            AppState::Member(member_id) if fresh || self.member_screen.is_none() => {
+                let Some(member) = self.members.lock().unwrap().find(member_id).cloned() else {
+                    println!("Unknown member {}", member_id);
+                    self.member_screen = None;
+                    return;
+                };
+                let filter = SessionFilter { member_id: Some(member.id.clone()), limit: Some(10), ..Default::default() };
+                let sessions = self.ledger.query(&filter).unwrap_or_else(|err| {
+                    println!("Cannot read sessions for member {}: {}", member.id, err);
+                    Vec::new()
+                });
+                let bg_path = images.join("member_bg.jpg");
+                self.member_screen = Some(if bg_path.exists() {
+                    MemberScreen::new(member, sessions, self.tariff.clone()).with_background_image(bg_path)
+                } else {
+                    MemberScreen::new(member, sessions, self.tariff.clone())
+                });
+            }
+            // Operator area, starting at the PIN pad
+            AppState::Operator if fresh => {
+                let filter = SessionFilter { limit: Some(20), ..Default::default() };
+                let sessions = self.ledger.query(&filter).unwrap_or_else(|err| {
+                    println!("Cannot read session ledger: {}", err);
+                    Vec::new()
+                });
+                self.operator_screen.open(sessions);
+            }
+            _ => {}
+        }
+    }
+
+    /// Drop the screens whose state is neither shown nor in the history
+    pub(super) fn tear_down_screens(&mut self) {
+        let live: Vec<StateKind> = self.navigation_stack.iter().chain(iter::once(&self.state)).map(AppState::kind).collect();
+        if !live.contains(&StateKind::SelectAmount) {
+            self.select_amount_screen = None;
+        }
+        if !live.contains(&StateKind::Connect) {
+            self.connect_screen = None;
+        }
+        if !live.contains(&StateKind::Payment) {
+            self.payment_screen = None;
+        }
+        if !live.contains(&StateKind::PaymentFailed) {
+            self.payment_failed_screen = None;
+        }
+        if !live.contains(&StateKind::Charging) {
+            self.charging_screen = None;
+        }
+        if !live.contains(&StateKind::Complete) {
+            self.complete_screen = None;
+        }
+        if !live.contains(&StateKind::Member) {
+            self.member_screen = None;
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use super::AppState;
+use crate::payment::Authorization;
+use crate::screen::ChargeType;
+
+/// `AppState` without its payload, as used in the transition table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateKind {
+    Splash,
+    Connect,
+    Standby,
+    SelectAmount,
+    Payment,
+    PaymentFailed,
+    Charging,
+    Complete,
+    Member,
+    Operator,
+}
+
+impl AppState {
+    pub fn kind(&self) -> StateKind {
+        match self {
+            AppState::Splash => StateKind::Splash,
+            AppState::Connect(_, _) => StateKind::Connect,
+            AppState::Standby => StateKind::Standby,
+            AppState::SelectAmount(_) => StateKind::SelectAmount,
+            AppState::Payment(_, _) => StateKind::Payment,
+            AppState::PaymentFailed(_, _, _, _) => StateKind::PaymentFailed,
+            AppState::Charging(_, _, _, _) => StateKind::Charging,
+            AppState::Complete(_, _, _, _, _, _) => StateKind::Complete,
+            AppState::Member(_) => StateKind::Member,
+            AppState::Operator => StateKind::Operator,
+        }
+    }
+}
+
+/// Extra condition a transition needs besides being in the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guard {
+    Always,
+    /// The contactor is open, so no screen is left behind with energy flowing
+    EnergyStopped,
+    /// Charging against the pre-authorization the router is currently holding
+    PaymentHeld,
+    /// A full charge has no amount to choose, so it goes straight to the cable
+    FullCharge,
+}
+
+/// What the router knows about the charger and payment when a transition is asked for
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Conditions<'a> {
+    pub energy_flowing: bool,
+    pub authorization: Option<&'a Authorization>,
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TransitionError {
+    #[error("{0:?} cannot go to {1:?}")]
+    NotAllowed(StateKind, StateKind),
+    #[error("{0:?} cannot go to {1:?} while energy is flowing")]
+    EnergyFlowing(StateKind, StateKind),
+    #[error("cannot start charging without an approved payment")]
+    NoPayment,
+    #[error("choose an amount before connecting")]
+    NoAmount,
+}
+
+use StateKind::*;
+
+/// Every legal move between screens. Going back is listed explicitly: popping the
+/// history is a transition like any other.
+const TRANSITIONS: &[(StateKind, StateKind, Guard)] = &[
+    (Splash, Standby, Guard::Always),
+    (Standby, SelectAmount, Guard::Always),
+    (Standby, Connect, Guard::FullCharge),
+    (Standby, Member, Guard::Always),
+    (Standby, Operator, Guard::Always),
+    (SelectAmount, Standby, Guard::Always),
+    (SelectAmount, Connect, Guard::Always),
+    (Connect, SelectAmount, Guard::Always),
+    (Connect, Standby, Guard::Always),
+    (Connect, Payment, Guard::Always),
+    (Payment, Connect, Guard::Always),
+    (Payment, PaymentFailed, Guard::Always),
+    (Payment, Charging, Guard::PaymentHeld),
+    (PaymentFailed, Payment, Guard::Always),
+    (Charging, Complete, Guard::EnergyStopped),
+    (Charging, Payment, Guard::EnergyStopped),
+    (Complete, Standby, Guard::EnergyStopped),
+    (Member, Standby, Guard::Always),
+    (Operator, Standby, Guard::Always),
+];
+
+/// Whether `from` may move to `to` right now
+pub fn check(from: &AppState, to: &AppState, conditions: &Conditions) -> Result<(), TransitionError> {
+    let (from_kind, to_kind) = (from.kind(), to.kind());
+    let guard = TRANSITIONS
+        .iter()
+        .find(|(table_from, table_to, _)| *table_from == from_kind && *table_to == to_kind)
+        .map(|(_, _, guard)| *guard)
+        .ok_or(TransitionError::NotAllowed(from_kind, to_kind))?;
+
+    match guard {
+        Guard::Always => Ok(()),
+        Guard::EnergyStopped if conditions.energy_flowing => Err(TransitionError::EnergyFlowing(from_kind, to_kind)),
+        Guard::EnergyStopped => Ok(()),
+        Guard::PaymentHeld => match to {
+            AppState::Charging(_, _, _, authorization) if conditions.authorization == Some(authorization) => Ok(()),
+            _ => Err(TransitionError::NoPayment),
+        },
+        Guard::FullCharge => match to {
+            AppState::Connect(ChargeType::Full, _) => Ok(()),
+            _ => Err(TransitionError::NoAmount),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::payment::PaymentError;
+    use crate::screen::PaymentMethod;
+    use crate::tariff::Tariff;
+
+    fn authorization() -> Authorization {
+        Authorization {
+            id: "AUTH-1".to_string(),
+            method: PaymentMethod::CreditCard,
+            amount: 20000.0,
+            id_token: None,
+        }
+    }
+
+    /// Walk `path` from its first state, checking every step
+    fn walk(path: &[AppState], conditions: &Conditions) -> Result<(), TransitionError> {
+        path.windows(2).try_for_each(|step| check(&step[0], &step[1], conditions))
+    }
+
+    #[test]
+    fn every_customer_journey_is_legal() {
+        let held = authorization();
+        let conditions = Conditions { energy_flowing: false, authorization: Some(&held) };
+        let watts = ChargeType::SpecificWatts(30.0);
+        let bill = Tariff::default().bill(&Default::default(), Duration::ZERO);
+        let charging = AppState::Charging(watts.clone(), 30.0, PaymentMethod::CreditCard, held.clone());
+        let complete = AppState::Complete(watts.clone(), 30.0, PaymentMethod::CreditCard, bill.clone(), Duration::ZERO, held.clone());
+        let failed = AppState::PaymentFailed(watts.clone(), 30.0, PaymentMethod::CreditCard, PaymentError::Timeout);
+
+        let connect = AppState::Connect(watts.clone(), 30.0);
+        let payment = AppState::Payment(watts.clone(), 30.0);
+
+        let journeys = [
+            // 충전량 선택 → 연결 → 결제 → 충전 → 완료
+            vec![
+                AppState::Splash,
+                AppState::Standby,
+                AppState::SelectAmount(watts.clone()),
+                connect.clone(),
+                payment.clone(),
+                charging.clone(),
+                complete.clone(),
+                AppState::Standby,
+            ],
+            // 결제 실패 후 다시 시도
+            vec![
+                AppState::Standby,
+                AppState::SelectAmount(watts.clone()),
+                connect.clone(),
+                payment.clone(),
+                failed,
+                payment.clone(),
+                charging.clone(),
+                complete,
+                AppState::Standby,
+            ],
+            // 충전 중단 후 결제 화면으로, 단계를 거슬러 처음으로
+            vec![
+                AppState::Standby,
+                AppState::SelectAmount(watts.clone()),
+                connect.clone(),
+                payment.clone(),
+                charging,
+                payment,
+                connect,
+                AppState::SelectAmount(watts.clone()),
+                AppState::Standby,
+            ],
+            // 완전 충전: 충전량 선택 없이 연결부터
+            vec![
+                AppState::Standby,
+                AppState::Connect(ChargeType::Full, 100.0),
+                AppState::Payment(ChargeType::Full, 100.0),
+                AppState::Charging(ChargeType::Full, 100.0, PaymentMethod::CreditCard, held.clone()),
+                AppState::Complete(ChargeType::Full, 100.0, PaymentMethod::CreditCard, bill, Duration::ZERO, held.clone()),
+                AppState::Standby,
+            ],
+            vec![AppState::Standby, AppState::Connect(ChargeType::Full, 100.0), AppState::Standby],
+            vec![AppState::Standby, AppState::Member("M0001".to_string()), AppState::Standby],
+            vec![AppState::Standby, AppState::Operator, AppState::Standby],
+        ];
+        for journey in journeys {
+            assert_eq!(walk(&journey, &conditions), Ok(()), "{:?}", journey);
+        }
+    }
+
+    #[test]
+    fn illegal_transitions_are_rejected() {
+        let held = authorization();
+        let watts = ChargeType::SpecificWatts(30.0);
+        let charging = AppState::Charging(watts.clone(), 30.0, PaymentMethod::CreditCard, held.clone());
+        let payment = AppState::Payment(watts.clone(), 30.0);
+        let bill = Tariff::default().bill(&Default::default(), Duration::ZERO);
+        let complete = AppState::Complete(watts.clone(), 30.0, PaymentMethod::CreditCard, bill, Duration::ZERO, held.clone());
+        let flowing = Conditions { energy_flowing: true, authorization: Some(&held) };
+
+        assert_eq!(
+            check(&charging, &payment, &flowing),
+            Err(TransitionError::EnergyFlowing(StateKind::Charging, StateKind::Payment))
+        );
+        assert_eq!(
+            check(&complete, &AppState::Standby, &flowing),
+            Err(TransitionError::EnergyFlowing(StateKind::Complete, StateKind::Standby))
+        );
+        assert_eq!(
+            check(&AppState::Standby, &charging, &Conditions::default()),
+            Err(TransitionError::NotAllowed(StateKind::Standby, StateKind::Charging))
+        );
+        assert_eq!(
+            check(&AppState::Splash, &AppState::Operator, &Conditions::default()),
+            Err(TransitionError::NotAllowed(StateKind::Splash, StateKind::Operator))
+        );
+        // 완전 충전이 아니면 충전량을 고르기 전에 연결이나 결제로 건너뛸 수 없음
+        assert_eq!(
+            check(&AppState::Standby, &AppState::Connect(watts.clone(), 0.0), &Conditions::default()),
+            Err(TransitionError::NoAmount)
+        );
+        assert_eq!(
+            check(&AppState::SelectAmount(watts.clone()), &payment, &Conditions::default()),
+            Err(TransitionError::NotAllowed(StateKind::SelectAmount, StateKind::Payment))
+        );
+
+        // 보류 중인 승인이 없거나 다른 승인이면 충전 불가
+        assert_eq!(check(&payment, &charging, &Conditions::default()), Err(TransitionError::NoPayment));
+        let other = Authorization { id: "AUTH-2".to_string(), ..held.clone() };
+        let other_held = Conditions { energy_flowing: false, authorization: Some(&other) };
+        assert_eq!(check(&payment, &charging, &other_held), Err(TransitionError::NoPayment));
+    }
+}
@@ -1,41 +1,45 @@
 use eframe::egui;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use std::path::PathBuf;
+use std::sync::Arc;
+use crate::theme;
 use crate::layout::top_bar::show_top_bar;
 use crate::layout::app_bar::AppBar;
 use crate::layout::app_container::calculate_scale;
-// use crate::layout::stepper::stepper;
+use crate::layout::stepper::journey_stepper;
 use crate::screen::select_amount_screen::ChargeType;
 use crate::screen::payment_screen::PaymentMethod;
+use crate::session::{SessionSnapshot, SharedSnapshot};
+use crate::tariff::Tariff;
+use crate::i18n;
+use crate::screen::{Screen, ScreenAction};
 
 pub struct ChargingScreen {
-    start_time: Instant,
+    // Published by the session engine; the screen only renders it
+    session: SharedSnapshot,
+    snapshot: SessionSnapshot,
+    tariff: Arc<Tariff>,
     charge_type: ChargeType,
-    charge_amount: f32,
     payment_method: PaymentMethod,
-    current_battery_level: f32,
-    charging_power: f32,
-    estimated_time: Duration,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
-    is_charging_complete: bool,
     app_bar: AppBar,
+    // The back button only asks; the stop goes out once the customer confirms
+    confirming_stop: bool,
 }
 
 impl ChargingScreen {
-    pub fn new(charge_type: ChargeType, charge_amount: f32, payment_method: PaymentMethod) -> Self {
+    pub fn new(charge_type: ChargeType, payment_method: PaymentMethod, session: SharedSnapshot, tariff: Arc<Tariff>) -> Self {
         Self {
-            start_time: Instant::now(),
+            session,
+            snapshot: SessionSnapshot::default(),
+            tariff,
             charge_type,
-            charge_amount,
             payment_method,
-            current_battery_level: 0.0,
-            charging_power: 0.0,
-            estimated_time: Duration::from_secs(0),
             background_image_path: None,
             background_image: None,
-            is_charging_complete: false,
-            app_bar: AppBar::new("Charging in Progress").with_back_button(),
+            app_bar: AppBar::new("charging.title").with_back_button(),
+            confirming_stop: false,
         }
     }
 
@@ -59,56 +63,13 @@ impl ChargingScreen {
             }
         }
     }
+}
 
-    pub fn update_charging(&mut self) {
-        let elapsed = self.start_time.elapsed();
-        
-        match self.charge_type {
-            ChargeType::SpecificWatts(target_watts) => {
-                // Specific watts charging simulation
-                let total_charge_time = Duration::from_secs_f32(target_watts * 6.0); // 6 seconds per 1kW
-                self.current_battery_level = (elapsed.as_secs_f32() / total_charge_time.as_secs_f32()).min(1.0);
-                self.charging_power = target_watts;
-                
-                if self.current_battery_level < 1.0 {
-                    let remaining_ratio = 1.0 - self.current_battery_level;
-                    self.estimated_time = Duration::from_secs_f32(remaining_ratio * total_charge_time.as_secs_f32());
-                } else {
-                    self.estimated_time = Duration::from_secs(0);
-                    self.is_charging_complete = true;
-                }
-            }
-            ChargeType::Percent(target_percent) => {
-                // Percent charging simulation
-                let total_charge_time = Duration::from_secs_f32(target_percent * 3.0); // 3 seconds per 1%
-                self.current_battery_level = (elapsed.as_secs_f32() / total_charge_time.as_secs_f32()).min(1.0);
-                self.charging_power = 50.0; // Fixed power
-                
-                if self.current_battery_level < 1.0 {
-                    let remaining_ratio = 1.0 - self.current_battery_level;
-                    self.estimated_time = Duration::from_secs_f32(remaining_ratio * total_charge_time.as_secs_f32());
-                } else {
-                    self.estimated_time = Duration::from_secs(0);
-                    self.is_charging_complete = true;
-                }
-            }
-        }
-    }
-
-    pub fn is_charging_complete(&self) -> bool {
-        self.is_charging_complete
-    }
-
-    pub fn is_back_clicked(&self) -> bool {
-        self.app_bar.is_back_clicked()
-    }
-
-    pub fn reset_back_clicked(&mut self) {
-        self.app_bar.reset_back_clicked();
-    }
-
-    pub fn show(&mut self, ctx: &egui::Context) {
-        self.update_charging();
+impl Screen for ChargingScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
+        self.snapshot = self.session.lock().unwrap().clone();
         self.load_background_image(ctx);
 
         let scale = calculate_scale(ctx);
@@ -119,7 +80,9 @@ impl ChargingScreen {
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
             .show(ctx, |ui| {
-                self.app_bar.show(ui, scale);
+                if self.app_bar.show(ui, scale) {
+                    self.confirming_stop = true;
+                }
             });
 
         egui::CentralPanel::default()
@@ -136,26 +99,31 @@ impl ChargingScreen {
                         egui::Color32::WHITE,
                     );
                 } else {
-                    let bg_color = egui::Color32::from_rgba_premultiplied(25, 35, 55, 255);
+                    let bg_color = theme.colors.background;
                     ui.painter().rect_filled(screen_rect, 0.0, bg_color);
                 }
 
                 ui.add_space(20.0 * scale);
 
+                // 진행 단계
+                journey_stepper(ui, 3, scale);
+
                 // 제목
                 ui.vertical_centered(|ui| {
-                    ui.add_space(30.0 * scale);
+                    ui.add_space(10.0 * scale);
 
-                    let title = if self.is_charging_complete {
-                        "Charging Complete!"
+                    let title = if self.snapshot.is_finished() {
+                        i18n::t("charging.complete")
+                    } else if self.charge_type == ChargeType::Full {
+                        i18n::t("charging.title_full")
                     } else {
-                        "Charging in Progress"
+                        i18n::t("charging.title")
                     };
 
                     ui.add(egui::Label::new(
                         egui::RichText::new(title)
-                            .font(egui::FontId::proportional(28.0 * scale))
-                            .color(egui::Color32::WHITE),
+                            .font(egui::FontId::proportional(theme.font_sizes.title * scale))
+                            .color(theme.colors.text),
                     ));
 
                     ui.add_space(20.0 * scale);
@@ -169,36 +137,36 @@ impl ChargingScreen {
                     // 배경 바
                     ui.painter().rect_filled(
                         progress_rect,
-                        egui::CornerRadius::same(12),
-                        egui::Color32::from_rgba_premultiplied(60, 60, 60, 255),
+                        egui::CornerRadius::same(theme.radii.progress),
+                        theme.colors.secondary,
                     );
 
                     // 프로그레스 바
-                    let progress_width = progress_rect.width() * self.current_battery_level;
+                    let progress_width = progress_rect.width() * self.snapshot.progress;
                     let progress_rect_filled = egui::Rect::from_min_size(
                         progress_rect.min,
                         egui::vec2(progress_width, progress_rect.height()),
                     );
 
-                    let progress_color = if self.current_battery_level < 0.2 {
-                        egui::Color32::from_rgb(255, 100, 100) // 빨간색
-                    } else if self.current_battery_level < 0.5 {
-                        egui::Color32::from_rgb(255, 200, 100) // 주황색
+                    let progress_color = if self.snapshot.progress < 0.2 {
+                        theme.colors.danger // 빨간색
+                    } else if self.snapshot.progress < 0.5 {
+                        theme.colors.warning // 주황색
                     } else {
-                        egui::Color32::from_rgb(100, 255, 100) // 초록색
+                        theme.colors.success_text // 초록색
                     };
 
                     ui.painter().rect_filled(
                         progress_rect_filled,
-                        egui::CornerRadius::same(12),
+                        egui::CornerRadius::same(theme.radii.progress),
                         progress_color,
                     );
 
                     // 프로그레스 바 테두리
                     ui.painter().rect_stroke(
                         progress_rect,
-                        egui::CornerRadius::same(12),
-                        egui::Stroke::new(2.0, egui::Color32::WHITE),
+                        egui::CornerRadius::same(theme.radii.progress),
+                        egui::Stroke::new(2.0, theme.colors.text),
                         egui::StrokeKind::Outside,
                     );
 
@@ -206,9 +174,9 @@ impl ChargingScreen {
 
                     // 배터리 레벨 텍스트
                     ui.add(egui::Label::new(
-                        egui::RichText::new(format!("{:.1}%", self.current_battery_level * 100.0))
-                            .font(egui::FontId::proportional(24.0 * scale))
-                            .color(egui::Color32::WHITE),
+                        egui::RichText::new(format!("{:.1}%", self.snapshot.progress * 100.0))
+                            .font(egui::FontId::proportional(theme.font_sizes.heading * scale))
+                            .color(theme.colors.text),
                     ));
 
                     ui.add_space(30.0 * scale);
@@ -220,41 +188,71 @@ impl ChargingScreen {
                         // 충전 전력
                         ui.vertical_centered(|ui| {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Charging Power")
-                                    .font(egui::FontId::proportional(16.0 * scale))
-                                    .color(egui::Color32::from_gray(200)),
+                                egui::RichText::new(i18n::t("common.charging_power"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text_muted),
+                            ));
+                            ui.add_space(5.0 * scale);
+                            ui.add(egui::Label::new(
+                                egui::RichText::new(format!("{:.1} kW", self.snapshot.power_kw))
+                                    .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                    .color(theme.colors.text),
+                            ));
+                        });
+
+                        // 충전량
+                        ui.vertical_centered(|ui| {
+                            ui.add(egui::Label::new(
+                                egui::RichText::new(i18n::t("charging.energy_delivered"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                             ui.add_space(5.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new(format!("{:.1} kW", self.charging_power))
-                                    .font(egui::FontId::proportional(20.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                egui::RichText::new(format!("{:.2} kWh", self.snapshot.energy_kwh))
+                                    .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                    .color(theme.colors.text),
+                            ));
+                        });
+
+                        // 현재 요금
+                        ui.vertical_centered(|ui| {
+                            ui.add(egui::Label::new(
+                                egui::RichText::new(i18n::t("charging.cost"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text_muted),
+                            ));
+                            ui.add_space(5.0 * scale);
+                            ui.add(egui::Label::new(
+                                egui::RichText::new(self.tariff.format_amount(self.snapshot.cost))
+                                    .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                    .color(theme.colors.warning),
                             ));
                         });
 
                         // 남은 시간
                         ui.vertical_centered(|ui| {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Estimated Time")
-                                    .font(egui::FontId::proportional(16.0 * scale))
-                                    .color(egui::Color32::from_gray(200)),
+                                egui::RichText::new(i18n::t("common.estimated_time"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                             ui.add_space(5.0 * scale);
-                            let time_text = if self.estimated_time.as_secs() == 0 {
-                                if self.is_charging_complete {
-                                    "Complete!".to_string()
+                            let time_text = if self.snapshot.estimated_time.as_secs() == 0 {
+                                if self.snapshot.is_finished() {
+                                    i18n::t("common.complete")
                                 } else {
-                                    "Calculating...".to_string()
+                                    i18n::t("common.calculating")
                                 }
                             } else {
-                                let minutes = self.estimated_time.as_secs() / 60;
-                                let seconds = self.estimated_time.as_secs() % 60;
+                                let minutes = self.snapshot.estimated_time.as_secs() / 60;
+                                let seconds = self.snapshot.estimated_time.as_secs() % 60;
                                 format!("{}:{}", minutes, format!("{:02}", seconds))
                             };
                             ui.add(egui::Label::new(
                                 egui::RichText::new(time_text)
-                                    .font(egui::FontId::proportional(20.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                    .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                    .color(theme.colors.text),
                             ));
                         });
                     });
@@ -262,50 +260,63 @@ impl ChargingScreen {
                     ui.add_space(30.0 * scale);
 
                     // 결제 방법 표시
-                    let payment_text = match self.payment_method {
-                        PaymentMethod::CreditCard => "💳 Credit Card",
-                        PaymentMethod::MobileApp => "📱 Mobile App",
-                        PaymentMethod::RFID => "🔑 RFID Card",
-                        PaymentMethod::Membership => "🎫 Membership",
-                    };
-
                     ui.add(egui::Label::new(
-                        egui::RichText::new(format!("Payment: {}", payment_text))
-                            .font(egui::FontId::proportional(16.0 * scale))
-                            .color(egui::Color32::from_gray(200)),
+                        egui::RichText::new(i18n::t_args("common.payment", &[("method", &self.payment_method.label())]))
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(theme.colors.text_muted),
                     ));
+                });
+            });
 
-                    ui.add_space(40.0 * scale);
-
-                    // 완료 버튼 (충전이 완료되었을 때만 표시)
-                    if self.is_charging_complete {
-                        let complete_btn = egui::Button::new(
-                            egui::RichText::new("View Summary")
-                                .font(egui::FontId::proportional(18.0 * scale))
-                                .color(egui::Color32::WHITE),
+        // 충전 중지 확인
+        if self.confirming_stop && !self.snapshot.is_finished() {
+            let modal = egui::Modal::new(egui::Id::new("confirm_stop")).show(ctx, |ui| {
+                ui.set_width(400.0 * scale);
+                ui.vertical_centered(|ui| {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t("charging.confirm_stop"))
+                            .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                            .color(theme.colors.text),
+                    ));
+                    ui.add_space(20.0 * scale);
+                    ui.horizontal(|ui| {
+                        let stop_btn = egui::Button::new(
+                            egui::RichText::new(i18n::t("charging.stop"))
+                                .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                .color(theme.colors.text),
                         )
-                        .min_size(egui::vec2(200.0 * scale, 50.0 * scale))
-                        .fill(egui::Color32::from_rgb(20, 180, 120))
-                        .corner_radius(egui::CornerRadius::same(10));
-
-                        let resp_complete = ui.add(complete_btn);
-                        if resp_complete.hovered() || resp_complete.is_pointer_button_down_on() {
-                            let glow_rect = resp_complete.rect.expand(8.0 * scale);
-                            let glow_shape = egui::epaint::RectShape::filled(
-                                glow_rect,
-                                egui::CornerRadius::same((12.0 * scale) as u8),
-                                egui::Color32::from_rgba_premultiplied(20, 180, 120, 80),
-                            ).with_blur_width(12.0 * scale);
-                            ui.painter().add(glow_shape);
+                        .min_size(egui::vec2(180.0 * scale, 50.0 * scale))
+                        .fill(theme.colors.danger)
+                        .corner_radius(egui::CornerRadius::same(theme.radii.button));
+                        if ui.add(stop_btn).clicked() {
+                            action = Some(ScreenAction::ChargingStopped);
                         }
-                        if resp_complete.clicked() {
-                            println!("Proceed to completion summary");
-                        }
-                    }
 
+                        let keep_btn = egui::Button::new(
+                            egui::RichText::new(i18n::t("charging.keep_charging"))
+                                .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                .color(theme.colors.text),
+                        )
+                        .min_size(egui::vec2(180.0 * scale, 50.0 * scale))
+                        .fill(theme.colors.secondary)
+                        .corner_radius(egui::CornerRadius::same(theme.radii.button));
+                        if ui.add(keep_btn).clicked() {
+                            self.confirming_stop = false;
+                        }
+                    });
                 });
             });
+            if modal.should_close() {
+                self.confirming_stop = false;
+            }
+        }
 
         ctx.request_repaint_after(Duration::from_millis(16));
+
+        // 목표에 도달하면 완료 화면으로
+        if self.snapshot.is_finished() {
+            return Some(ScreenAction::ChargingComplete);
+        }
+        action
     }
 }
@@ -1,23 +1,37 @@
 use eframe::egui;
 use std::time::{Duration, Instant};
 use std::path::PathBuf;
+use std::sync::Arc;
+use crate::theme;
 use crate::layout::top_bar::show_top_bar;
 use crate::layout::app_bar::AppBar;
 use crate::layout::app_container::calculate_scale;
-// use crate::layout::stepper::stepper;
+use crate::layout::idle_notice::idle_notice;
+use crate::layout::stepper::journey_stepper;
 use crate::screen::select_amount_screen::ChargeType;
 use crate::screen::payment_screen::PaymentMethod;
+use crate::tariff::{Bill, IdleTimer, Tariff};
+use crate::hardware::control_pilot::PilotState;
+use crate::hardware::SharedCharger;
+use crate::i18n;
+use crate::screen::{Screen, ScreenAction};
+use chrono::{DateTime, Utc};
 
 pub struct CompleteScreen {
     start_time: Instant,
     charge_type: ChargeType,
     charge_amount: f32,
     payment_method: PaymentMethod,
-    total_cost: f32,
+    bill: Bill,
+    // Pre-authorized amount; what the bill does not use goes back to the customer
+    hold: Option<f64>,
+    tariff: Arc<Tariff>,
+    charger: SharedCharger,
+    idle: IdleTimer,
+    bill_taken: bool,
     charging_duration: Duration,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
-    return_home_clicked: bool,
     app_bar: AppBar,
 }
 
@@ -26,23 +40,40 @@ impl CompleteScreen {
         charge_type: ChargeType,
         charge_amount: f32,
         payment_method: PaymentMethod,
-        total_cost: f32,
+        bill: Bill,
         charging_duration: Duration,
+        tariff: Arc<Tariff>,
+        charger: SharedCharger,
     ) -> Self {
         Self {
             start_time: Instant::now(),
             charge_type,
             charge_amount,
             payment_method,
-            total_cost,
+            bill,
+            hold: None,
+            tariff,
+            charger,
+            idle: IdleTimer::start(),
+            bill_taken: false,
             charging_duration,
             background_image_path: None,
             background_image: None,
-            return_home_clicked: false,
-            app_bar: AppBar::new("Charging Complete"),
+            app_bar: AppBar::new("complete.title"),
         }
     }
 
+    /// Count the idle time from when the session stopped charging, not from when the screen appeared
+    pub fn with_charging_ended_at(mut self, ended_at: DateTime<Utc>) -> Self {
+        self.idle = IdleTimer::since(ended_at);
+        self
+    }
+
+    pub fn with_hold(mut self, amount: f64) -> Self {
+        self.hold = Some(amount);
+        self
+    }
+
     pub fn with_background_image(mut self, image_path: PathBuf) -> Self {
         self.background_image_path = Some(image_path);
         self
@@ -64,17 +95,101 @@ impl CompleteScreen {
         }
     }
 
-    pub fn is_return_home_clicked(&self) -> bool {
-        self.return_home_clicked
+    /// Stop the idle fee once the vehicle is unplugged
+    fn update_idle(&mut self) {
+        if self.idle.is_stopped() {
+            return;
+        }
+        let mut charger = self.charger.lock().unwrap();
+        charger.poll();
+        if charger.pilot_state() == PilotState::A {
+            self.idle.stop();
+        }
     }
 
-    pub fn reset_return_home_clicked(&mut self) {
-        self.return_home_clicked = false;
+    /// Session bill including the idle fee so far, against the hold if there is one
+    fn current_bill(&self) -> Bill {
+        let bill = self.tariff.with_idle_fee(&self.bill, self.idle.elapsed());
+        match self.hold {
+            Some(hold) => bill.capped_at(hold),
+            None => bill,
+        }
     }
 
-    pub fn show(&mut self, ctx: &egui::Context) {
+    /// The final bill, handed out once when the cable is unplugged and the idle fee is settled
+    fn take_final_bill(&mut self) -> Option<Bill> {
+        if self.bill_taken || !self.idle.is_stopped() {
+            return None;
+        }
+        self.bill_taken = true;
+        Some(self.current_bill())
+    }
+
+    fn breakdown_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .bill
+            .segments
+            .iter()
+            .filter(|segment| segment.energy_kwh > 0.0)
+            .map(|segment| {
+                i18n::t_args(
+                    "complete.segment",
+                    &[
+                        ("period", &segment.rate.label()),
+                        ("start", &segment.start.format("%H:%M")),
+                        ("end", &segment.end.format("%H:%M")),
+                        ("energy", &format!("{:.2}", segment.energy_kwh)),
+                        ("price", &format!("{:.1}", segment.rate.price_per_kwh)),
+                        ("cost", &self.tariff.format_amount(segment.cost())),
+                    ],
+                )
+            })
+            .collect();
+        if self.bill.session_fee > 0.0 {
+            lines.push(i18n::t_args("complete.session_fee", &[("amount", &self.tariff.format_amount(self.bill.session_fee))]));
+        }
+        if self.bill.time_cost > 0.0 {
+            lines.push(i18n::t_args("complete.time", &[("amount", &self.tariff.format_amount(self.bill.time_cost))]));
+        }
+        if self.bill.discount > 0.0 {
+            lines.push(i18n::t_args("complete.member_discount", &[("amount", &self.tariff.format_amount(self.bill.discount))]));
+        }
+        let idle_fee = self.current_bill().idle_fee;
+        if idle_fee > 0.0 {
+            lines.push(i18n::t_args("complete.idle_fee", &[("amount", &self.tariff.format_amount(idle_fee))]));
+        }
+        if let Some(hold) = self.hold {
+            let bill = self.current_bill();
+            let released = hold - bill.total;
+            if released > 0.0 {
+                lines.push(i18n::t_args(
+                    "complete.hold_released",
+                    &[("amount", &self.tariff.format_amount(released)), ("hold", &self.tariff.format_amount(hold))],
+                ));
+            }
+            if bill.uncollected > 0.0 {
+                lines.push(i18n::t_args(
+                    "complete.uncollected",
+                    &[("amount", &self.tariff.format_amount(bill.uncollected)), ("hold", &self.tariff.format_amount(hold))],
+                ));
+            }
+        }
+        lines
+    }
+}
+
+impl Screen for CompleteScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
+        self.update_idle();
         self.load_background_image(ctx);
 
+        // 케이블을 뽑아 과금이 끝나면 최종 요금 전달 (홈 버튼보다 먼저)
+        if let Some(bill) = self.take_final_bill() {
+            action = Some(ScreenAction::SessionEnded(bill));
+        }
+
         let scale = calculate_scale(ctx);
 
         show_top_bar(ctx, scale, None);
@@ -100,50 +215,56 @@ impl CompleteScreen {
                         egui::Color32::WHITE,
                     );
                 } else {
-                    let bg_color = egui::Color32::from_rgba_premultiplied(25, 35, 55, 255);
+                    let bg_color = theme.colors.background;
                     ui.painter().rect_filled(screen_rect, 0.0, bg_color);
                 }
 
                 ui.add_space(20.0 * scale);
 
+                // 진행 단계
+                journey_stepper(ui, 4, scale);
+
                 // 제목
                 ui.vertical_centered(|ui| {
-                    ui.add_space(30.0 * scale);
+                    ui.add_space(10.0 * scale);
 
                     // 완료 아이콘
                     ui.add(egui::Label::new(
-                        egui::RichText::new("✅ Complete")
-                            .font(egui::FontId::proportional(48.0 * scale))
-                            .color(egui::Color32::from_rgb(100, 255, 100)),
+                        egui::RichText::new(i18n::t("complete.badge"))
+                            .font(egui::FontId::proportional(theme.font_sizes.display * scale))
+                            .color(theme.colors.success_text),
                     ));
 
                     ui.add_space(15.0 * scale);
 
                     ui.add(egui::Label::new(
-                        egui::RichText::new("Charging Complete!")
-                            .font(egui::FontId::proportional(28.0 * scale))
-                            .color(egui::Color32::WHITE),
+                        egui::RichText::new(i18n::t("complete.heading"))
+                            .font(egui::FontId::proportional(theme.font_sizes.title * scale))
+                            .color(theme.colors.text),
                     ));
 
                     ui.add_space(30.0 * scale);
 
+                    // 요금 내역 (요금 구간별 + 기본료/시간 요금)
+                    let breakdown = self.breakdown_lines();
+
                     // 충전 요약 정보
                     let summary_rect = egui::Rect::from_center_size(
                         ui.available_rect_before_wrap().center(),
-                        egui::vec2(500.0 * scale, 300.0 * scale),
+                        egui::vec2(500.0 * scale, (300.0 + 22.0 * breakdown.len() as f32) * scale),
                     );
 
                     // 요약 박스 배경
                     ui.painter().rect_filled(
                         summary_rect,
-                        egui::CornerRadius::same(15),
-                        egui::Color32::from_rgba_premultiplied(40, 50, 70, 200),
+                        egui::CornerRadius::same(theme.radii.panel),
+                        theme.colors.surface,
                     );
 
                     ui.painter().rect_stroke(
                         summary_rect,
-                        egui::CornerRadius::same(15),
-                        egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 255, 100)),
+                        egui::CornerRadius::same(theme.radii.panel),
+                        egui::Stroke::new(2.0, theme.colors.success_text),
                         egui::StrokeKind::Outside,
                     );
 
@@ -153,16 +274,27 @@ impl CompleteScreen {
 
                     // 충전 정보
                     let charge_info = match self.charge_type {
-                        ChargeType::SpecificWatts(watts) => format!("Charged: {:.0} kW", watts),
-                        ChargeType::Percent(percent) => format!("Target: {:.0}%", percent),
+                        ChargeType::SpecificWatts(_) => {
+                            i18n::t_args("complete.charged", &[("energy", &format!("{:.2}", self.bill.energy_kwh))])
+                        }
+                        ChargeType::Percent(_) => i18n::t_args(
+                            "complete.target",
+                            &[
+                                ("percent", &format!("{:.0}", self.charge_amount)),
+                                ("energy", &format!("{:.2}", self.bill.energy_kwh)),
+                            ],
+                        ),
+                        ChargeType::Full => {
+                            i18n::t_args("complete.full", &[("energy", &format!("{:.2}", self.bill.energy_kwh))])
+                        }
                     };
 
                     ui.painter().text(
                         egui::pos2(center_x, y_pos),
                         egui::Align2::CENTER_CENTER,
                         &charge_info,
-                        egui::FontId::proportional(20.0 * scale),
-                        egui::Color32::WHITE,
+                        egui::FontId::proportional(theme.font_sizes.subheading * scale),
+                        theme.colors.text,
                     );
 
                     y_pos += 35.0 * scale;
@@ -170,43 +302,52 @@ impl CompleteScreen {
                     // 충전 시간
                     let duration_minutes = self.charging_duration.as_secs() / 60;
                     let duration_seconds = self.charging_duration.as_secs() % 60;
-                    let duration_text = format!("Duration: {}:{}", duration_minutes, format!("{:02}", duration_seconds));
+                    let duration_text = i18n::t_args(
+                        "complete.duration",
+                        &[("duration", &format!("{}:{:02}", duration_minutes, duration_seconds))],
+                    );
 
                     ui.painter().text(
                         egui::pos2(center_x, y_pos),
                         egui::Align2::CENTER_CENTER,
                         &duration_text,
-                        egui::FontId::proportional(18.0 * scale),
-                        egui::Color32::from_gray(200),
+                        egui::FontId::proportional(theme.font_sizes.large * scale),
+                        theme.colors.text_muted,
                     );
 
                     y_pos += 30.0 * scale;
 
                     // 결제 방법
-                    let payment_text = match self.payment_method {
-                        PaymentMethod::CreditCard => "💳 Credit Card",
-                        PaymentMethod::MobileApp => "📱 Mobile App",
-                        PaymentMethod::RFID => "🔑 RFID Card",
-                        PaymentMethod::Membership => "🎫 Membership",
-                    };
-
                     ui.painter().text(
                         egui::pos2(center_x, y_pos),
                         egui::Align2::CENTER_CENTER,
-                        &format!("Payment: {}", payment_text),
-                        egui::FontId::proportional(18.0 * scale),
-                        egui::Color32::from_gray(200),
+                        i18n::t_args("common.payment", &[("method", &self.payment_method.label())]),
+                        egui::FontId::proportional(theme.font_sizes.large * scale),
+                        theme.colors.text_muted,
                     );
 
-                    y_pos += 40.0 * scale;
+                    y_pos += 30.0 * scale;
+
+                    for line in &breakdown {
+                        ui.painter().text(
+                            egui::pos2(center_x, y_pos),
+                            egui::Align2::CENTER_CENTER,
+                            line,
+                            egui::FontId::proportional(theme.font_sizes.caption * scale),
+                            theme.colors.text_subtle,
+                        );
+                        y_pos += 22.0 * scale;
+                    }
+
+                    y_pos += 10.0 * scale;
 
                     // 총 비용
                     ui.painter().text(
                         egui::pos2(center_x, y_pos),
                         egui::Align2::CENTER_CENTER,
-                        "Total Cost",
-                        egui::FontId::proportional(16.0 * scale),
-                        egui::Color32::from_gray(200),
+                        i18n::t("complete.total_cost"),
+                        egui::FontId::proportional(theme.font_sizes.body * scale),
+                        theme.colors.text_muted,
                     );
 
                     y_pos += 25.0 * scale;
@@ -214,9 +355,9 @@ impl CompleteScreen {
                     ui.painter().text(
                         egui::pos2(center_x, y_pos),
                         egui::Align2::CENTER_CENTER,
-                        &format!("{:.0} KRW", self.total_cost),
-                        egui::FontId::proportional(24.0 * scale),
-                        egui::Color32::from_rgb(255, 200, 100),
+                        self.tariff.format_amount(self.current_bill().total),
+                        egui::FontId::proportional(theme.font_sizes.heading * scale),
+                        theme.colors.warning,
                     );
 
                     y_pos += 40.0 * scale;
@@ -225,35 +366,46 @@ impl CompleteScreen {
                     ui.painter().text(
                         egui::pos2(center_x, y_pos),
                         egui::Align2::CENTER_CENTER,
-                        "Thank you for using our charging service!",
-                        egui::FontId::proportional(16.0 * scale),
-                        egui::Color32::from_gray(180),
+                        i18n::t("complete.thank_you"),
+                        egui::FontId::proportional(theme.font_sizes.body * scale),
+                        theme.colors.text_subtle,
                     );
 
                     ui.add_space(40.0 * scale);
 
-                    // 홈으로 돌아가기 버튼
+                    // 과금 대기 안내 (케이블을 뽑을 때까지)
+                    idle_notice(ui, &self.tariff, &self.idle, scale);
+
+                    ui.add_space(10.0 * scale);
+
+                    // 홈으로 돌아가기 버튼 (케이블을 뽑은 후에만 활성화)
+                    let can_return = self.idle.is_stopped();
+                    let home_color = if can_return {
+                        theme.colors.success
+                    } else {
+                        theme.colors.disabled
+                    };
                     let home_btn = egui::Button::new(
-                        egui::RichText::new("Return to Home")
-                            .font(egui::FontId::proportional(18.0 * scale))
-                            .color(egui::Color32::WHITE),
+                        egui::RichText::new(i18n::t("complete.return_home"))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
                     )
                     .min_size(egui::vec2(200.0 * scale, 50.0 * scale))
-                    .fill(egui::Color32::from_rgb(20, 180, 120))
-                    .corner_radius(egui::CornerRadius::same(10));
+                    .fill(home_color)
+                    .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                     let resp_home = ui.add(home_btn);
-                    if resp_home.hovered() || resp_home.is_pointer_button_down_on() {
+                    if can_return && (resp_home.hovered() || resp_home.is_pointer_button_down_on()) {
                         let glow_rect = resp_home.rect.expand(8.0 * scale);
                         let glow_shape = egui::epaint::RectShape::filled(
                             glow_rect,
-                            egui::CornerRadius::same((12.0 * scale) as u8),
-                            egui::Color32::from_rgba_premultiplied(20, 180, 120, 80),
+                            theme.glow_radius(scale),
+                            theme.glow(theme.colors.success),
                         ).with_blur_width(12.0 * scale);
                         ui.painter().add(glow_shape);
                     }
-                    if resp_home.clicked() {
-                        self.return_home_clicked = true;
+                    if resp_home.clicked() && can_return && action.is_none() {
+                        action = Some(ScreenAction::Home);
                     }
 
                     ui.add_space(20.0 * scale);
@@ -264,13 +416,13 @@ impl CompleteScreen {
 
                         // 영수증 인쇄 버튼
                         let receipt_btn = egui::Button::new(
-                            egui::RichText::new("🖨️ Print Receipt")
-                                .font(egui::FontId::proportional(14.0 * scale))
-                                .color(egui::Color32::WHITE),
+                            egui::RichText::new(i18n::t("complete.print_receipt"))
+                                .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                                .color(theme.colors.text),
                         )
                         .min_size(egui::vec2(120.0 * scale, 35.0 * scale))
-                        .fill(egui::Color32::from_rgba_premultiplied(60, 60, 80, 255))
-                        .corner_radius(egui::CornerRadius::same(8));
+                        .fill(theme.colors.secondary)
+                        .corner_radius(egui::CornerRadius::same(theme.radii.small));
 
                         if ui.add(receipt_btn).clicked() {
                             println!("Print receipt requested");
@@ -278,13 +430,13 @@ impl CompleteScreen {
 
                         // 다시 충전 버튼
                         let recharge_btn = egui::Button::new(
-                            egui::RichText::new("🔄 Charge Again")
-                                .font(egui::FontId::proportional(14.0 * scale))
-                                .color(egui::Color32::WHITE),
+                            egui::RichText::new(i18n::t("complete.charge_again"))
+                                .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                                .color(theme.colors.text),
                         )
                         .min_size(egui::vec2(120.0 * scale, 35.0 * scale))
-                        .fill(egui::Color32::from_rgba_premultiplied(60, 60, 80, 255))
-                        .corner_radius(egui::CornerRadius::same(8));
+                        .fill(theme.colors.secondary)
+                        .corner_radius(egui::CornerRadius::same(theme.radii.small));
 
                         if ui.add(recharge_btn).clicked() {
                             println!("Start new charging session");
@@ -294,5 +446,6 @@ impl CompleteScreen {
             });
 
         ctx.request_repaint_after(Duration::from_millis(16));
+        action
     }
 }
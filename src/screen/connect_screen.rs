@@ -1,38 +1,51 @@
 use eframe::egui;
-use std::time::{Duration, Instant};
 use std::path::PathBuf;
+use crate::theme;
 use crate::layout::top_bar::show_top_bar;
-// use crate::layout::app_bar::AppBar;
+use crate::layout::app_bar::AppBar;
+use crate::layout::stepper::journey_stepper;
 use crate::layout::app_container::calculate_scale;
+use crate::hardware::control_pilot::{current_for_duty_cycle, PilotState};
+use crate::hardware::SharedCharger;
+use crate::i18n;
+use crate::router::AppState;
+use crate::screen::{ChargeType, Screen, ScreenAction};
 
 pub struct ConnectScreen {
-    start_time: Instant,
+    charger: SharedCharger,
+    charge_type: ChargeType,
+    charge_amount: f32,
+    // 연결되면 한 번만 자동으로 결제로 넘어감 (결제에서 돌아오면 버튼으로)
+    advanced: bool,
+    offered_current: f32,
     connection_status: ConnectionStatus,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
-    proceed_clicked: bool,
-    // app_bar: AppBar,
+    app_bar: AppBar,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConnectionStatus {
-    Waiting,      // Waiting for EV connection
-    Connecting,   // Connecting to EV
-    Verifying,    // Verifying communication protocol
-    Finalizing,   // Finalizing connection
-    Connected,    // EV connected
-    Error,        // Connection error
+    Waiting,      // State A: no vehicle on the cable
+    Connecting,   // State B: vehicle detected, no current offered yet
+    Verifying,    // State B: PWM running, vehicle diode not yet confirmed
+    Finalizing,   // State B: current offered, waiting for the vehicle to close S2
+    Connected,    // State C/D: vehicle ready to accept energy
+    Error,        // State E/F or failed diode check
 }
 
 impl ConnectScreen {
-    pub fn new() -> Self {
+    pub fn new(charger: SharedCharger, charge_type: ChargeType, charge_amount: f32) -> Self {
         Self {
-            start_time: Instant::now(),
+            charger,
+            charge_type,
+            charge_amount,
+            advanced: false,
+            offered_current: 0.0,
             connection_status: ConnectionStatus::Waiting,
             background_image_path: None,
             background_image: None,
-            proceed_clicked: false,
-            // app_bar: AppBar::new("Connect Your EV"),
+            app_bar: AppBar::new("connect.title").with_back_button(),
         }
     }
 
@@ -41,27 +54,11 @@ impl ConnectScreen {
         self
     }
 
-    pub fn is_proceed_clicked(&self) -> bool {
-        self.proceed_clicked
-    }
-
-    pub fn reset_proceed_clicked(&mut self) {
-        self.proceed_clicked = false;
-    }
-
-    // pub fn is_back_clicked(&self) -> bool {
-        // self.app_bar.is_back_clicked()
-    // }
-
-    // pub fn reset_back_clicked(&mut self) {
-    //     self.app_bar.reset_back_clicked();
-    // }
-
     pub fn get_connection_status(&self) -> &ConnectionStatus {
         &self.connection_status
     }
 
-    pub fn is_connection_complete(&self) -> bool {
+    fn is_connection_complete(&self) -> bool {
         matches!(self.connection_status, ConnectionStatus::Connected)
     }
 
@@ -82,149 +79,168 @@ impl ConnectScreen {
     }
 
     fn update_connection_status(&mut self) {
-        let elapsed = self.start_time.elapsed();
-        
-        // Simulate EV charger connection detection
-        match self.connection_status {
-            ConnectionStatus::Waiting => {
-                // Step 1: Connector detection (1 second)
-                if elapsed > Duration::from_secs(1) {
-                    self.connection_status = ConnectionStatus::Connecting;
-                }
-            }
-            ConnectionStatus::Connecting => {
-                // Step 2: Electrical connection verification (2 seconds)
-                if elapsed > Duration::from_secs(2) {
-                    self.connection_status = ConnectionStatus::Verifying;
-                }
-            }
-            ConnectionStatus::Verifying => {
-                // Step 3: Communication protocol verification (3 seconds)
-                if elapsed > Duration::from_secs(3) {
-                    self.connection_status = ConnectionStatus::Finalizing;
-                }
-            }
-            ConnectionStatus::Finalizing => {
-                // Step 4: Final connection completion (5 seconds)
-                if elapsed > Duration::from_secs(5) {
-                    self.connection_status = ConnectionStatus::Connected;
-                }
-            }
-            ConnectionStatus::Connected => {
-                // Connection maintained - continuous monitoring in real implementation
-            }
-            ConnectionStatus::Error => {
-                // Error state - retry logic needed
-            }
+        let mut charger = self.charger.lock().unwrap();
+        if let Some(event) = charger.poll() {
+            println!("Control pilot: {:?} (state {:?})", event, charger.pilot_state());
         }
+
+        let pilot = charger.pilot();
+        self.offered_current = pilot.duty_cycle().and_then(current_for_duty_cycle).unwrap_or(0.0);
+        self.connection_status = if charger.fault().is_some() {
+            ConnectionStatus::Error
+        } else {
+            match pilot.state() {
+                PilotState::A => ConnectionStatus::Waiting,
+                PilotState::C | PilotState::D => ConnectionStatus::Connected,
+                PilotState::E | PilotState::F => ConnectionStatus::Error,
+                PilotState::B if !pilot.is_pwm_enabled() => ConnectionStatus::Connecting,
+                PilotState::B if !pilot.is_diode_verified() => ConnectionStatus::Verifying,
+                PilotState::B => ConnectionStatus::Finalizing,
+            }
+        };
     }
+}
 
-    pub fn show(&mut self, ctx: &egui::Context) {
+impl Screen for ConnectScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
         let scale = calculate_scale(ctx);
         
         // Update connection status
         self.update_connection_status();
 
+        let theme = theme::current();
+        let mut action = None;
         show_top_bar(ctx, scale, None);
 
         // AppBar display
-        // egui::CentralPanel::default()
-        //     .frame(egui::Frame::NONE)
-        //     .show(ctx, |ui| {
-        //         self.app_bar.show(ui, scale);
-        //     });
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                if self.app_bar.show(ui, scale) {
+                    action = Some(ScreenAction::Back);
+                }
+            });
 
         // Main content - display different UI based on connection status
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
             .show(ctx, |ui| {
+                ui.add_space(20.0 * scale);
+
+                // 진행 단계
+                journey_stepper(ui, 1, scale);
+
                 // Layout for precise center alignment
                 ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
                     // Space for vertical center alignment
-                    ui.add_space(ui.available_height() / 2.0 - 40.0 * scale);
+                    ui.add_space(ui.available_height() / 2.0 - 60.0 * scale);
                     
                     // Display message based on connection status
                     match self.connection_status {
                         ConnectionStatus::Waiting => {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Connect Your Car")
-                                    .font(egui::FontId::proportional(32.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                egui::RichText::new(i18n::t("connect.title"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                                    .color(theme.colors.text),
                             ));
                             ui.add_space(10.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Please connect the charging cable")
-                                    .font(egui::FontId::proportional(18.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(200, 200, 200, 255)),
+                                egui::RichText::new(i18n::t("connect.subtitle"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                         }
                         ConnectionStatus::Connecting => {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Connecting...")
-                                    .font(egui::FontId::proportional(32.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(100, 200, 255, 255)),
+                                egui::RichText::new(i18n::t("connect.connecting"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                                    .color(theme.colors.info),
                             ));
                             ui.add_space(10.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Detecting vehicle connection")
-                                    .font(egui::FontId::proportional(18.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(200, 200, 200, 255)),
+                                egui::RichText::new(i18n::t("connect.detecting"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                         }
                         ConnectionStatus::Verifying => {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Verifying...")
-                                    .font(egui::FontId::proportional(32.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(255, 200, 100, 255)),
+                                egui::RichText::new(i18n::t("connect.verifying"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                                    .color(theme.colors.warning),
                             ));
                             ui.add_space(10.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Checking electrical connection")
-                                    .font(egui::FontId::proportional(18.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(200, 200, 200, 255)),
+                                egui::RichText::new(i18n::t("connect.checking"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                         }
                         ConnectionStatus::Finalizing => {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Finalizing...")
-                                    .font(egui::FontId::proportional(32.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(255, 150, 100, 255)),
+                                egui::RichText::new(i18n::t("connect.finalizing"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                                    .color(theme.colors.warning),
                             ));
                             ui.add_space(10.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Establishing communication protocol")
-                                    .font(egui::FontId::proportional(18.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(200, 200, 200, 255)),
+                                egui::RichText::new(i18n::t("connect.establishing"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                         }
                         ConnectionStatus::Connected => {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Connected!")
-                                    .font(egui::FontId::proportional(32.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(100, 255, 100, 255)),
+                                egui::RichText::new(i18n::t("connect.connected"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                                    .color(theme.colors.success_text),
                             ));
                             ui.add_space(10.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Vehicle ready for charging")
-                                    .font(egui::FontId::proportional(18.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(200, 200, 200, 255)),
+                                egui::RichText::new(i18n::t_args("connect.ready", &[("current", &format!("{:.0}", self.offered_current))]))
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text_muted),
                             ));
+
+                            // 결제에서 돌아온 경우 직접 다시 진행
+                            if self.advanced {
+                                ui.add_space(40.0 * scale);
+                                let continue_btn = egui::Button::new(
+                                    egui::RichText::new(i18n::t("connect.continue"))
+                                        .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                        .color(theme.colors.text),
+                                )
+                                .min_size(egui::vec2(250.0 * scale, 50.0 * scale))
+                                .fill(theme.colors.success)
+                                .corner_radius(egui::CornerRadius::same(theme.radii.button));
+
+                                if ui.add(continue_btn).clicked() {
+                                    action = Some(ScreenAction::Navigate(AppState::Payment(self.charge_type.clone(), self.charge_amount)));
+                                }
+                            }
                         }
                         ConnectionStatus::Error => {
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Connection Error")
-                                    .font(egui::FontId::proportional(32.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(255, 100, 100, 255)),
+                                egui::RichText::new(i18n::t("connect.error"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                                    .color(theme.colors.danger),
                             ));
                             ui.add_space(10.0 * scale);
                             ui.add(egui::Label::new(
-                                egui::RichText::new("Please check the connection")
-                                    .font(egui::FontId::proportional(18.0 * scale))
-                                    .color(egui::Color32::from_rgba_premultiplied(200, 200, 200, 255)),
+                                egui::RichText::new(i18n::t("connect.error_hint"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text_muted),
                             ));
                         }
                     }
                 });
             });
+
+        // 차량이 처음 연결되면 바로 결제로
+        if self.is_connection_complete() && !self.advanced {
+            self.advanced = true;
+            action = Some(ScreenAction::Navigate(AppState::Payment(self.charge_type.clone(), self.charge_amount)));
+        }
+        action
     }
 }
@@ -0,0 +1,186 @@
+use eframe::egui;
+use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::theme;
+use crate::layout::top_bar::show_top_bar;
+use crate::layout::app_bar::AppBar;
+use crate::layout::app_container::calculate_scale;
+use crate::ledger::SessionRecord;
+use crate::membership::Member;
+use crate::tariff::Tariff;
+use crate::i18n;
+use crate::screen::{Screen, ScreenAction};
+
+/// Account overview for a member who tapped their card on the standby screen
+pub struct MemberScreen {
+    member: Member,
+    sessions: Vec<SessionRecord>,
+    tariff: Arc<Tariff>,
+    background_image_path: Option<PathBuf>,
+    background_image: Option<egui::TextureHandle>,
+    app_bar: AppBar,
+}
+
+impl MemberScreen {
+    pub fn new(member: Member, sessions: Vec<SessionRecord>, tariff: Arc<Tariff>) -> Self {
+        Self {
+            member,
+            sessions,
+            tariff,
+            background_image_path: None,
+            background_image: None,
+            app_bar: AppBar::new("member.title").with_back_button(),
+        }
+    }
+
+    pub fn with_background_image(mut self, image_path: PathBuf) -> Self {
+        self.background_image_path = Some(image_path);
+        self
+    }
+
+    fn load_background_image(&mut self, ctx: &egui::Context) {
+        if let Some(ref path) = self.background_image_path {
+            if self.background_image.is_none() {
+                if let Ok(image_data) = std::fs::read(path) {
+                    if let Ok(image) = image::load_from_memory(&image_data) {
+                        let rgba_image = image.to_rgba8();
+                        let size = [rgba_image.width() as usize, rgba_image.height() as usize];
+                        let pixels = rgba_image.into_raw();
+                        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+                        self.background_image = Some(ctx.load_texture("member_background", color_image, Default::default()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Screen for MemberScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
+        self.load_background_image(ctx);
+
+        let scale = calculate_scale(ctx);
+
+        show_top_bar(ctx, scale, None);
+
+        // AppBar 표시
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                if self.app_bar.show(ui, scale) {
+                    action = Some(ScreenAction::Back);
+                }
+            });
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                let screen_rect = ui.max_rect();
+
+                // 배경 이미지 또는 색상
+                if let Some(ref texture) = self.background_image {
+                    ui.painter().image(
+                        texture.id(),
+                        screen_rect,
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+                } else {
+                    let bg_color = theme.colors.background;
+                    ui.painter().rect_filled(screen_rect, 0.0, bg_color);
+                }
+
+                ui.add_space(20.0 * scale);
+
+                ui.vertical_centered(|ui| {
+                    ui.add_space(30.0 * scale);
+
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(&self.member.name)
+                            .font(egui::FontId::proportional(theme.font_sizes.title * scale))
+                            .color(theme.colors.text),
+                    ));
+
+                    ui.add_space(10.0 * scale);
+
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t_args("member.balance", &[("amount", &self.tariff.format_amount(self.member.balance))]))
+                            .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                            .color(theme.colors.warning),
+                    ));
+
+                    let plan = if self.member.plan.energy_discount_percent > 0.0 {
+                        i18n::t_args(
+                            "member.plan_discount",
+                            &[
+                                ("plan", &self.member.plan.name),
+                                ("percent", &format!("{:.0}", self.member.plan.energy_discount_percent)),
+                            ],
+                        )
+                    } else {
+                        i18n::t_args("member.plan", &[("plan", &self.member.plan.name)])
+                    };
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(plan)
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(theme.colors.text_muted),
+                    ));
+
+                    ui.add_space(30.0 * scale);
+
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t("member.recent_sessions"))
+                            .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                            .color(theme.colors.text),
+                    ));
+
+                    ui.add_space(10.0 * scale);
+
+                    if self.sessions.is_empty() {
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(i18n::t("member.no_sessions"))
+                                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                .color(theme.colors.text_subtle),
+                        ));
+                    } else {
+                        egui::Grid::new("member_sessions")
+                            .num_columns(4)
+                            .spacing(egui::vec2(30.0 * scale, 10.0 * scale))
+                            .show(ui, |ui| {
+                                for header in ["member.date", "member.energy", "member.discount", "member.total"] {
+                                    ui.add(egui::Label::new(
+                                        egui::RichText::new(i18n::t(header))
+                                            .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                                            .color(theme.colors.text_subtle),
+                                    ));
+                                }
+                                ui.end_row();
+
+                                for session in &self.sessions {
+                                    let cells = [
+                                        session.ended_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+                                        format!("{:.2} kWh", session.energy_kwh),
+                                        self.tariff.format_amount(session.discount),
+                                        self.tariff.format_amount(session.total),
+                                    ];
+                                    for cell in cells {
+                                        ui.add(egui::Label::new(
+                                            egui::RichText::new(cell)
+                                                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                                .color(theme.colors.text),
+                                        ));
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    }
+                });
+            });
+
+        ctx.request_repaint_after(Duration::from_millis(16));
+        action
+    }
+}
@@ -1,17 +1,57 @@
+use eframe::egui;
+use crate::router::AppState;
+use crate::payment::{Authorization, PaymentError};
+use crate::tariff::Bill;
+
 pub mod splash_screen;
 pub mod connect_screen;
 pub mod standby_screen;
-pub mod full_charge_screen;
 pub mod select_amount_screen;
 pub mod payment_screen;
+pub mod payment_failed_screen;
 pub mod charging_screen;
 pub mod complete_screen;
+pub mod member_screen;
+pub mod operator_screen;
 
 pub use splash_screen::SplashScreen;
 pub use connect_screen::ConnectScreen;
 pub use standby_screen::StandbyScreen;
-pub use full_charge_screen::FullChargeScreen;
 pub use select_amount_screen::{SelectAmountScreen, ChargeType};
 pub use payment_screen::{PaymentScreen, PaymentMethod};
+pub use payment_failed_screen::PaymentFailedScreen;
 pub use charging_screen::ChargingScreen;
 pub use complete_screen::CompleteScreen;
+pub use member_screen::MemberScreen;
+pub use operator_screen::OperatorScreen;
+
+/// What a screen asks the router to do, returned from the frame it happened in.
+/// There are no click flags to reset: an action is handled once and dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScreenAction {
+    /// Open another screen on top of this one
+    Navigate(AppState),
+    /// Return to the previous screen
+    Back,
+    /// The customer is done: Standby with an empty history and the default language
+    Home,
+    NextLanguage,
+    OpenOperator,
+    /// Start a fresh copy of the app and close this one
+    Restart,
+    PaymentApproved(Authorization),
+    PaymentFailed(PaymentMethod, PaymentError),
+    RetryPayment,
+    ChooseAnotherMethod,
+    /// The charging session ended on its own (target, hold or vehicle)
+    ChargingComplete,
+    /// The customer stopped charging early; bill what was delivered
+    ChargingStopped,
+    /// Cable unplugged after charging: the final bill, with any idle fee
+    SessionEnded(Bill),
+}
+
+pub trait Screen {
+    /// Draw one frame
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction>;
+}
@@ -0,0 +1,569 @@
+use eframe::egui;
+use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::theme;
+use crate::layout::top_bar::show_top_bar;
+use crate::layout::app_bar::AppBar;
+use crate::layout::app_container::calculate_scale;
+use crate::config::KioskConfig;
+use crate::hardware::SharedCharger;
+use crate::ledger::SessionRecord;
+use crate::operator::{PinLock, PinResult, SharedAvailability};
+use crate::tariff::Tariff;
+use crate::i18n;
+use crate::screen::{Screen, ScreenAction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorTab {
+    Config,
+    Connectors,
+    Hardware,
+    Sessions,
+}
+
+impl OperatorTab {
+    const ALL: [OperatorTab; 4] = [OperatorTab::Config, OperatorTab::Connectors, OperatorTab::Hardware, OperatorTab::Sessions];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            OperatorTab::Config => "operator.tab_config",
+            OperatorTab::Connectors => "operator.tab_connectors",
+            OperatorTab::Hardware => "operator.tab_hardware",
+            OperatorTab::Sessions => "operator.tab_sessions",
+        }
+    }
+}
+
+/// Technician area behind the top bar Settings button: PIN pad first, then
+/// configuration, connector availability, hardware status and recent sessions
+pub struct OperatorScreen {
+    config: KioskConfig,
+    charger: SharedCharger,
+    tariff: Arc<Tariff>,
+    availability: SharedAvailability,
+    pin_lock: PinLock,
+    pin_entry: String,
+    pin_result: Option<PinResult>,
+    unlocked: bool,
+    tab: OperatorTab,
+    config_text: String,
+    // 저장 결과: Ok(()) 저장됨, Err(메시지) 검증 실패
+    save_result: Option<Result<(), String>>,
+    sessions: Vec<SessionRecord>,
+    last_activity: Instant,
+    background_image_path: Option<PathBuf>,
+    background_image: Option<egui::TextureHandle>,
+    app_bar: AppBar,
+}
+
+impl OperatorScreen {
+    pub fn new(config: KioskConfig, charger: SharedCharger, tariff: Arc<Tariff>, availability: SharedAvailability) -> Self {
+        Self {
+            pin_lock: PinLock::new(&config.operator.pin),
+            config,
+            charger,
+            tariff,
+            availability,
+            pin_entry: String::new(),
+            pin_result: None,
+            unlocked: false,
+            tab: OperatorTab::Hardware,
+            config_text: String::new(),
+            save_result: None,
+            sessions: Vec::new(),
+            last_activity: Instant::now(),
+            background_image_path: None,
+            background_image: None,
+            app_bar: AppBar::new("operator.title").with_back_button(),
+        }
+    }
+
+    pub fn with_background_image(mut self, image_path: PathBuf) -> Self {
+        self.background_image_path = Some(image_path);
+        self
+    }
+
+    fn load_background_image(&mut self, ctx: &egui::Context) {
+        if let Some(ref path) = self.background_image_path {
+            if self.background_image.is_none() {
+                if let Ok(image_data) = std::fs::read(path) {
+                    if let Ok(image) = image::load_from_memory(&image_data) {
+                        let rgba_image = image.to_rgba8();
+                        let size = [rgba_image.width() as usize, rgba_image.height() as usize];
+                        let pixels = rgba_image.into_raw();
+                        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+                        self.background_image = Some(ctx.load_texture("operator_background", color_image, Default::default()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start a visit at the PIN pad, with the latest sessions and the config file as it is on disk
+    pub fn open(&mut self, sessions: Vec<SessionRecord>) {
+        self.unlocked = false;
+        self.pin_entry.clear();
+        self.pin_result = None;
+        self.tab = OperatorTab::Hardware;
+        self.config_text = std::fs::read_to_string(&self.config.file).unwrap_or_default();
+        self.save_result = None;
+        self.sessions = sessions;
+        self.last_activity = Instant::now();
+    }
+
+    /// Nobody touched the screen for `operator.idle_timeout_secs`
+    fn is_timed_out(&self) -> bool {
+        self.last_activity.elapsed() >= self.config.operator.idle_timeout()
+    }
+
+    fn submit_pin(&mut self) {
+        let result = self.pin_lock.enter(&self.pin_entry, Instant::now());
+        self.pin_entry.clear();
+        if result == PinResult::Accepted {
+            println!("Operator signed in");
+            self.unlocked = true;
+            self.pin_result = None;
+        } else {
+            println!("Operator PIN refused: {:?}", result);
+            self.pin_result = Some(result);
+        }
+    }
+
+    fn save_config(&mut self) {
+        self.save_result = Some(match KioskConfig::parse(&self.config_text) {
+            Ok(_) => std::fs::write(&self.config.file, &self.config_text).map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        });
+        if let Some(Ok(())) = self.save_result {
+            println!("Operator saved {}", self.config.file.display());
+        }
+    }
+
+    fn show_pin_pad(&mut self, ui: &mut egui::Ui, scale: f32) {
+        let theme = theme::current();
+        let locked = self.pin_lock.lockout_remaining(Instant::now());
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(10.0 * scale);
+            ui.add(egui::Label::new(
+                egui::RichText::new(i18n::t("operator.enter_pin"))
+                    .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                    .color(theme.colors.text),
+            ));
+            ui.add_space(10.0 * scale);
+
+            // 입력한 자릿수만 표시
+            let masked = if self.pin_entry.is_empty() { "-".to_string() } else { "*".repeat(self.pin_entry.len()) };
+            ui.add(egui::Label::new(
+                egui::RichText::new(masked)
+                    .font(egui::FontId::monospace(theme.font_sizes.title * scale))
+                    .color(theme.colors.text),
+            ));
+
+            let message = match (locked, self.pin_result) {
+                (Some(remaining), _) => Some(i18n::t_args("operator.locked", &[("seconds", &remaining.as_secs().max(1))])),
+                (None, Some(PinResult::Rejected { attempts_left })) => {
+                    Some(i18n::t_args("operator.wrong_pin", &[("attempts", &attempts_left)]))
+                }
+                _ => None,
+            };
+            if let Some(message) = message {
+                ui.add(egui::Label::new(
+                    egui::RichText::new(message)
+                        .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                        .color(theme.colors.danger),
+                ));
+            }
+            ui.add_space(15.0 * scale);
+
+            let key_size = egui::vec2(70.0 * scale, 50.0 * scale);
+            let rows = [["1", "2", "3"], ["4", "5", "6"], ["7", "8", "9"], ["C", "0", "OK"]];
+            let pad_width = key_size.x * 3.0 + 2.0 * 10.0 * scale;
+            for row in rows {
+                ui.allocate_ui_with_layout(
+                    egui::vec2(pad_width, key_size.y),
+                    egui::Layout::left_to_right(egui::Align::Center),
+                    |ui| {
+                        ui.spacing_mut().item_spacing = egui::vec2(10.0 * scale, 0.0);
+                        for key in row {
+                            let (text, fill) = match key {
+                                "C" => (i18n::t("operator.clear"), theme.colors.secondary),
+                                "OK" => (i18n::t("operator.ok"), theme.colors.success),
+                                digit => (digit.to_string(), theme.colors.primary),
+                            };
+                            let button = egui::Button::new(
+                                egui::RichText::new(text)
+                                    .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                                    .color(theme.colors.text),
+                            )
+                            .min_size(key_size)
+                            .fill(if locked.is_some() { theme.colors.disabled } else { fill })
+                            .corner_radius(egui::CornerRadius::same(theme.radii.button));
+
+                            if ui.add_enabled(locked.is_none(), button).clicked() {
+                                match key {
+                                    "C" => self.pin_entry.clear(),
+                                    "OK" => self.submit_pin(),
+                                    digit if self.pin_entry.len() < 8 => self.pin_entry.push_str(digit),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    },
+                );
+                ui.add_space(10.0 * scale);
+            }
+        });
+    }
+
+    /// Returns true when Restart was clicked
+    fn show_operator_area(&mut self, ui: &mut egui::Ui, scale: f32) -> bool {
+        let theme = theme::current();
+        let mut restart_clicked = false;
+
+        ui.horizontal(|ui| {
+            ui.add_space(20.0 * scale);
+            ui.spacing_mut().item_spacing = egui::vec2(10.0 * scale, 0.0);
+            for tab in OperatorTab::ALL {
+                let button = egui::Button::new(
+                    egui::RichText::new(i18n::t(tab.label_key()))
+                        .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                        .color(theme.colors.text),
+                )
+                .min_size(egui::vec2(130.0 * scale, 40.0 * scale))
+                .fill(if self.tab == tab { theme.colors.success } else { theme.colors.secondary })
+                .corner_radius(egui::CornerRadius::same(theme.radii.small));
+                if ui.add(button).clicked() {
+                    self.tab = tab;
+                }
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.add_space(20.0 * scale);
+                let restart = egui::Button::new(
+                    egui::RichText::new(i18n::t("operator.restart"))
+                        .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                        .color(theme.colors.text),
+                )
+                .min_size(egui::vec2(130.0 * scale, 40.0 * scale))
+                .fill(theme.colors.danger)
+                .corner_radius(egui::CornerRadius::same(theme.radii.small));
+                if ui.add(restart).clicked() {
+                    println!("Operator requested a restart");
+                    restart_clicked = true;
+                }
+            });
+        });
+
+        ui.add_space(15.0 * scale);
+
+        egui::Frame::default()
+            .fill(theme.colors.surface)
+            .corner_radius(egui::CornerRadius::same(theme.radii.panel))
+            .inner_margin(egui::Margin::same((15.0 * scale) as i8))
+            .outer_margin(egui::Margin::symmetric((20.0 * scale) as i8, 0))
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| match self.tab {
+                    OperatorTab::Config => self.show_config(ui, scale),
+                    OperatorTab::Connectors => self.show_connectors(ui, scale),
+                    OperatorTab::Hardware => self.show_hardware(ui, scale),
+                    OperatorTab::Sessions => self.show_sessions(ui, scale),
+                });
+            });
+        restart_clicked
+    }
+
+    fn show_config(&mut self, ui: &mut egui::Ui, scale: f32) {
+        let theme = theme::current();
+
+        ui.add(egui::Label::new(
+            egui::RichText::new(i18n::t_args("operator.config_file", &[("path", &self.config.file.display())]))
+                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                .color(theme.colors.text),
+        ));
+        ui.add(egui::Label::new(
+            egui::RichText::new(i18n::t("operator.config_hint"))
+                .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                .color(theme.colors.text_muted),
+        ));
+        ui.add_space(8.0 * scale);
+
+        ui.horizontal(|ui| {
+            let save = egui::Button::new(
+                egui::RichText::new(i18n::t("operator.save"))
+                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                    .color(theme.colors.text),
+            )
+            .min_size(egui::vec2(100.0 * scale, 36.0 * scale))
+            .fill(theme.colors.success)
+            .corner_radius(egui::CornerRadius::same(theme.radii.small));
+            if ui.add(save).clicked() {
+                self.save_config();
+            }
+
+            match &self.save_result {
+                Some(Ok(())) => {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t("operator.saved"))
+                            .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                            .color(theme.colors.success_text),
+                    ));
+                }
+                Some(Err(error)) => {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t_args("operator.save_failed", &[("error", error)]))
+                            .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                            .color(theme.colors.danger),
+                    ));
+                }
+                None => {}
+            }
+        });
+        ui.add_space(8.0 * scale);
+
+        let editor = egui::TextEdit::multiline(&mut self.config_text)
+            .font(egui::FontId::monospace(theme.font_sizes.caption * scale))
+            .code_editor()
+            .desired_rows(16)
+            .desired_width(f32::INFINITY);
+        if ui.add(editor).changed() {
+            self.save_result = None;
+        }
+    }
+
+    fn show_connectors(&mut self, ui: &mut egui::Ui, scale: f32) {
+        let theme = theme::current();
+        let mut changed = None;
+
+        egui::Grid::new("operator_connectors")
+            .num_columns(4)
+            .spacing(egui::vec2(30.0 * scale, 12.0 * scale))
+            .show(ui, |ui| {
+                let availability = self.availability.lock().unwrap();
+                for connector in &self.config.connectors {
+                    let available = availability.is_available(connector.id);
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t_args("operator.connector", &[("id", &connector.id)]))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
+                    ));
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t_args("operator.max_current", &[("current", &connector.max_current_a)]))
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(theme.colors.text_muted),
+                    ));
+                    let (status, color) = if available {
+                        (i18n::t("operator.available"), theme.colors.success_text)
+                    } else {
+                        (i18n::t("operator.unavailable"), theme.colors.warning)
+                    };
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(status)
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(color),
+                    ));
+
+                    let toggle = egui::Button::new(
+                        egui::RichText::new(i18n::t(if available { "operator.take_out" } else { "operator.put_back" }))
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(theme.colors.text),
+                    )
+                    .min_size(egui::vec2(180.0 * scale, 36.0 * scale))
+                    .fill(if available { theme.colors.warning } else { theme.colors.success })
+                    .corner_radius(egui::CornerRadius::same(theme.radii.small));
+                    if ui.add(toggle).clicked() {
+                        changed = Some((connector.id, !available));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some((connector_id, available)) = changed {
+            if let Err(err) = self.availability.lock().unwrap().set_available(connector_id, available) {
+                println!("{}", err);
+            }
+        }
+    }
+
+    fn show_hardware(&mut self, ui: &mut egui::Ui, scale: f32) {
+        let theme = theme::current();
+        let (rows, healthy) = {
+            let mut charger = self.charger.lock().unwrap();
+            let meter = charger.read_meter();
+            let emergency_stop = charger.is_emergency_stop_pressed();
+            let on_off = |on: bool, on_key: &str, off_key: &str| i18n::t(if on { on_key } else { off_key });
+            let rows = vec![
+                (i18n::t("operator.pilot_state"), format!("{:?}", charger.pilot_state())),
+                (
+                    i18n::t("operator.fault"),
+                    charger.fault().map_or_else(|| i18n::t("operator.none"), |state| format!("{:?}", state)),
+                ),
+                (i18n::t("operator.contactor"), on_off(charger.is_energy_flowing(), "operator.closed", "operator.open")),
+                (i18n::t("operator.cable_lock"), on_off(charger.is_cable_locked(), "operator.locked_state", "operator.unlocked_state")),
+                (i18n::t("operator.emergency_stop"), on_off(emergency_stop, "operator.pressed", "operator.released")),
+                (i18n::t("operator.offered_current"), format!("{:.0} A", charger.max_current())),
+                (
+                    i18n::t("operator.meter"),
+                    format!(
+                        "{:.2} kWh · {:.1} kW · {:.0} V · {:.1} A",
+                        meter.energy_wh / 1000.0,
+                        meter.power_w / 1000.0,
+                        meter.voltage_v,
+                        meter.current_a
+                    ),
+                ),
+                (
+                    i18n::t("operator.vehicle_soc"),
+                    meter.vehicle_soc.map_or_else(|| "-".to_string(), |soc| format!("{:.0}%", soc)),
+                ),
+                (
+                    i18n::t("operator.backend"),
+                    match self.config.backend.url {
+                        Some(ref url) => format!("{} (OCPP {})", url, self.config.backend.version),
+                        None => i18n::t("operator.offline"),
+                    },
+                ),
+                (i18n::t("operator.charger_id"), self.config.charger.id.clone()),
+                (i18n::t("operator.version"), env!("CARGO_PKG_VERSION").to_string()),
+            ];
+            (rows, charger.fault().is_none() && !emergency_stop)
+        };
+
+        ui.add(egui::Label::new(
+            egui::RichText::new(i18n::t(if healthy { "operator.healthy" } else { "operator.attention" }))
+                .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                .color(if healthy { theme.colors.success_text } else { theme.colors.danger }),
+        ));
+        ui.add_space(10.0 * scale);
+
+        egui::Grid::new("operator_hardware")
+            .num_columns(2)
+            .spacing(egui::vec2(30.0 * scale, 8.0 * scale))
+            .show(ui, |ui| {
+                for (label, value) in rows {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(label)
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(theme.colors.text_muted),
+                    ));
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(value)
+                            .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                            .color(theme.colors.text),
+                    ));
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn show_sessions(&mut self, ui: &mut egui::Ui, scale: f32) {
+        let theme = theme::current();
+
+        if self.sessions.is_empty() {
+            ui.add(egui::Label::new(
+                egui::RichText::new(i18n::t("operator.no_sessions"))
+                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                    .color(theme.colors.text_subtle),
+            ));
+            return;
+        }
+
+        egui::Grid::new("operator_sessions")
+            .num_columns(5)
+            .spacing(egui::vec2(24.0 * scale, 8.0 * scale))
+            .show(ui, |ui| {
+                for header in ["member.date", "operator.connector_column", "operator.payment_column", "member.energy", "member.total"] {
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t(header))
+                            .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                            .color(theme.colors.text_subtle),
+                    ));
+                }
+                ui.end_row();
+
+                for session in &self.sessions {
+                    let cells = [
+                        session.ended_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+                        session.connector_id.to_string(),
+                        session.payment_method.label(),
+                        format!("{:.2} kWh", session.energy_kwh),
+                        self.tariff.format_amount(session.total),
+                    ];
+                    for cell in cells {
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(cell)
+                                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                .color(theme.colors.text),
+                        ));
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+impl Screen for OperatorScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
+        self.load_background_image(ctx);
+
+        // 터치/키 입력이 있으면 자동 종료 시간 연장
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.any_down()) {
+            self.last_activity = Instant::now();
+        }
+
+        let scale = calculate_scale(ctx);
+
+        show_top_bar(ctx, scale, None);
+
+        // AppBar 표시
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                if self.app_bar.show(ui, scale) {
+                    action = Some(ScreenAction::Back);
+                }
+            });
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                let screen_rect = ui.max_rect();
+
+                // 배경 이미지 또는 색상
+                if let Some(ref texture) = self.background_image {
+                    ui.painter().image(
+                        texture.id(),
+                        screen_rect,
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+                } else {
+                    let bg_color = theme.colors.background;
+                    ui.painter().rect_filled(screen_rect, 0.0, bg_color);
+                }
+
+                ui.add_space(20.0 * scale);
+
+                if self.unlocked {
+                    if self.show_operator_area(ui, scale) {
+                        action = Some(ScreenAction::Restart);
+                    }
+                } else {
+                    self.show_pin_pad(ui, scale);
+                }
+            });
+
+        ctx.request_repaint_after(Duration::from_millis(16));
+
+        // 기술자가 자리를 비우면 대기 화면으로
+        if action.is_none() && self.is_timed_out() {
+            println!("Operator screen idle, returning to standby");
+            return Some(ScreenAction::Back);
+        }
+        action
+    }
+}
@@ -0,0 +1,197 @@
+use eframe::egui;
+use std::time::Duration;
+use std::path::PathBuf;
+use crate::theme;
+use crate::layout::top_bar::show_top_bar;
+use crate::layout::app_bar::AppBar;
+use crate::layout::stepper::journey_stepper;
+use crate::layout::app_container::calculate_scale;
+use crate::screen::payment_screen::PaymentMethod;
+use crate::payment::PaymentError;
+use crate::i18n;
+use crate::screen::{Screen, ScreenAction};
+
+pub struct PaymentFailedScreen {
+    payment_method: PaymentMethod,
+    error: PaymentError,
+    background_image_path: Option<PathBuf>,
+    background_image: Option<egui::TextureHandle>,
+    app_bar: AppBar,
+}
+
+impl PaymentFailedScreen {
+    pub fn new(payment_method: PaymentMethod, error: PaymentError) -> Self {
+        Self {
+            payment_method,
+            error,
+            background_image_path: None,
+            background_image: None,
+            app_bar: AppBar::new("payment_failed.title").with_back_button(),
+        }
+    }
+
+    pub fn with_background_image(mut self, image_path: PathBuf) -> Self {
+        self.background_image_path = Some(image_path);
+        self
+    }
+
+    fn load_background_image(&mut self, ctx: &egui::Context) {
+        if let Some(ref path) = self.background_image_path {
+            if self.background_image.is_none() {
+                if let Ok(image_data) = std::fs::read(path) {
+                    if let Ok(image) = image::load_from_memory(&image_data) {
+                        let rgba_image = image.to_rgba8();
+                        let size = [rgba_image.width() as usize, rgba_image.height() as usize];
+                        let pixels = rgba_image.into_raw();
+                        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+                        self.background_image = Some(ctx.load_texture("payment_failed_background", color_image, Default::default()));
+                    }
+                }
+            }
+        }
+    }
+
+    fn headline(&self) -> (String, String) {
+        match self.error {
+            PaymentError::Declined(ref reason) => (
+                i18n::t("payment_failed.declined"),
+                i18n::t_args("payment_failed.declined_message", &[("reason", reason)]),
+            ),
+            PaymentError::Timeout => (
+                i18n::t("payment_failed.timeout"),
+                i18n::t("payment_failed.timeout_message"),
+            ),
+            PaymentError::Communication(_) => (
+                i18n::t("payment_failed.communication"),
+                i18n::t("payment_failed.communication_message"),
+            ),
+            PaymentError::Cancelled => (
+                i18n::t("payment_failed.cancelled"),
+                i18n::t("payment_failed.cancelled_message"),
+            ),
+        }
+    }
+}
+
+impl Screen for PaymentFailedScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
+        self.load_background_image(ctx);
+
+        let scale = calculate_scale(ctx);
+
+        show_top_bar(ctx, scale, None);
+
+        // AppBar 표시
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                // 뒤로 가기는 "다른 결제 수단 선택"과 같음
+                if self.app_bar.show(ui, scale) {
+                    action = Some(ScreenAction::ChooseAnotherMethod);
+                }
+            });
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| {
+                let screen_rect = ui.max_rect();
+
+                // 배경 이미지 또는 색상
+                if let Some(ref texture) = self.background_image {
+                    ui.painter().image(
+                        texture.id(),
+                        screen_rect,
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+                } else {
+                    let bg_color = theme.colors.background;
+                    ui.painter().rect_filled(screen_rect, 0.0, bg_color);
+                }
+
+                ui.add_space(20.0 * scale);
+
+                // 진행 단계
+                journey_stepper(ui, 2, scale);
+
+                ui.vertical_centered(|ui| {
+                    ui.add_space(20.0 * scale);
+
+                    let (title, message) = self.headline();
+                    let title_color = match self.error {
+                        PaymentError::Cancelled => theme.colors.warning,
+                        _ => theme.colors.danger,
+                    };
+
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(title)
+                            .font(egui::FontId::proportional(theme.font_sizes.headline * scale))
+                            .color(title_color),
+                    ));
+
+                    ui.add_space(20.0 * scale);
+
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(message)
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
+                    ));
+
+                    ui.add_space(10.0 * scale);
+
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(i18n::t_args("payment_failed.method", &[("method", &self.payment_method.label())]))
+                            .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                            .color(theme.colors.text_subtle),
+                    ));
+
+                    ui.add_space(40.0 * scale);
+
+                    // 다시 시도
+                    let retry_btn = egui::Button::new(
+                        egui::RichText::new(i18n::t("payment_failed.retry"))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
+                    )
+                    .min_size(egui::vec2(250.0 * scale, 50.0 * scale))
+                    .fill(theme.colors.success)
+                    .corner_radius(egui::CornerRadius::same(theme.radii.button));
+
+                    let resp_retry = ui.add(retry_btn);
+                    if resp_retry.hovered() || resp_retry.is_pointer_button_down_on() {
+                        let glow_rect = resp_retry.rect.expand(8.0 * scale);
+                        let glow_shape = egui::epaint::RectShape::filled(
+                            glow_rect,
+                            theme.glow_radius(scale),
+                            theme.glow(theme.colors.success),
+                        ).with_blur_width(12.0 * scale);
+                        ui.painter().add(glow_shape);
+                    }
+                    if resp_retry.clicked() {
+                        action = Some(ScreenAction::RetryPayment);
+                    }
+
+                    ui.add_space(15.0 * scale);
+
+                    // 다른 결제 수단 선택
+                    let change_btn = egui::Button::new(
+                        egui::RichText::new(i18n::t("payment_failed.change_method"))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
+                    )
+                    .min_size(egui::vec2(250.0 * scale, 50.0 * scale))
+                    .fill(theme.colors.secondary)
+                    .corner_radius(egui::CornerRadius::same(theme.radii.button));
+
+                    if ui.add(change_btn).clicked() {
+                        action = Some(ScreenAction::ChooseAnotherMethod);
+                    }
+                });
+            });
+
+        ctx.request_repaint_after(Duration::from_millis(16));
+        action
+    }
+}
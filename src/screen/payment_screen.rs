@@ -1,13 +1,25 @@
 use eframe::egui;
 use std::time::{Duration, Instant};
 use std::path::PathBuf;
+use std::sync::Arc;
+use crate::theme;
 use crate::layout::top_bar::show_top_bar;
 use crate::layout::app_bar::AppBar;
 use crate::layout::app_container::calculate_scale;
-// use crate::layout::stepper::stepper;
+use crate::layout::stepper::journey_stepper;
 use crate::screen::select_amount_screen::ChargeType;
-
-#[derive(Debug, Clone, PartialEq)]
+use crate::tariff::Tariff;
+use crate::config::PaymentConfig;
+use crate::payment::{Authorization, PaymentError, PaymentRequest, SharedPaymentProvider};
+use crate::auth::{CardRead, IdToken};
+use crate::layout::card_notice::card_notice;
+use crate::i18n;
+use crate::screen::{Screen, ScreenAction};
+
+// Held on top of the estimate so a rate change or a longer session is still covered
+const PRE_AUTH_MARGIN: f64 = 1.2;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum PaymentMethod {
     CreditCard,
     MobileApp,
@@ -15,31 +27,69 @@ pub enum PaymentMethod {
     Membership,
 }
 
+impl PaymentMethod {
+    /// Name shown to the customer, with its icon
+    pub fn label(&self) -> String {
+        i18n::t(match self {
+            PaymentMethod::CreditCard => "payment_method.credit_card",
+            PaymentMethod::MobileApp => "payment_method.mobile_app",
+            PaymentMethod::RFID => "payment_method.rfid",
+            PaymentMethod::Membership => "payment_method.membership",
+        })
+    }
+}
+
 pub struct PaymentScreen {
     start_time: Instant,
     charge_type: ChargeType,
     charge_amount: f32,
+    tariff: Arc<Tariff>,
+    payment: SharedPaymentProvider,
+    full_charge_hold: f64,
     selected_payment: Option<PaymentMethod>,
+    pending_authorization: Option<PaymentRequest<Authorization>>,
+    authorization: Option<Authorization>,
+    failure: Option<(PaymentMethod, PaymentError)>,
+    id_token: Option<IdToken>,
+    card_read: Option<CardRead>,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
-    proceed_clicked: bool,
     app_bar: AppBar,
 }
 
 impl PaymentScreen {
-    pub fn new(charge_type: ChargeType, charge_amount: f32) -> Self {
+    pub fn new(charge_type: ChargeType, charge_amount: f32, tariff: Arc<Tariff>, payment: SharedPaymentProvider) -> Self {
         Self {
             start_time: Instant::now(),
             charge_type,
             charge_amount,
+            tariff,
+            payment,
+            full_charge_hold: PaymentConfig::default().full_charge_hold,
             selected_payment: None,
+            pending_authorization: None,
+            authorization: None,
+            failure: None,
+            id_token: None,
+            card_read: None,
             background_image_path: None,
             background_image: None,
-            proceed_clicked: false,
-            app_bar: AppBar::new("Select Payment Method").with_back_button(),
+            app_bar: AppBar::new("payment.title").with_back_button(),
         }
     }
 
+    /// Amount held for a full charge instead of an estimate
+    pub fn with_full_charge_hold(mut self, amount: f64) -> Self {
+        self.full_charge_hold = amount;
+        self
+    }
+
+    /// Customer already identified by a card, e.g. on the standby screen
+    pub fn with_id_token(mut self, token: IdToken) -> Self {
+        self.id_token = Some(token);
+        self
+    }
+
     pub fn with_background_image(mut self, image_path: PathBuf) -> Self {
         self.background_image_path = Some(image_path);
         self
@@ -61,36 +111,95 @@ impl PaymentScreen {
         }
     }
 
-    pub fn get_selected_payment(&self) -> Option<PaymentMethod> {
-        self.selected_payment.clone()
+    /// A card tapped at the reader. An accepted card pays straight away: from
+    /// the member balance if Membership is selected, otherwise by RFID.
+    pub fn card_presented(&mut self, read: CardRead) {
+        if read.is_accepted() && !self.is_authorizing() {
+            self.id_token = Some(read.token.clone());
+            let method = match self.selected_payment {
+                Some(PaymentMethod::Membership) => PaymentMethod::Membership,
+                _ => PaymentMethod::RFID,
+            };
+            self.retry(method);
+        }
+        self.card_read = Some(read);
     }
 
-    pub fn is_proceed_clicked(&self) -> bool {
-        self.proceed_clicked
+    /// Try the same method again, e.g. after a terminal timeout
+    pub fn retry(&mut self, method: PaymentMethod) {
+        self.selected_payment = Some(method.clone());
+        self.start_authorization(method);
     }
 
-    pub fn reset_proceed_clicked(&mut self) {
-        self.proceed_clicked = false;
+    pub fn choose_another_method(&mut self) {
+        self.selected_payment = None;
     }
 
-    pub fn is_back_clicked(&self) -> bool {
-        self.app_bar.is_back_clicked()
+    fn is_authorizing(&self) -> bool {
+        self.pending_authorization.is_some()
     }
 
-    pub fn reset_back_clicked(&mut self) {
-        self.app_bar.reset_back_clicked();
+    fn pre_authorization_amount(&self) -> f64 {
+        match self.charge_type {
+            ChargeType::Full => self.full_charge_hold,
+            _ => (self.calculate_cost() * PRE_AUTH_MARGIN).ceil(),
+        }
     }
 
-    fn calculate_cost(&self) -> f32 {
-        // Simple cost calculation (assuming 200 won per kWh)
-        match self.charge_type {
-            ChargeType::SpecificWatts(watts) => watts * 0.2, // 1kW = 200 won
-            ChargeType::Percent(percent) => percent * 0.1,   // 1% = 100 won
+    // RFID and membership payments are made with a card tapped at the reader
+    fn needs_card(&self) -> bool {
+        matches!(self.selected_payment, Some(PaymentMethod::RFID | PaymentMethod::Membership)) && self.id_token.is_none()
+    }
+
+    fn start_authorization(&mut self, method: PaymentMethod) {
+        let amount = self.pre_authorization_amount();
+        let id_token = self.id_token.clone();
+        self.failure = None;
+        self.pending_authorization = Some(PaymentRequest::spawn(self.payment.clone(), move |provider| {
+            provider.pre_authorize(&method, id_token.as_ref(), amount)
+        }));
+    }
+
+    fn cancel_authorization(&mut self) {
+        if let Some(request) = self.pending_authorization.take() {
+            request.cancel(self.payment.clone());
+            if let Some(method) = self.selected_payment.clone() {
+                println!("Payment cancelled by user");
+                self.failure = Some((method, PaymentError::Cancelled));
+            }
         }
     }
 
-    pub fn show(&mut self, ctx: &egui::Context) {
+    fn poll_authorization(&mut self) {
+        let Some(result) = self.pending_authorization.as_ref().and_then(PaymentRequest::poll) else {
+            return;
+        };
+        self.pending_authorization = None;
+        match result {
+            Ok(authorization) => {
+                println!("Payment authorized: {} ({})", authorization.id, self.tariff.format_amount(authorization.amount));
+                self.authorization = Some(authorization);
+            }
+            Err(err) => {
+                println!("Payment failed: {}", err);
+                if let Some(method) = self.selected_payment.clone() {
+                    self.failure = Some((method, err));
+                }
+            }
+        }
+    }
+
+    fn calculate_cost(&self) -> f64 {
+        self.tariff.estimate(&self.charge_type, self.charge_amount).total
+    }
+}
+
+impl Screen for PaymentScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
         self.load_background_image(ctx);
+        self.poll_authorization();
 
         let scale = calculate_scale(ctx);
 
@@ -100,8 +209,14 @@ impl PaymentScreen {
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
             .show(ctx, |ui| {
-                self.app_bar.show(ui, scale);
+                if self.app_bar.show(ui, scale) {
+                    action = Some(ScreenAction::Back);
+                }
             });
+        // The terminal is already holding the card; leaving now would orphan the hold
+        if self.is_authorizing() {
+            action = None;
+        }
 
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
@@ -117,77 +232,105 @@ impl PaymentScreen {
                         egui::Color32::WHITE,
                     );
                 } else {
-                    let bg_color = egui::Color32::from_rgba_premultiplied(25, 35, 55, 255);
+                    let bg_color = theme.colors.background;
                     ui.painter().rect_filled(screen_rect, 0.0, bg_color);
                 }
 
                 ui.add_space(20.0 * scale);
 
+                // 진행 단계
+                journey_stepper(ui, 2, scale);
+
                 // 제목
                 ui.vertical_centered(|ui| {
-                    ui.add_space(30.0 * scale);
+                    ui.add_space(10.0 * scale);
 
                     ui.add(egui::Label::new(
-                        egui::RichText::new("Select Payment Method")
-                            .font(egui::FontId::proportional(28.0 * scale))
-                            .color(egui::Color32::WHITE),
+                        egui::RichText::new(i18n::t("payment.title"))
+                            .font(egui::FontId::proportional(theme.font_sizes.title * scale))
+                            .color(theme.colors.text),
                     ));
 
                     ui.add_space(20.0 * scale);
 
                     // 충전 정보 요약
                     let charge_info = match self.charge_type {
-                        ChargeType::SpecificWatts(watts) => format!("Charging: {:.0} kW", watts),
-                        ChargeType::Percent(percent) => format!("Target: {:.0}%", percent),
+                        ChargeType::SpecificWatts(_) => {
+                            i18n::t_args("payment.charging_kwh", &[("amount", &format!("{:.0}", self.charge_amount))])
+                        }
+                        ChargeType::Percent(_) => {
+                            i18n::t_args("payment.target", &[("percent", &format!("{:.0}", self.charge_amount))])
+                        }
+                        ChargeType::Full => i18n::t("payment.full"),
                     };
 
                     ui.add(egui::Label::new(
                         egui::RichText::new(charge_info)
-                            .font(egui::FontId::proportional(18.0 * scale))
-                            .color(egui::Color32::from_gray(200)),
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text_muted),
                     ));
 
-                    let cost = self.calculate_cost();
+                    // 완전 충전은 예상 요금 대신 사전승인 금액 안내
+                    let cost_info = match self.charge_type {
+                        ChargeType::Full => i18n::t_args(
+                            "payment.full_hold",
+                            &[("amount", &self.tariff.format_amount(self.pre_authorization_amount()))],
+                        ),
+                        _ => i18n::t_args("payment.estimated_cost", &[("cost", &self.tariff.format_amount(self.calculate_cost()))]),
+                    };
+                    ui.add(egui::Label::new(
+                        egui::RichText::new(cost_info)
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.warning),
+                    ));
+
+                    // 현재 적용 요금 (시간대/계절별)
+                    let rate = self.tariff.rate_at(chrono::Local::now());
                     ui.add(egui::Label::new(
-                        egui::RichText::new(format!("Estimated Cost: {:.0} KRW", cost))
-                            .font(egui::FontId::proportional(18.0 * scale))
-                            .color(egui::Color32::from_rgb(255, 200, 100)),
+                        egui::RichText::new(i18n::t_args(
+                            "payment.current_rate",
+                            &[("price", &self.tariff.format_amount(rate.price_per_kwh)), ("period", &rate.label())],
+                        ))
+                        .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                        .color(theme.colors.text_subtle),
                     ));
 
                     ui.add_space(30.0 * scale);
 
                     // 결제 방법 선택
                     let payment_methods = vec![
-                        (PaymentMethod::CreditCard, "💳 Credit Card", "Insert or tap your card"),
-                        (PaymentMethod::MobileApp, "📱 Mobile App", "Scan QR code with your app"),
-                        (PaymentMethod::RFID, "🔑 RFID Card", "Tap your RFID card"),
-                        (PaymentMethod::Membership, "🎫 Membership", "Use membership benefits"),
+                        (PaymentMethod::CreditCard, "payment.credit_card_hint"),
+                        (PaymentMethod::MobileApp, "payment.mobile_app_hint"),
+                        (PaymentMethod::RFID, "payment.rfid_hint"),
+                        (PaymentMethod::Membership, "payment.membership_hint"),
                     ];
 
-                    for (method, title, description) in payment_methods {
+                    for (method, description_key) in payment_methods {
+                        let title = method.label();
+                        let description = i18n::t(description_key);
                         let is_selected = self.selected_payment == Some(method.clone());
                         let btn_color = if is_selected {
-                            egui::Color32::from_rgb(20, 180, 120)
+                            theme.colors.success
                         } else {
-                            egui::Color32::from_rgba_premultiplied(60, 60, 80, 255)
+                            theme.colors.secondary
                         };
 
                         let btn = egui::Button::new("")
                             .min_size(egui::vec2(400.0 * scale, 60.0 * scale))
                             .fill(btn_color)
-                            .corner_radius(egui::CornerRadius::same(10));
+                            .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                         let resp = ui.add(btn);
                         if resp.hovered() || resp.is_pointer_button_down_on() {
                             let glow_rect = resp.rect.expand(6.0 * scale);
                             let glow_shape = egui::epaint::RectShape::filled(
                                 glow_rect,
-                                egui::CornerRadius::same((12.0 * scale) as u8),
-                                egui::Color32::from_rgba_premultiplied(100, 100, 120, 80),
+                                theme.glow_radius(scale),
+                                theme.glow(theme.colors.secondary),
                             ).with_blur_width(10.0 * scale);
                             ui.painter().add(glow_shape);
                         }
-                        if resp.clicked() {
+                        if resp.clicked() && !self.is_authorizing() {
                             self.selected_payment = Some(method);
                         }
 
@@ -200,16 +343,16 @@ impl PaymentScreen {
                             text_pos,
                             egui::Align2::LEFT_CENTER,
                             title,
-                            egui::FontId::proportional(18.0 * scale),
-                            egui::Color32::WHITE,
+                            egui::FontId::proportional(theme.font_sizes.large * scale),
+                            theme.colors.text,
                         );
                         
                         ui.painter().text(
                             desc_pos,
                             egui::Align2::LEFT_CENTER,
                             description,
-                            egui::FontId::proportional(14.0 * scale),
-                            egui::Color32::from_gray(200),
+                            egui::FontId::proportional(theme.font_sizes.caption * scale),
+                            theme.colors.text_muted,
                         );
                         
                         if is_selected {
@@ -217,9 +360,9 @@ impl PaymentScreen {
                             ui.painter().text(
                                 check_pos,
                                 egui::Align2::CENTER_CENTER,
-                                "OK",
-                                egui::FontId::proportional(24.0 * scale),
-                                egui::Color32::WHITE,
+                                i18n::t("payment.selected"),
+                                egui::FontId::proportional(theme.font_sizes.heading * scale),
+                                theme.colors.text,
                             );
                         }
 
@@ -228,40 +371,98 @@ impl PaymentScreen {
 
                     ui.add_space(30.0 * scale);
 
+                    // RFID 카드 인식 결과 / 카드 태그 안내
+                    if let Some(read) = self.card_read.as_ref().filter(|read| read.is_recent()) {
+                        card_notice(ui, read, scale);
+                        ui.add_space(10.0 * scale);
+                    } else if self.needs_card() {
+                        let prompt = match self.selected_payment {
+                            Some(PaymentMethod::Membership) => i18n::t("payment.tap_membership"),
+                            _ => i18n::t("payment.tap_rfid"),
+                        };
+                        ui.add(egui::Label::new(
+                            egui::RichText::new(prompt)
+                                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                .color(theme.colors.warning),
+                        ));
+                        ui.add_space(10.0 * scale);
+                    }
+
+                    // 결제 진행 상태 (실패는 별도 화면)
+                    if self.is_authorizing() {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Spinner::new().size(20.0 * scale));
+                            ui.add(egui::Label::new(
+                                egui::RichText::new(i18n::t_args(
+                                    "payment.authorizing",
+                                    &[("amount", &self.tariff.format_amount(self.pre_authorization_amount()))],
+                                ))
+                                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                .color(theme.colors.text_muted),
+                            ));
+                        });
+                        ui.add_space(10.0 * scale);
+
+                        let cancel_btn = egui::Button::new(
+                            egui::RichText::new(i18n::t("common.cancel"))
+                                .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                .color(theme.colors.text),
+                        )
+                        .min_size(egui::vec2(150.0 * scale, 40.0 * scale))
+                        .fill(theme.colors.secondary)
+                        .corner_radius(egui::CornerRadius::same(theme.radii.button));
+                        if ui.add(cancel_btn).clicked() {
+                            self.cancel_authorization();
+                        }
+                        ui.add_space(10.0 * scale);
+                    }
+
                     // 진행 버튼 (결제 방법이 선택되었을 때만 활성화)
-                    let can_proceed = self.selected_payment.is_some();
+                    // RFID/회원 결제는 카드를 태그해야 진행 가능
+                    let can_proceed = self.selected_payment.is_some() && !self.is_authorizing() && !self.needs_card();
                     let proceed_color = if can_proceed {
-                        egui::Color32::from_rgb(20, 180, 120)
+                        theme.colors.success
                     } else {
-                        egui::Color32::from_gray(100)
+                        theme.colors.disabled
                     };
 
                     let proceed_btn = egui::Button::new(
-                        egui::RichText::new("Start Charging")
-                            .font(egui::FontId::proportional(18.0 * scale))
-                            .color(egui::Color32::WHITE),
+                        egui::RichText::new(i18n::t("payment.start"))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
                     )
                     .min_size(egui::vec2(250.0 * scale, 50.0 * scale))
                     .fill(proceed_color)
-                    .corner_radius(egui::CornerRadius::same(10));
+                    .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                     let resp_proceed = ui.add(proceed_btn);
                     if can_proceed && (resp_proceed.hovered() || resp_proceed.is_pointer_button_down_on()) {
                         let glow_rect = resp_proceed.rect.expand(8.0 * scale);
                         let glow_shape = egui::epaint::RectShape::filled(
                             glow_rect,
-                            egui::CornerRadius::same((12.0 * scale) as u8),
-                            egui::Color32::from_rgba_premultiplied(20, 180, 120, 80),
+                            theme.glow_radius(scale),
+                            theme.glow(theme.colors.success),
                         ).with_blur_width(12.0 * scale);
                         ui.painter().add(glow_shape);
                     }
                     if resp_proceed.clicked() && can_proceed {
-                        self.proceed_clicked = true;
+                        if let Some(method) = self.selected_payment.clone() {
+                            self.start_authorization(method);
+                        }
                     }
 
                 });
             });
 
         ctx.request_repaint_after(Duration::from_millis(16));
+
+        // 승인되면 충전으로, 실패하면 실패 화면으로 (한 번만 전달)
+        if let Some(authorization) = self.authorization.take() {
+            return Some(ScreenAction::PaymentApproved(authorization));
+        }
+        if let Some((method, error)) = self.failure.take() {
+            return Some(ScreenAction::PaymentFailed(method, error));
+        }
+        action
     }
 }
@@ -1,15 +1,20 @@
 use eframe::egui;
 use std::time::{Duration, Instant};
 use std::path::PathBuf;
+use crate::theme;
 use crate::layout::top_bar::show_top_bar;
 use crate::layout::app_bar::AppBar;
 use crate::layout::app_container::calculate_scale;
-// use crate::layout::stepper::stepper;
+use crate::i18n;
+use crate::router::AppState;
+use crate::screen::{Screen, ScreenAction};
+use crate::layout::stepper::journey_stepper;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum ChargeType {
     SpecificWatts(f32),  // 특정 와트로 충전
     Percent(f32),        // 특정 퍼센트까지 충전
+    Full,                // 완전 충전 (설정된 금액을 사전승인)
 }
 
 pub struct SelectAmountScreen {
@@ -18,7 +23,6 @@ pub struct SelectAmountScreen {
     input_value: String,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
-    proceed_clicked: bool,
     app_bar: AppBar,
 }
 
@@ -26,12 +30,12 @@ impl SelectAmountScreen {
     pub fn new(charge_type: ChargeType) -> Self {
         let initial_value = match charge_type {
             ChargeType::SpecificWatts(_) => "50".to_string(),
-            ChargeType::Percent(_) => "80".to_string(),
+            ChargeType::Percent(_) | ChargeType::Full => "80".to_string(),
         };
 
         let title = match charge_type {
-            ChargeType::SpecificWatts(_) => "Select Charging Amount(kW)",
-            ChargeType::Percent(_) => "Select Target Battery Level",
+            ChargeType::SpecificWatts(_) => "select_amount.title_watts",
+            ChargeType::Percent(_) | ChargeType::Full => "select_amount.title_percent",
         };
 
         Self {
@@ -40,7 +44,6 @@ impl SelectAmountScreen {
             input_value: initial_value,
             background_image_path: None,
             background_image: None,
-            proceed_clicked: false,
             app_bar: AppBar::new(title).with_back_button(),
         }
     }
@@ -73,24 +76,12 @@ impl SelectAmountScreen {
     pub fn get_input_value(&self) -> f32 {
         self.input_value.parse().unwrap_or(0.0)
     }
+}
 
-    pub fn is_proceed_clicked(&self) -> bool {
-        self.proceed_clicked
-    }
-
-    pub fn reset_proceed_clicked(&mut self) {
-        self.proceed_clicked = false;
-    }
-
-    pub fn is_back_clicked(&self) -> bool {
-        self.app_bar.is_back_clicked()
-    }
-
-    pub fn reset_back_clicked(&mut self) {
-        self.app_bar.reset_back_clicked();
-    }
-
-    pub fn show(&mut self, ctx: &egui::Context) {
+impl Screen for SelectAmountScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
         self.load_background_image(ctx);
 
         let scale = calculate_scale(ctx);
@@ -101,7 +92,9 @@ impl SelectAmountScreen {
         egui::CentralPanel::default()
             .frame(egui::Frame::NONE)
             .show(ctx, |ui| {
-                self.app_bar.show(ui, scale);
+                if self.app_bar.show(ui, scale) {
+                    action = Some(ScreenAction::Back);
+                }
             });
 
         egui::CentralPanel::default()
@@ -118,25 +111,28 @@ impl SelectAmountScreen {
                         egui::Color32::WHITE,
                     );
                 } else {
-                    let bg_color = egui::Color32::from_rgba_premultiplied(25, 35, 55, 255);
+                    let bg_color = theme.colors.background;
                     ui.painter().rect_filled(screen_rect, 0.0, bg_color);
                 }
 
                 ui.add_space(20.0 * scale);
 
+                // 진행 단계
+                journey_stepper(ui, 0, scale);
+
                 // 제목
                 ui.vertical_centered(|ui| {
-                    ui.add_space(40.0 * scale);
+                    ui.add_space(10.0 * scale);
 
                     let title = match self.charge_type {
-                        ChargeType::SpecificWatts(_) => "Select Charging Amount (kW)",
-                        ChargeType::Percent(_) => "Select Target Battery Level (%)",
+                        ChargeType::SpecificWatts(_) => i18n::t("select_amount.heading_watts"),
+                        ChargeType::Percent(_) | ChargeType::Full => i18n::t("select_amount.heading_percent"),
                     };
 
                     ui.add(egui::Label::new(
                         egui::RichText::new(title)
-                            .font(egui::FontId::proportional(28.0 * scale))
-                            .color(egui::Color32::WHITE),
+                            .font(egui::FontId::proportional(theme.font_sizes.title * scale))
+                            .color(theme.colors.text),
                     ));
 
                     ui.add_space(30.0 * scale);
@@ -150,7 +146,7 @@ impl SelectAmountScreen {
                         let input_height = 50.0 * scale;
 
                         let text_edit = egui::TextEdit::singleline(&mut self.input_value)
-                            .font(egui::FontId::proportional(24.0 * scale))
+                            .font(egui::FontId::proportional(theme.font_sizes.heading * scale))
                             .desired_width(input_width)
                             .desired_rows(1);
 
@@ -159,15 +155,15 @@ impl SelectAmountScreen {
                         // 단위 표시
                         let unit = match self.charge_type {
                             ChargeType::SpecificWatts(_) => "kW",
-                            ChargeType::Percent(_) => "%",
+                            ChargeType::Percent(_) | ChargeType::Full => "%",
                         };
 
                         ui.vertical_centered(|ui| {
                             ui.add_space(15.0 * scale);
                             ui.add(egui::Label::new(
                                 egui::RichText::new(unit)
-                                    .font(egui::FontId::proportional(20.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                    .font(egui::FontId::proportional(theme.font_sizes.subheading * scale))
+                                    .color(theme.colors.text),
                             ));
                         });
                     });
@@ -182,7 +178,7 @@ impl SelectAmountScreen {
                             ("75 kW", "75"),
                             ("100 kW", "100"),
                         ],
-                        ChargeType::Percent(_) => vec![
+                        ChargeType::Percent(_) | ChargeType::Full => vec![
                             ("50%", "50"),
                             ("80%", "80"),
                             ("90%", "90"),
@@ -191,9 +187,9 @@ impl SelectAmountScreen {
                     };
 
                     ui.add(egui::Label::new(
-                        egui::RichText::new("Quick Select")
-                            .font(egui::FontId::proportional(18.0 * scale))
-                            .color(egui::Color32::from_gray(200)),
+                        egui::RichText::new(i18n::t("select_amount.quick_select"))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text_muted),
                     ));
 
                     ui.add_space(15.0 * scale);
@@ -205,20 +201,20 @@ impl SelectAmountScreen {
                         for (label, value) in preset_options {
                             let btn = egui::Button::new(
                                 egui::RichText::new(label)
-                                    .font(egui::FontId::proportional(16.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text),
                             )
                             .min_size(egui::vec2(80.0 * scale, 40.0 * scale))
-                            .fill(egui::Color32::from_rgba_premultiplied(60, 60, 80, 255))
-                            .corner_radius(egui::CornerRadius::same(8));
+                            .fill(theme.colors.secondary)
+                            .corner_radius(egui::CornerRadius::same(theme.radii.small));
 
                             let resp = ui.add(btn);
                             if resp.hovered() || resp.is_pointer_button_down_on() {
                                 let glow_rect = resp.rect.expand(4.0 * scale);
                                 let glow_shape = egui::epaint::RectShape::filled(
                                     glow_rect,
-                                    egui::CornerRadius::same((8.0 * scale) as u8),
-                                    egui::Color32::from_rgba_premultiplied(100, 100, 120, 80),
+                                    egui::CornerRadius::same((theme.radii.small as f32 * scale) as u8),
+                                    theme.glow(theme.colors.secondary),
                                 ).with_blur_width(8.0 * scale);
                                 ui.painter().add(glow_shape);
                             }
@@ -232,31 +228,32 @@ impl SelectAmountScreen {
 
                     // 진행 버튼
                     let proceed_btn = egui::Button::new(
-                        egui::RichText::new("Proceed to Payment")
-                            .font(egui::FontId::proportional(18.0 * scale))
-                            .color(egui::Color32::WHITE),
+                        egui::RichText::new(i18n::t("select_amount.proceed"))
+                            .font(egui::FontId::proportional(theme.font_sizes.large * scale))
+                            .color(theme.colors.text),
                     )
                     .min_size(egui::vec2(250.0 * scale, 50.0 * scale))
-                    .fill(egui::Color32::from_rgb(20, 180, 120))
-                    .corner_radius(egui::CornerRadius::same(10));
+                    .fill(theme.colors.success)
+                    .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                     let resp_proceed = ui.add(proceed_btn);
                     if resp_proceed.hovered() || resp_proceed.is_pointer_button_down_on() {
                         let glow_rect = resp_proceed.rect.expand(8.0 * scale);
                         let glow_shape = egui::epaint::RectShape::filled(
                             glow_rect,
-                            egui::CornerRadius::same((12.0 * scale) as u8),
-                            egui::Color32::from_rgba_premultiplied(20, 180, 120, 80),
+                            theme.glow_radius(scale),
+                            theme.glow(theme.colors.success),
                         ).with_blur_width(12.0 * scale);
                         ui.painter().add(glow_shape);
                     }
                     if resp_proceed.clicked() {
-                        self.proceed_clicked = true;
+                        action = Some(ScreenAction::Navigate(AppState::Connect(self.charge_type.clone(), self.get_input_value())));
                     }
 
                 });
             });
 
         ctx.request_repaint_after(Duration::from_millis(16));
+        action
     }
 }
@@ -1,8 +1,12 @@
 use eframe::egui;
 use std::time::{Duration, Instant};
 use std::path::PathBuf;
+use crate::theme;
 use crate::layout::app_bar::AppBar;
 use crate::layout::app_container::calculate_scale;
+use crate::i18n;
+use crate::router::AppState;
+use crate::screen::{Screen, ScreenAction};
 
 pub struct SplashScreen {
     start_time: Instant,
@@ -10,6 +14,7 @@ pub struct SplashScreen {
     logo_scale: f32,
     text_alpha: f32,
     show_text: bool,
+    duration: Duration,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
     // app_bar: AppBar,
@@ -23,6 +28,7 @@ impl SplashScreen {
             logo_scale: 0.0,
             text_alpha: 0.0,
             show_text: false,
+            duration: Duration::from_millis(4000),
             background_image_path: None,
             background_image: None,
             // app_bar: AppBar::new("EV Charger"),
@@ -34,6 +40,12 @@ impl SplashScreen {
         self
     }
 
+    /// How long the splash stays up, including the one second fade-out
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
     fn load_background_image(&mut self, ctx: &egui::Context) {
         if let Some(ref path) = self.background_image_path {
             if self.background_image.is_none() {
@@ -70,20 +82,23 @@ impl SplashScreen {
             self.text_alpha = text_progress;
         }
         
-        // 전체 페이드아웃 (3초 후 시작, 1초 동안)
-        if elapsed > Duration::from_millis(3000) {
-            let fade_start = 3000.0;
-            let fade_duration = 1000.0;
+        // 전체 페이드아웃 (끝나기 1초 전부터)
+        let fade_duration = 1000.0_f32.min(self.duration.as_millis() as f32);
+        let fade_start = self.duration.as_millis() as f32 - fade_duration;
+        if elapsed.as_millis() as f32 > fade_start {
             let fade_progress = ((elapsed.as_millis() as f32 - fade_start) / fade_duration).min(1.0);
             self.fade_progress = fade_progress;
         }
     }
 
-    pub fn is_finished(&self) -> bool {
-        self.start_time.elapsed() > Duration::from_millis(4000)
+    fn is_finished(&self) -> bool {
+        self.start_time.elapsed() > self.duration
     }
+}
 
-    pub fn show(&mut self, ctx: &egui::Context) {
+impl Screen for SplashScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
         self.update_animation();
         self.load_background_image(ctx);
 
@@ -106,7 +121,7 @@ impl SplashScreen {
                     );
                 } else {
                     // 배경 그라데이션 (fallback)
-                    let bg_color = egui::Color32::from_rgba_premultiplied(20, 30, 50, 255);
+                    let bg_color = theme.colors.background;
                     ui.painter().rect_filled(screen_rect, 0.0, bg_color);
                 }
                 
@@ -121,8 +136,7 @@ impl SplashScreen {
                 
                 // 로딩 바
                 if self.show_text {
-                    let loading_alpha = (255.0 * self.text_alpha) as u8;
-                    let loading_color = egui::Color32::from_rgba_premultiplied(0, 120, 215, loading_alpha);
+                    let loading_color = theme.colors.primary.gamma_multiply(self.text_alpha);
                     
                     let loading_rect = egui::Rect::from_center_size(
                         egui::pos2(screen_rect.center().x, screen_rect.center().y + 150.0),
@@ -130,7 +144,7 @@ impl SplashScreen {
                     );
                     
                     // 로딩 바 배경
-                    let bg_color = egui::Color32::from_rgba_premultiplied(100, 100, 100, loading_alpha);
+                    let bg_color = theme.colors.disabled.gamma_multiply(self.text_alpha);
                     ui.painter().rect_filled(loading_rect, 2.0, bg_color);
                     
                     // 로딩 바 진행률
@@ -144,14 +158,13 @@ impl SplashScreen {
                 
                 // 버전 정보
                 if self.show_text {
-                    let version_alpha = (255.0 * self.text_alpha * 0.7) as u8;
-                    let version_color = egui::Color32::from_rgba_premultiplied(200, 200, 200, version_alpha);
+                    let version_color = theme.colors.text_muted.gamma_multiply(self.text_alpha * 0.7);
                     
                     ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
                         ui.add_space(30.0);
                         ui.add(egui::Label::new(
-                            egui::RichText::new("Version 1.0.0")
-                                .font(egui::FontId::proportional(12.0))
+                            egui::RichText::new(i18n::t_args("splash.version", &[("version", &"1.0.0")]))
+                                .font(egui::FontId::proportional(theme.font_sizes.small))
                                 .color(version_color),
                         ));
                     });
@@ -160,5 +173,8 @@ impl SplashScreen {
 
         // 60fps로 업데이트
         ctx.request_repaint_after(Duration::from_millis(16));
+
+        // 스플래시가 끝나면 연결 화면으로
+        self.is_finished().then_some(ScreenAction::Navigate(AppState::Standby))
     }
 }
@@ -1,8 +1,15 @@
 use eframe::egui;
 use std::time::{Duration, Instant};
 use std::path::PathBuf;
+use crate::theme;
 use crate::layout::top_bar::show_top_bar;
 use crate::layout::app_container::calculate_scale;
+use crate::layout::card_notice::card_notice;
+use crate::auth::CardRead;
+use crate::i18n;
+use crate::router::AppState;
+use crate::screen::{Screen, ScreenAction};
+use crate::screen::select_amount_screen::ChargeType;
 
 pub struct StandbyScreen {
     start_time: Instant,
@@ -12,9 +19,9 @@ pub struct StandbyScreen {
     instruction_alpha: f32,
     background_image_path: Option<PathBuf>,
     background_image: Option<egui::TextureHandle>,
-    full_charge_clicked: bool,
-    specific_watts_clicked: bool,
-    percent_clicked: bool,
+    // 관리자가 커넥터를 사용 중지하면 충전 버튼 대신 안내 표시
+    available: bool,
+    card_read: Option<CardRead>,
 }
 
 impl StandbyScreen {
@@ -27,9 +34,8 @@ impl StandbyScreen {
             instruction_alpha: 1.0,  // Always fully opaque
             background_image_path: None,
             background_image: None,
-            full_charge_clicked: false,
-            specific_watts_clicked: false,
-            percent_clicked: false,
+            available: true,
+            card_read: None,
         }
     }
 
@@ -70,38 +76,29 @@ impl StandbyScreen {
         self.instruction_alpha = 1.0;
     }
 
-    pub fn is_full_charge_clicked(&self) -> bool {
-        self.full_charge_clicked
+    pub fn set_available(&mut self, available: bool) {
+        self.available = available;
     }
 
-    pub fn reset_full_charge_clicked(&mut self) {
-        self.full_charge_clicked = false;
-    }
-
-    pub fn is_specific_watts_clicked(&self) -> bool {
-        self.specific_watts_clicked
-    }
-
-    pub fn reset_specific_watts_clicked(&mut self) {
-        self.specific_watts_clicked = false;
-    }
-
-    pub fn is_percent_clicked(&self) -> bool {
-        self.percent_clicked
-    }
-
-    pub fn reset_percent_clicked(&mut self) {
-        self.percent_clicked = false;
+    /// Show the result of a card tapped at the reader
+    pub fn card_presented(&mut self, read: CardRead) {
+        self.card_read = Some(read);
     }
+}
 
-    pub fn show(&mut self, ctx: &egui::Context) {
+impl Screen for StandbyScreen {
+    fn show(&mut self, ctx: &egui::Context) -> Option<ScreenAction> {
+        let theme = theme::current();
+        let mut action = None;
         self.update_animation();
         self.load_background_image(ctx);
 
         let scale = calculate_scale(ctx);
 
         // Top bar (split)
-        show_top_bar(ctx, scale, None);
+        if show_top_bar(ctx, scale, None) {
+            action = Some(ScreenAction::OpenOperator);
+        }
 
         // 중앙 콘텐츠
         egui::CentralPanel::default()
@@ -109,37 +106,51 @@ impl StandbyScreen {
             .show(ctx, |ui| {
                 
                 // Standby screen content
-                // Stepper: Select -> Payment -> Charging -> Complete
                 ui.add_space(8.0 * scale);
-                // stepper(
-                //     ui,
-                //     &["Select amount", "Payment method", "Charging", "Complete"],
-                //     0,
-                //     scale,
-                // );
                 
                 // spacing under top area
                 ui.add_space(20.0);
                 
                 // 안내 메시지
                 if self.show_instructions {
-                    let instruction_alpha = (255.0 * self.instruction_alpha) as u8;
-                    let _instruction_color = egui::Color32::from_rgba_premultiplied(255, 255, 255, instruction_alpha);
+                    let instruction_alpha = self.instruction_alpha;
+                    let _instruction_color = theme.colors.text.gamma_multiply(instruction_alpha);
                     
                     ui.vertical_centered(|ui| {
                         ui.add_space(30.0 * scale);
 
+                        // RFID 카드 인식 결과
+                        if let Some(read) = self.card_read.as_ref().filter(|read| read.is_recent()) {
+                            card_notice(ui, read, scale);
+                            ui.add_space(10.0 * scale);
+                        }
+
                         let btn_size = egui::vec2(240.0 * scale, 56.0 * scale);
                         
                         ui.vertical_centered(|ui| {
+                            if !self.available {
+                                ui.add(egui::Label::new(
+                                    egui::RichText::new(i18n::t("standby.unavailable"))
+                                        .font(egui::FontId::proportional(theme.font_sizes.heading * scale))
+                                        .color(theme.colors.warning),
+                                ));
+                                ui.add_space(8.0 * scale);
+                                ui.add(egui::Label::new(
+                                    egui::RichText::new(i18n::t("standby.unavailable_hint"))
+                                        .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                        .color(theme.colors.text_muted),
+                                ));
+                                return;
+                            }
+
                             let specific_btn = egui::Button::new(
-                                egui::RichText::new("Charge by specific watts")
-                                    .font(egui::FontId::proportional(16.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                egui::RichText::new(i18n::t("standby.charge_watts"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text),
                             )
                             .min_size(btn_size)
-                            .fill(egui::Color32::from_rgba_premultiplied(35, 140, 240, instruction_alpha))
-                            .corner_radius(egui::CornerRadius::same(10));
+                            .fill(theme.colors.primary.gamma_multiply(instruction_alpha))
+                            .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                             let resp_spec = ui.add(specific_btn);
                             if resp_spec.hovered() || resp_spec.is_pointer_button_down_on() {
@@ -147,26 +158,26 @@ impl StandbyScreen {
                                 let glow_rect = resp_spec.rect.expand(8.0 * scale);
                                 let glow_shape = egui::epaint::RectShape::filled(
                                     glow_rect,
-                                    egui::CornerRadius::same((12.0 * scale) as u8),
-                                    egui::Color32::from_rgba_premultiplied(35, 140, 240, 80),
+                                    theme.glow_radius(scale),
+                                    theme.glow(theme.colors.primary),
                                 ).with_blur_width(12.0 * scale);
                                 ui.painter().add(glow_shape);
                             }
                             if resp_spec.clicked() {
                                 println!("Selected: charge specific watts");
-                                self.specific_watts_clicked = true;
+                                action = Some(ScreenAction::Navigate(AppState::SelectAmount(ChargeType::SpecificWatts(0.0))));
                             }
 
                             ui.add_space(10.0 * scale);
 
                             let percent_btn = egui::Button::new(
-                                egui::RichText::new("Charge by percent")
-                                    .font(egui::FontId::proportional(16.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                egui::RichText::new(i18n::t("standby.charge_percent"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text),
                             )
                             .min_size(btn_size)
-                            .fill(egui::Color32::from_rgba_premultiplied(160, 120, 240, instruction_alpha))
-                            .corner_radius(egui::CornerRadius::same(10));
+                            .fill(theme.colors.accent.gamma_multiply(instruction_alpha))
+                            .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                             let resp_pct = ui.add(percent_btn);
                             if resp_pct.hovered() || resp_pct.is_pointer_button_down_on() {
@@ -174,26 +185,26 @@ impl StandbyScreen {
                                 let glow_rect = resp_pct.rect.expand(8.0 * scale);
                                 let glow_shape = egui::epaint::RectShape::filled(
                                     glow_rect,
-                                    egui::CornerRadius::same((12.0 * scale) as u8),
-                                    egui::Color32::from_rgba_premultiplied(160, 120, 240, 80),
+                                    theme.glow_radius(scale),
+                                    theme.glow(theme.colors.accent),
                                 ).with_blur_width(12.0 * scale);
                                 ui.painter().add(glow_shape);
                             }
                             if resp_pct.clicked() {
                                 println!("Selected: charge by percent");
-                                self.percent_clicked = true;
+                                action = Some(ScreenAction::Navigate(AppState::SelectAmount(ChargeType::Percent(0.0))));
                             }
 
                             ui.add_space(10.0 * scale);
 
                             let full_btn = egui::Button::new(
-                                egui::RichText::new("Full charge")
-                                    .font(egui::FontId::proportional(16.0 * scale))
-                                    .color(egui::Color32::WHITE),
+                                egui::RichText::new(i18n::t("standby.full_charge"))
+                                    .font(egui::FontId::proportional(theme.font_sizes.body * scale))
+                                    .color(theme.colors.text),
                             )
                             .min_size(btn_size)
-                            .fill(egui::Color32::from_rgba_premultiplied(20, 180, 120, instruction_alpha))
-                            .corner_radius(egui::CornerRadius::same(10));
+                            .fill(theme.colors.success.gamma_multiply(instruction_alpha))
+                            .corner_radius(egui::CornerRadius::same(theme.radii.button));
 
                             let resp_full = ui.add(full_btn);
                             if resp_full.hovered() || resp_full.is_pointer_button_down_on() {
@@ -201,14 +212,30 @@ impl StandbyScreen {
                                 let glow_rect = resp_full.rect.expand(8.0 * scale);
                                 let glow_shape = egui::epaint::RectShape::filled(
                                     glow_rect,
-                                    egui::CornerRadius::same((12.0 * scale) as u8),
-                                    egui::Color32::from_rgba_premultiplied(20, 180, 120, 80),
+                                    theme.glow_radius(scale),
+                                    theme.glow(theme.colors.success),
                                 ).with_blur_width(12.0 * scale);
                                 ui.painter().add(glow_shape);
                             }
                             if resp_full.clicked() {
                                 println!("Selected: full charge");
-                                self.full_charge_clicked = true;
+                                action = Some(ScreenAction::Navigate(AppState::Connect(ChargeType::Full, 100.0)));
+                            }
+
+                            ui.add_space(30.0 * scale);
+
+                            // 언어 전환 (다음 언어 이름으로 표시)
+                            let language_btn = egui::Button::new(
+                                egui::RichText::new(i18n::current().next().native_name())
+                                    .font(egui::FontId::proportional(theme.font_sizes.caption * scale))
+                                    .color(theme.colors.text),
+                            )
+                            .min_size(egui::vec2(120.0 * scale, 36.0 * scale))
+                            .fill(theme.colors.secondary.gamma_multiply(instruction_alpha))
+                            .corner_radius(egui::CornerRadius::same(theme.radii.small));
+
+                            if ui.add(language_btn).clicked() {
+                                action = Some(ScreenAction::NextLanguage);
                             }
                         });
                     });
@@ -217,5 +244,6 @@ impl StandbyScreen {
 
         // 60fps로 업데이트
         ctx.request_repaint_after(Duration::from_millis(16));
+        action
     }
 }
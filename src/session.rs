@@ -0,0 +1,345 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local, Utc};
+use crate::hardware::battery;
+use crate::hardware::{HardwareError, MeterReading, SharedCharger};
+use crate::screen::ChargeType;
+use crate::tariff::{Tariff, Usage};
+
+// How often the engine samples the charger, whatever the frame rate
+const TICK_INTERVAL: Duration = Duration::from_millis(200);
+// Battery level rise needed before the pack size is worked out from the meter
+const MIN_SOC_RISE: f32 = 2.0;
+
+/// Why a session stopped delivering energy
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEnd {
+    /// The kWh or SoC target was reached
+    TargetReached,
+    /// The session cost reached the payment hold
+    CostLimit,
+    /// Vehicle stopped drawing, cable pulled or emergency stop
+    VehicleStopped,
+    /// Stopped from the kiosk before the target
+    Stopped,
+    /// The contactor could not be closed
+    Failed(String),
+}
+
+/// What the engine knows about the session, published every tick
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionSnapshot {
+    pub energy_kwh: f32,
+    pub power_kw: f32,
+    pub soc: Option<f32>,
+    /// Towards the target, 0.0 to 1.0 (the battery level for SoC targets)
+    pub progress: f32,
+    pub elapsed: Duration,
+    /// Zero until the rate is known, and once the session has ended
+    pub estimated_time: Duration,
+    pub usage: Usage,
+    pub cost: f64,
+    pub ended: Option<SessionEnd>,
+    /// When energy stopped flowing; the idle time at the connector counts from here
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl SessionSnapshot {
+    pub fn is_finished(&self) -> bool {
+        self.ended.is_some()
+    }
+}
+
+pub type SharedSnapshot = Arc<Mutex<SessionSnapshot>>;
+
+/// Session bookkeeping from meter readings, kept apart from the thread
+struct Tracker {
+    tariff: Arc<Tariff>,
+    charge_type: ChargeType,
+    target: f32,
+    cost_limit: Option<f64>,
+    meter_start_wh: f64,
+    start_soc: Option<f32>,
+    snapshot: SessionSnapshot,
+}
+
+impl Tracker {
+    fn new(tariff: Arc<Tariff>, charge_type: ChargeType, target: f32, cost_limit: Option<f64>) -> Self {
+        Self {
+            tariff,
+            charge_type,
+            target,
+            cost_limit,
+            meter_start_wh: 0.0,
+            start_soc: None,
+            snapshot: SessionSnapshot::default(),
+        }
+    }
+
+    /// The contactor just closed: meter from here
+    fn begin(&mut self, meter: &MeterReading) {
+        self.meter_start_wh = meter.energy_wh;
+        self.start_soc = meter.vehicle_soc;
+    }
+
+    /// Metered kWh that fill the whole battery: from what went in so far once the
+    /// level has risen enough, the tariff's reference pack until then
+    fn pack_kwh(&self, soc: f32) -> f32 {
+        match self.start_soc {
+            Some(start) if soc - start >= MIN_SOC_RISE => self.snapshot.energy_kwh / (soc - start) * 100.0,
+            _ => self.tariff.reference_pack_kwh as f32,
+        }
+    }
+
+    /// Time left if the vehicle follows the usual CC/CV curve from here: the
+    /// power drops above 80%, so the last part of a charge takes the longest
+    fn estimate(&self, value: f32) -> Duration {
+        let power_kw = self.snapshot.power_kw;
+        if power_kw <= 0.0 {
+            return Duration::ZERO;
+        }
+        let remaining_kwh = match self.charge_type {
+            ChargeType::SpecificWatts(_) => self.target - value,
+            ChargeType::Percent(_) | ChargeType::Full => (self.target - value) / 100.0 * self.pack_kwh(value),
+        };
+        match self.snapshot.soc {
+            Some(soc) => battery::time_to_deliver(self.pack_kwh(soc), soc, power_kw, remaining_kwh),
+            None => Duration::from_secs_f32(remaining_kwh.max(0.0) / power_kw * 3600.0),
+        }
+    }
+
+    /// Take a meter reading; returns why the session has to end, if it does
+    fn update(&mut self, meter: &MeterReading, energy_flowing: bool, elapsed: Duration) -> Option<SessionEnd> {
+        let snapshot = &mut self.snapshot;
+        snapshot.energy_kwh = ((meter.energy_wh - self.meter_start_wh) / 1000.0) as f32;
+        snapshot.power_kw = meter.power_w / 1000.0;
+        snapshot.soc = meter.vehicle_soc;
+        snapshot.elapsed = elapsed;
+        snapshot.usage.record(&self.tariff, Local::now(), snapshot.energy_kwh as f64);
+        snapshot.cost = self.tariff.bill(&snapshot.usage, elapsed).total;
+
+        // Progress is measured in kWh for an energy target and in SoC % for a percent target.
+        // Without a battery level from the vehicle, a percent target is that share of the
+        // tariff's reference pack, as quoted at payment.
+        let value = match (&self.charge_type, meter.vehicle_soc) {
+            (ChargeType::SpecificWatts(_), _) => snapshot.energy_kwh,
+            (ChargeType::Percent(_) | ChargeType::Full, Some(soc)) => soc,
+            (ChargeType::Percent(_) | ChargeType::Full, None) => {
+                snapshot.energy_kwh / self.tariff.reference_pack_kwh as f32 * 100.0
+            }
+        };
+        snapshot.progress = match (&self.charge_type, meter.vehicle_soc) {
+            (ChargeType::Percent(_) | ChargeType::Full, Some(soc)) => soc / 100.0,
+            _ if self.target > 0.0 => (value / self.target).min(1.0),
+            _ => 1.0,
+        };
+
+        let end = if value >= self.target {
+            Some(SessionEnd::TargetReached)
+        } else if self.cost_limit.is_some_and(|limit| snapshot.cost >= limit) {
+            Some(SessionEnd::CostLimit)
+        } else if !energy_flowing {
+            Some(SessionEnd::VehicleStopped)
+        } else {
+            None
+        };
+
+        self.snapshot.estimated_time = if end.is_none() { self.estimate(value) } else { Duration::ZERO };
+        end
+    }
+
+    fn end(&mut self, end: SessionEnd) {
+        self.snapshot.estimated_time = Duration::ZERO;
+        self.snapshot.ended = Some(end);
+        self.snapshot.ended_at = Some(Utc::now());
+    }
+}
+
+/// A charging session running on its own thread: closes the contactor once the
+/// vehicle asks for energy, meters and prices what is delivered and stops at the
+/// target or the cost limit. Screens only read its snapshots, so the session
+/// goes on whichever screen is shown. Dropping it stops charging.
+pub struct ChargingSession {
+    snapshot: SharedSnapshot,
+    stop: Option<mpsc::Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ChargingSession {
+    /// Start charging towards `target` (kWh or SoC % depending on `charge_type`),
+    /// never costing more than `cost_limit`
+    pub fn start(
+        charger: SharedCharger,
+        tariff: Arc<Tariff>,
+        charge_type: ChargeType,
+        target: f32,
+        cost_limit: Option<f64>,
+    ) -> Self {
+        let snapshot: SharedSnapshot = Arc::new(Mutex::new(SessionSnapshot::default()));
+        let (stop, stop_requested) = mpsc::channel();
+        let tracker = Tracker::new(tariff, charge_type, target, cost_limit);
+        let published = snapshot.clone();
+        let worker = std::thread::spawn(move || run(charger, tracker, published, stop_requested));
+        Self { snapshot, stop: Some(stop), worker: Some(worker) }
+    }
+
+    /// A session that has already ended, without a thread behind it (for the router tests)
+    #[cfg(all(test, not(feature = "hardware")))]
+    pub fn finished(snapshot: SessionSnapshot) -> Self {
+        Self { snapshot: Arc::new(Mutex::new(snapshot)), stop: None, worker: None }
+    }
+
+    /// Where the published snapshots are, for screens to render
+    pub fn snapshots(&self) -> SharedSnapshot {
+        self.snapshot.clone()
+    }
+
+    pub fn snapshot(&self) -> SessionSnapshot {
+        self.snapshot.lock().unwrap().clone()
+    }
+
+    /// Open the contactor (unless the session already ended) and wait for the last snapshot
+    pub fn stop(&mut self) -> SessionSnapshot {
+        self.stop.take();
+        if let Some(worker) = self.worker.take() {
+            if worker.join().is_err() {
+                println!("Charging session thread panicked");
+            }
+        }
+        self.snapshot()
+    }
+}
+
+impl Drop for ChargingSession {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run(charger: SharedCharger, mut tracker: Tracker, published: SharedSnapshot, stop_requested: mpsc::Receiver<()>) {
+    let mut energy_started_at: Option<Instant> = None;
+    loop {
+        // Any message or a dropped sender asks the session to stop
+        let stopping = match stop_requested.recv_timeout(TICK_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) => false,
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        let finished = {
+            let mut charger = charger.lock().unwrap();
+            charger.poll();
+            let meter = charger.read_meter();
+
+            // Close the contactor once the vehicle asks for energy (state C/D)
+            if energy_started_at.is_none() && !stopping {
+                match charger.start_energy() {
+                    Ok(()) => {
+                        energy_started_at = Some(Instant::now());
+                        tracker.begin(&meter);
+                    }
+                    Err(HardwareError::VehicleNotReady) => {}
+                    Err(err) => {
+                        println!("Cannot start charging: {}", err);
+                        tracker.end(SessionEnd::Failed(err.to_string()));
+                    }
+                }
+            }
+
+            if let Some(started_at) = energy_started_at {
+                if let Some(end) = tracker.update(&meter, charger.is_energy_flowing(), started_at.elapsed()) {
+                    tracker.end(end);
+                }
+            }
+            if stopping && !tracker.snapshot.is_finished() {
+                tracker.end(SessionEnd::Stopped);
+            }
+            if tracker.snapshot.is_finished() {
+                charger.stop_energy();
+            }
+            tracker.snapshot.is_finished()
+        };
+
+        *published.lock().unwrap() = tracker.snapshot.clone();
+        if finished {
+            println!(
+                "Charging session ended ({:?}): {:.2} kWh in {}s",
+                tracker.snapshot.ended,
+                tracker.snapshot.energy_kwh,
+                tracker.snapshot.elapsed.as_secs()
+            );
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(energy_wh: f64, soc: f32) -> MeterReading {
+        MeterReading { energy_wh, power_w: 50000.0, vehicle_soc: Some(soc), ..Default::default() }
+    }
+
+    #[test]
+    fn session_ends_at_the_energy_target() {
+        let mut tracker = Tracker::new(Arc::new(Tariff::default()), ChargeType::SpecificWatts(10.0), 10.0, None);
+        tracker.begin(&reading(1000.0, 20.0));
+
+        assert_eq!(tracker.update(&reading(6000.0, 28.0), true, Duration::from_secs(60)), None);
+        assert_eq!(tracker.snapshot.energy_kwh, 5.0);
+        assert_eq!(tracker.snapshot.progress, 0.5);
+        // 5 kWh left at 50 kW
+        assert_eq!(tracker.snapshot.estimated_time.as_secs_f32().round(), 360.0);
+        assert_eq!(tracker.snapshot.cost, Tariff::default().bill(&tracker.snapshot.usage, Duration::from_secs(60)).total);
+
+        assert_eq!(tracker.update(&reading(11000.0, 36.0), true, Duration::from_secs(120)), Some(SessionEnd::TargetReached));
+        assert_eq!(tracker.snapshot.estimated_time, Duration::ZERO);
+    }
+
+    #[test]
+    fn session_stops_at_the_hold_or_when_the_vehicle_does() {
+        let tariff = Arc::new(Tariff::default());
+        let mut tracker = Tracker::new(tariff.clone(), ChargeType::Full, 100.0, Some(1000.0));
+        tracker.begin(&reading(0.0, 20.0));
+        assert_eq!(tracker.update(&reading(100.0, 21.0), true, Duration::from_secs(10)), None);
+        // 100 kWh costs far more than the hold
+        assert_eq!(tracker.update(&reading(100_000.0, 90.0), true, Duration::from_secs(600)), Some(SessionEnd::CostLimit));
+
+        let mut tracker = Tracker::new(tariff, ChargeType::Percent(80.0), 80.0, None);
+        tracker.begin(&reading(0.0, 20.0));
+        assert_eq!(tracker.update(&reading(5000.0, 40.0), false, Duration::from_secs(60)), Some(SessionEnd::VehicleStopped));
+        assert_eq!(tracker.snapshot.progress, 0.4);
+    }
+
+    #[test]
+    fn percent_target_without_a_battery_level_goes_by_energy() {
+        let tariff = Arc::new(Tariff::default());
+        let mut tracker = Tracker::new(tariff.clone(), ChargeType::Percent(80.0), 80.0, None);
+        let reading = |energy_wh: f64| MeterReading { energy_wh, power_w: 11000.0, vehicle_soc: None, ..Default::default() };
+        tracker.begin(&reading(0.0));
+
+        // 80% of the 64 kWh reference pack is 51.2 kWh
+        assert_eq!(tracker.update(&reading(25_600.0), true, Duration::from_secs(600)), None);
+        assert_eq!(tracker.snapshot.soc, None);
+        assert_eq!(tracker.snapshot.progress, 0.5);
+        assert_eq!(tracker.snapshot.estimated_time.as_secs_f32().round(), (25.6 / 11.0 * 3600.0_f32).round());
+        assert_eq!(tracker.update(&reading(51_200.0), true, Duration::from_secs(1200)), Some(SessionEnd::TargetReached));
+    }
+
+    #[test]
+    fn estimate_allows_for_the_taper_above_eighty_percent() {
+        let estimate = |soc: f32, target: f32| {
+            let mut tracker = Tracker::new(Arc::new(Tariff::default()), ChargeType::Percent(target), target, None);
+            tracker.begin(&reading(0.0, 20.0));
+            // 0.64 kWh per percent, as in a 64 kWh pack
+            tracker.update(&reading((soc - 20.0) as f64 * 640.0, soc), true, Duration::from_secs(600));
+            tracker.snapshot.estimated_time
+        };
+        // 20 percent before the taper go at the full 50 kW: 12.8 kWh in about 15 minutes
+        assert_eq!(estimate(60.0, 80.0).as_secs_f32().round(), 922.0);
+        // The same 20 percent above 80% take far longer
+        assert!(estimate(80.0, 100.0) > estimate(60.0, 80.0) * 2);
+    }
+}
@@ -0,0 +1,421 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Datelike, Local, Timelike, Utc};
+use serde::Deserialize;
+use crate::screen::ChargeType;
+use crate::i18n;
+
+pub const DEFAULT_TARIFF_PATH: &str = "config/tariff.toml";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TariffError {
+    #[error("cannot read tariff: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid tariff: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("invalid tariff: {0}")]
+    Invalid(String),
+}
+
+/// Load band of a time-of-use tariff (경부하 / 중간부하 / 최대부하)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadPeriod {
+    Light,
+    Mid,
+    Peak,
+}
+
+/// Energy prices for a set of months. Hours are local `[start, end)` ranges,
+/// where a start after the end runs past midnight (`[22, 6]`); any hour not
+/// listed as mid or peak is light load.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Season {
+    pub name: String,
+    pub months: Vec<u32>,
+    pub light_load: f64,
+    pub mid_load: f64,
+    pub peak_load: f64,
+    #[serde(default)]
+    pub mid_hours: Vec<[u32; 2]>,
+    #[serde(default)]
+    pub peak_hours: Vec<[u32; 2]>,
+}
+
+impl Season {
+    fn period_at(&self, hour: u32) -> LoadPeriod {
+        let within = |ranges: &[[u32; 2]]| {
+            ranges.iter().any(|&[start, end]| if start <= end { (start..end).contains(&hour) } else { hour >= start || hour < end })
+        };
+        if within(&self.peak_hours) {
+            LoadPeriod::Peak
+        } else if within(&self.mid_hours) {
+            LoadPeriod::Mid
+        } else {
+            LoadPeriod::Light
+        }
+    }
+
+    fn check_hours(&self) -> Result<(), TariffError> {
+        for &[start, end] in self.mid_hours.iter().chain(&self.peak_hours) {
+            if start > 24 || end > 24 || start == end {
+                return Err(TariffError::Invalid(format!("season {}: hours [{}, {}]", self.name, start, end)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Energy price in effect at a point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rate {
+    pub season: Option<String>,
+    pub period: Option<LoadPeriod>,
+    pub price_per_kwh: f64,
+}
+
+impl Rate {
+    pub fn label(&self) -> String {
+        let period = match self.period {
+            Some(LoadPeriod::Light) => i18n::t("tariff.light_load"),
+            Some(LoadPeriod::Mid) => i18n::t("tariff.mid_load"),
+            Some(LoadPeriod::Peak) => i18n::t("tariff.peak_load"),
+            None => i18n::t("tariff.flat_rate"),
+        };
+        match self.season {
+            Some(ref season) => format!("{} {}", season, period),
+            None => period,
+        }
+    }
+}
+
+/// Prices applied to every session. All amounts are in `currency`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Tariff {
+    pub currency: String,
+    // Used when no season covers the current month
+    pub energy_price_per_kwh: f64,
+    pub session_fee: f64,
+    pub minimum_charge: f64,
+    pub price_per_minute: f64,
+    // Used to turn a percent target into an energy estimate before the vehicle reports its battery
+    pub reference_pack_kwh: f64,
+    // Overstay: charged per started minute once the grace period after charging runs out
+    pub idle_grace_minutes: f64,
+    pub idle_fee_per_minute: f64,
+    pub seasons: Vec<Season>,
+}
+
+impl Default for Tariff {
+    fn default() -> Self {
+        Self {
+            currency: "KRW".to_string(),
+            energy_price_per_kwh: 347.2,
+            session_fee: 0.0,
+            minimum_charge: 0.0,
+            price_per_minute: 0.0,
+            reference_pack_kwh: 64.0,
+            idle_grace_minutes: 10.0,
+            idle_fee_per_minute: 0.0,
+            seasons: Vec::new(),
+        }
+    }
+}
+
+/// Energy drawn while a single rate was in effect
+#[derive(Debug, Clone, PartialEq)]
+pub struct BillSegment {
+    pub rate: Rate,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub energy_kwh: f64,
+}
+
+impl BillSegment {
+    pub fn cost(&self) -> f64 {
+        self.energy_kwh * self.rate.price_per_kwh
+    }
+}
+
+/// Session energy split by the rate in effect when it was delivered
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Usage {
+    segments: Vec<BillSegment>,
+    energy_kwh: f64,
+}
+
+impl Usage {
+    /// Record the session's cumulative energy at `time`. The energy since the
+    /// previous reading is billed at the rate in effect now.
+    pub fn record(&mut self, tariff: &Tariff, time: DateTime<Local>, energy_kwh: f64) {
+        let delta = (energy_kwh - self.energy_kwh).max(0.0);
+        self.energy_kwh = self.energy_kwh.max(energy_kwh);
+
+        let rate = tariff.rate_at(time);
+        match self.segments.last_mut() {
+            Some(segment) if segment.rate == rate => {
+                segment.end = time;
+                segment.energy_kwh += delta;
+            }
+            _ => self.segments.push(BillSegment { rate, start: time, end: time, energy_kwh: delta }),
+        }
+    }
+
+    pub fn energy_kwh(&self) -> f64 {
+        self.energy_kwh
+    }
+}
+
+/// Itemised cost of a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bill {
+    pub energy_kwh: f64,
+    pub duration: Duration,
+    pub segments: Vec<BillSegment>,
+    pub energy_cost: f64,
+    pub time_cost: f64,
+    pub session_fee: f64,
+    pub idle_fee: f64,
+    // Membership plan discount, already taken off `total`
+    pub discount: f64,
+    pub total: f64,
+    // Part of `total` above the payment hold, which the card is not charged for
+    pub uncollected: f64,
+}
+
+impl Bill {
+    /// This bill settled against a payment hold of `hold`; whatever the hold
+    /// does not cover (usually a long idle fee) is kept as `uncollected`
+    pub fn capped_at(&self, hold: f64) -> Bill {
+        Bill { uncollected: (self.total - hold).max(0.0), ..self.clone() }
+    }
+}
+
+/// How long a finished session keeps the connector occupied: runs from the
+/// end of charging until the cable is unplugged
+#[derive(Debug, Clone, Copy)]
+pub struct IdleTimer {
+    started_at: Instant,
+    stopped_at: Option<Instant>,
+}
+
+impl IdleTimer {
+    pub fn start() -> Self {
+        Self { started_at: Instant::now(), stopped_at: None }
+    }
+
+    /// Idle since charging ended at `ended_at`
+    pub fn since(ended_at: DateTime<Utc>) -> Self {
+        let idle = (Utc::now() - ended_at).to_std().unwrap_or_default();
+        let now = Instant::now();
+        Self { started_at: now.checked_sub(idle).unwrap_or(now), stopped_at: None }
+    }
+
+    pub fn stop(&mut self) {
+        self.stopped_at.get_or_insert_with(Instant::now);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped_at.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.stopped_at.unwrap_or_else(Instant::now).duration_since(self.started_at)
+    }
+}
+
+impl Tariff {
+    pub fn load(path: &Path) -> Result<Tariff, TariffError> {
+        let text = std::fs::read_to_string(path)?;
+        let tariff: Tariff = toml::from_str(&text)?;
+        for season in &tariff.seasons {
+            season.check_hours()?;
+        }
+        Ok(tariff)
+    }
+
+    /// Tariff from `path`, falling back to the built-in prices if it is missing or invalid
+    pub fn load_or_default(path: &Path) -> Tariff {
+        match Tariff::load(path) {
+            Ok(tariff) => tariff,
+            Err(err) => {
+                println!("Using default tariff ({}): {}", path.display(), err);
+                Tariff::default()
+            }
+        }
+    }
+
+    pub fn rate_at(&self, time: DateTime<Local>) -> Rate {
+        match self.seasons.iter().find(|season| season.months.contains(&time.month())) {
+            Some(season) => {
+                let period = season.period_at(time.hour());
+                Rate {
+                    season: Some(season.name.clone()),
+                    period: Some(period),
+                    price_per_kwh: match period {
+                        LoadPeriod::Light => season.light_load,
+                        LoadPeriod::Mid => season.mid_load,
+                        LoadPeriod::Peak => season.peak_load,
+                    },
+                }
+            }
+            None => Rate { season: None, period: None, price_per_kwh: self.energy_price_per_kwh },
+        }
+    }
+
+    pub fn bill(&self, usage: &Usage, duration: Duration) -> Bill {
+        let energy_cost: f64 = usage.segments.iter().map(BillSegment::cost).sum();
+        let time_cost = duration.as_secs_f64() / 60.0 * self.price_per_minute;
+        let subtotal = energy_cost + time_cost + self.session_fee;
+        Bill {
+            energy_kwh: usage.energy_kwh(),
+            duration,
+            segments: usage.segments.clone(),
+            energy_cost,
+            time_cost,
+            session_fee: self.session_fee,
+            idle_fee: 0.0,
+            discount: 0.0,
+            total: subtotal.max(self.minimum_charge).round(),
+            uncollected: 0.0,
+        }
+    }
+
+    pub fn idle_grace_period(&self) -> Duration {
+        Duration::from_secs_f64(self.idle_grace_minutes.max(0.0) * 60.0)
+    }
+
+    pub fn idle_fee(&self, idle: Duration) -> f64 {
+        let overstay = idle.saturating_sub(self.idle_grace_period());
+        (overstay.as_secs_f64() / 60.0).ceil() * self.idle_fee_per_minute
+    }
+
+    /// `bill` with the idle fee for `idle` time at the connector
+    pub fn with_idle_fee(&self, bill: &Bill, idle: Duration) -> Bill {
+        let idle_fee = self.idle_fee(idle);
+        Bill { idle_fee, total: bill.total - bill.idle_fee + idle_fee, ..bill.clone() }
+    }
+
+    /// Energy a charge target will take, for quoting before the session starts
+    pub fn estimated_energy_kwh(&self, charge_type: &ChargeType, amount: f32) -> f64 {
+        match charge_type {
+            ChargeType::SpecificWatts(_) => amount as f64,
+            ChargeType::Percent(_) | ChargeType::Full => amount as f64 / 100.0 * self.reference_pack_kwh,
+        }
+    }
+
+    /// Quote for a charge target at the current rate. The time component and
+    /// any rate change during the session are only known once it ends.
+    pub fn estimate(&self, charge_type: &ChargeType, amount: f32) -> Bill {
+        let mut usage = Usage::default();
+        usage.record(self, Local::now(), self.estimated_energy_kwh(charge_type, amount));
+        self.bill(&usage, Duration::ZERO)
+    }
+
+    pub fn format_amount(&self, amount: f64) -> String {
+        format!("{:.0} {}", amount, self.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(month: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, month, 1, hour, minute, 0).unwrap()
+    }
+
+    fn seasonal() -> Tariff {
+        Tariff::load(Path::new(DEFAULT_TARIFF_PATH)).unwrap()
+    }
+
+    #[test]
+    fn rate_follows_season_and_hour() {
+        let tariff = seasonal();
+        let price = |month, hour, minute| tariff.rate_at(at(month, hour, minute)).price_per_kwh;
+        // Summer: mid load up to 11:00, peak from 11:00, light from 22:00
+        assert_eq!(price(7, 10, 59), 347.2);
+        assert_eq!(price(7, 11, 0), 412.6);
+        assert_eq!(price(7, 21, 59), 347.2);
+        assert_eq!(price(7, 22, 0), 250.8);
+        // Same hour, next season
+        assert_eq!(price(8, 11, 0), 412.6);
+        assert_eq!(price(9, 11, 0), 347.2);
+        assert_eq!(tariff.rate_at(at(9, 11, 0)).season.as_deref(), Some("spring/fall"));
+
+        let flat = Tariff { seasons: Vec::new(), ..tariff.clone() };
+        assert_eq!(flat.rate_at(at(7, 11, 0)), Rate { season: None, period: None, price_per_kwh: 347.2 });
+    }
+
+    #[test]
+    fn hours_past_midnight_wrap_around() {
+        let season = Season {
+            name: "night".to_string(),
+            months: (1..=12).collect(),
+            light_load: 100.0,
+            mid_load: 200.0,
+            peak_load: 300.0,
+            mid_hours: Vec::new(),
+            peak_hours: vec![[22, 6]],
+        };
+        assert_eq!(season.period_at(23), LoadPeriod::Peak);
+        assert_eq!(season.period_at(0), LoadPeriod::Peak);
+        assert_eq!(season.period_at(5), LoadPeriod::Peak);
+        assert_eq!(season.period_at(6), LoadPeriod::Light);
+        assert_eq!(season.period_at(21), LoadPeriod::Light);
+        assert!(season.check_hours().is_ok());
+
+        for hours in [[8, 8], [20, 25]] {
+            let bad = Season { peak_hours: vec![hours], ..season.clone() };
+            assert!(matches!(bad.check_hours(), Err(TariffError::Invalid(_))));
+        }
+    }
+
+    #[test]
+    fn bill_adds_rate_segments_time_and_session_fee() {
+        let tariff = Tariff { session_fee: 500.0, price_per_minute: 10.0, ..seasonal() };
+        let mut usage = Usage::default();
+        usage.record(&tariff, at(7, 10, 30), 0.0);
+        usage.record(&tariff, at(7, 10, 59), 10.0);
+        // Everything since the last reading goes at the peak rate now in effect
+        usage.record(&tariff, at(7, 11, 30), 15.0);
+
+        let bill = tariff.bill(&usage, Duration::from_secs(3600));
+        assert_eq!(bill.segments.len(), 2);
+        assert_eq!(bill.segments[0].energy_kwh, 10.0);
+        assert_eq!(bill.segments[1].energy_kwh, 5.0);
+        assert_eq!(bill.energy_kwh, 15.0);
+        assert!((bill.energy_cost - (10.0 * 347.2 + 5.0 * 412.6)).abs() < 1e-9);
+        assert_eq!(bill.time_cost, 600.0);
+        assert_eq!(bill.session_fee, 500.0);
+        assert_eq!(bill.total, (bill.energy_cost + 600.0 + 500.0).round());
+    }
+
+    #[test]
+    fn short_session_pays_the_minimum_charge() {
+        let tariff = Tariff { session_fee: 300.0, minimum_charge: 1000.0, ..Tariff::default() };
+        let mut usage = Usage::default();
+        usage.record(&tariff, at(7, 12, 0), 1.0);
+        let bill = tariff.bill(&usage, Duration::from_secs(120));
+        assert_eq!(bill.total, 1000.0);
+        // Past the minimum the itemised amounts count
+        usage.record(&tariff, at(7, 12, 5), 5.0);
+        assert_eq!(tariff.bill(&usage, Duration::from_secs(420)).total, (5.0_f64 * 347.2 + 300.0).round());
+    }
+
+    #[test]
+    fn idle_fee_starts_after_the_grace_period() {
+        let tariff = Tariff { idle_grace_minutes: 10.0, idle_fee_per_minute: 100.0, ..Tariff::default() };
+        assert_eq!(tariff.idle_fee(Duration::from_secs(10 * 60)), 0.0);
+        // Every started minute counts
+        assert_eq!(tariff.idle_fee(Duration::from_secs(10 * 60 + 1)), 100.0);
+        assert_eq!(tariff.idle_fee(Duration::from_secs(25 * 60)), 1500.0);
+
+        let bill = tariff.bill(&Usage::default(), Duration::ZERO);
+        let idle = tariff.with_idle_fee(&Bill { total: 2000.0, ..bill }, Duration::from_secs(12 * 60));
+        assert_eq!((idle.idle_fee, idle.total), (200.0, 2200.0));
+        // Recomputing replaces the earlier idle fee instead of adding to it
+        assert_eq!(tariff.with_idle_fee(&idle, Duration::from_secs(13 * 60)).total, 2300.0);
+    }
+}
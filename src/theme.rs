@@ -0,0 +1,247 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Deserializer};
+
+pub const DEFAULT_THEME_PATH: &str = "config/theme.toml";
+
+/// Built-in themes, by name
+const BUILTIN_THEMES: [(&str, &str); 2] = [
+    ("dark", include_str!("../themes/dark.toml")),
+    ("high-contrast", include_str!("../themes/high_contrast.toml")),
+];
+
+// 버튼 호버 글로우의 불투명도 (기존 화면들과 동일)
+const GLOW_ALPHA: u8 = 80;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThemeError {
+    #[error("cannot read theme: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid theme: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("unknown base theme `{0}` (expected dark or high-contrast)")]
+    UnknownBase(String),
+}
+
+/// Semantic colors. In TOML: `"#RRGGBB"` or `"#RRGGBBAA"` (alpha not premultiplied).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Colors {
+    /// Screen background behind all content
+    #[serde(deserialize_with = "hex_color")]
+    pub background: Color32,
+    /// Panels and summary boxes drawn over the background
+    #[serde(deserialize_with = "hex_color")]
+    pub surface: Color32,
+    /// Top bar and app bar
+    #[serde(deserialize_with = "hex_color")]
+    pub bar: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub primary: Color32,
+    /// Neutral buttons (back, cancel, unselected options)
+    #[serde(deserialize_with = "hex_color")]
+    pub secondary: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub accent: Color32,
+    /// Confirming actions (start, proceed, selected option)
+    #[serde(deserialize_with = "hex_color")]
+    pub success: Color32,
+    /// Success messages and a full battery
+    #[serde(deserialize_with = "hex_color")]
+    pub success_text: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub info: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub warning: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub danger: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub text: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub text_muted: Color32,
+    #[serde(deserialize_with = "hex_color")]
+    pub text_subtle: Color32,
+    /// Disabled buttons and inactive tracks
+    #[serde(deserialize_with = "hex_color")]
+    pub disabled: Color32,
+}
+
+/// Font sizes in points, before the screen `scale`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FontSizes {
+    pub display: f32,
+    pub headline: f32,
+    pub title: f32,
+    pub heading: f32,
+    pub subheading: f32,
+    pub large: f32,
+    pub body: f32,
+    pub caption: f32,
+    pub small: f32,
+    pub tiny: f32,
+}
+
+/// Corner radii in points
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Radii {
+    pub button: u8,
+    pub small: u8,
+    pub panel: u8,
+    pub progress: u8,
+    /// Hover glow around buttons, multiplied by the screen `scale`
+    pub glow: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub colors: Colors,
+    pub font_sizes: FontSizes,
+    pub radii: Radii,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin("dark").expect("built-in dark theme")
+    }
+}
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let table = builtin_table(name)?;
+        toml::Value::Table(table).try_into().ok()
+    }
+
+    /// Theme file: a built-in `base` (default `dark`) with any tokens overridden,
+    /// so rebranding only needs the colors that change
+    pub fn parse(text: &str) -> Result<Theme, ThemeError> {
+        let overrides: toml::Table = toml::from_str(text)?;
+        let base = overrides.get("base").and_then(toml::Value::as_str).unwrap_or("dark");
+        let mut table = builtin_table(base).ok_or_else(|| ThemeError::UnknownBase(base.to_string()))?;
+        merge(&mut table, overrides);
+        table.remove("base");
+        Ok(toml::Value::Table(table).try_into()?)
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, ThemeError> {
+        Theme::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// `selected` as a built-in theme name or a theme file, else the dark theme
+    pub fn load_or_default(selected: &str) -> Theme {
+        if let Some(theme) = Theme::builtin(selected) {
+            return theme;
+        }
+        let path = Path::new(selected);
+        match Theme::load(path) {
+            Ok(theme) => theme,
+            Err(err) => {
+                println!("Using dark theme ({}): {}", path.display(), err);
+                Theme::default()
+            }
+        }
+    }
+
+    /// Soft glow drawn behind a hovered button of `color`
+    pub fn glow(&self, color: Color32) -> Color32 {
+        Color32::from_rgba_premultiplied(color.r(), color.g(), color.b(), GLOW_ALPHA)
+    }
+
+    pub fn glow_radius(&self, scale: f32) -> egui::CornerRadius {
+        egui::CornerRadius::same((self.radii.glow * scale) as u8)
+    }
+}
+
+fn builtin_table(name: &str) -> Option<toml::Table> {
+    let (_, source) = BUILTIN_THEMES.iter().find(|(builtin, _)| *builtin == name)?;
+    toml::from_str(source).ok()
+}
+
+/// Overlay `overrides` onto `base`, table by table
+fn merge(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => merge(base, overrides),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse_hex(&text).ok_or_else(|| serde::de::Error::custom(format!("`{}` is not a #RRGGBB or #RRGGBBAA color", text)))
+}
+
+fn parse_hex(text: &str) -> Option<Color32> {
+    let hex = text.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color32::from_rgba_unmultiplied(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+static CURRENT: RwLock<Option<Arc<Theme>>> = RwLock::new(None);
+
+/// Theme used by every screen from now on
+pub fn init(theme: Theme) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(theme));
+}
+
+pub fn current() -> Arc<Theme> {
+    if let Some(theme) = CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return theme.clone();
+    }
+    let theme = Arc::new(Theme::default());
+    CURRENT.write().unwrap_or_else(|e| e.into_inner()).get_or_insert(theme).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_themes_parse() {
+        for (name, _) in BUILTIN_THEMES {
+            let theme = Theme::builtin(name).unwrap_or_else(|| panic!("{} theme", name));
+            assert_eq!(theme.name, name);
+        }
+        let dark = Theme::default();
+        assert_eq!(dark.colors.background, Color32::from_rgb(25, 35, 55));
+        assert_eq!(dark.colors.bar, Color32::from_rgba_premultiplied(20, 20, 25, 200));
+    }
+
+    #[test]
+    fn shipped_theme_file_is_the_dark_theme() {
+        assert_eq!(Theme::load(Path::new(DEFAULT_THEME_PATH)).unwrap(), Theme::default());
+    }
+
+    #[test]
+    fn theme_file_overrides_its_base() {
+        let theme = Theme::parse(
+            r##"
+            base = "high-contrast"
+            name = "acme"
+
+            [colors]
+            primary = "#FF6600"
+
+            [radii]
+            button = 4
+            "##,
+        )
+        .unwrap();
+        let base = Theme::builtin("high-contrast").unwrap();
+        assert_eq!(theme.name, "acme");
+        assert_eq!(theme.colors.primary, Color32::from_rgb(255, 102, 0));
+        assert_eq!(theme.colors.background, base.colors.background);
+        assert_eq!(theme.radii.button, 4);
+        assert_eq!(theme.font_sizes, base.font_sizes);
+
+        assert!(matches!(Theme::parse("base = \"neon\""), Err(ThemeError::UnknownBase(_))));
+        assert!(matches!(Theme::parse("[colors]\nprimary = \"orange\""), Err(ThemeError::Parse(_))));
+    }
+}